@@ -0,0 +1,192 @@
+//! Benchmarks of the CPU bound parts of the client: deserializing bridge responses, resolving
+//! rooms, looking resources up in a [`HueState`], and applying events to a cache. They matter on small hardware such as a Raspberry Pi
+//! Zero, where a full fetch of a large home is noticeable.
+//!
+//! Run with `cargo bench`.
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use hueclient::{
+    Device, FakeEventGenerator, HealthCache, HueEvent, HueState, Light, Resource, Room,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const ROOMS: usize = 10;
+const LIGHTS_PER_ROOM: usize = 10;
+
+fn id(kind: u32, n: usize) -> String {
+    format!("{kind:08x}-0000-4000-8000-{n:012x}")
+}
+
+fn light(n: usize) -> Value {
+    json!({
+        "id": id(1, n),
+        "id_v1": format!("/lights/{n}"),
+        "owner": { "rid": id(2, n), "rtype": "device" },
+        "metadata": { "name": format!("Light {n}"), "archetype": "sultan_bulb", "function": "mixed" },
+        "service_id": 0,
+        "on": { "on": n.is_multiple_of(2) },
+        "dimming": { "brightness": 42.5, "min_dim_level": 0.2 },
+        "color_temperature": {
+            "mirek": 366,
+            "mirek_valid": true,
+            "mirek_schema": { "mirek_minimum": 153, "mirek_maximum": 500 }
+        },
+        "color": {
+            "xy": { "x": 0.4573, "y": 0.41 },
+            "gamut": {
+                "red": { "x": 0.6915, "y": 0.3083 },
+                "green": { "x": 0.17, "y": 0.7 },
+                "blue": { "x": 0.1532, "y": 0.0475 }
+            },
+            "gamut_type": "C"
+        },
+        "dynamics": { "status": "none", "speed": 0.0, "speed_valid": false },
+        "mode": "normal",
+        "type": "light"
+    })
+}
+
+fn device(n: usize) -> Value {
+    json!({
+        "id": id(2, n),
+        "metadata": { "name": format!("Light {n}"), "archetype": "sultan_bulb" },
+        "services": [
+            { "rid": id(1, n), "rtype": "light" },
+            { "rid": id(3, n), "rtype": "zigbee_connectivity" },
+            { "rid": id(4, n), "rtype": "entertainment" }
+        ],
+        "type": "device"
+    })
+}
+
+fn room(r: usize) -> Value {
+    let children: Vec<Value> = (0..LIGHTS_PER_ROOM)
+        .map(|l| json!({ "rid": id(2, r * LIGHTS_PER_ROOM + l), "rtype": "device" }))
+        .collect();
+    json!({
+        "id": id(5, r),
+        "metadata": { "name": format!("Room {r}"), "archetype": "living_room" },
+        "children": children,
+        "services": [{ "rid": id(6, r), "rtype": "grouped_light" }],
+        "type": "room"
+    })
+}
+
+fn bench_deserialize_lights(c: &mut Criterion) {
+    let lights: Vec<Value> = (0..ROOMS * LIGHTS_PER_ROOM).map(light).collect();
+    let body = json!({ "errors": [], "data": lights }).to_string();
+    c.bench_function("deserialize 100 lights", |b| {
+        b.iter(|| {
+            let response: HashMap<String, Vec<Light>> =
+                serde_json::from_str(black_box(&body)).unwrap();
+            response
+        })
+    });
+}
+
+fn all_resources() -> Vec<Value> {
+    let count = ROOMS * LIGHTS_PER_ROOM;
+    (0..count)
+        .map(light)
+        .chain((0..count).map(device))
+        .chain((0..ROOMS).map(room))
+        .collect()
+}
+
+fn bench_deserialize_resources(c: &mut Criterion) {
+    let body = json!({ "errors": [], "data": all_resources() }).to_string();
+    // the same steps as Bridge::get_all_resources, which skips the resources that do not parse
+    c.bench_function("deserialize 210 mixed resources", |b| {
+        b.iter(|| {
+            let mut response: HashMap<String, Vec<Value>> =
+                serde_json::from_str(black_box(&body)).unwrap();
+            response
+                .remove("data")
+                .unwrap()
+                .iter()
+                .filter_map(|value| Resource::deserialize(value).ok())
+                .collect::<Vec<_>>()
+        })
+    });
+}
+
+fn bench_state_lookups(c: &mut Criterion) {
+    let resources = all_resources()
+        .iter()
+        .map(|value| Resource::deserialize(value).unwrap())
+        .collect();
+    let mut state = HueState::default();
+    state.record_event(&HueEvent::Add(resources));
+    let lights: Vec<String> = (0..ROOMS * LIGHTS_PER_ROOM).map(|n| id(1, n)).collect();
+    c.bench_function("look 100 lights up in the state", |b| {
+        b.iter(|| {
+            lights
+                .iter()
+                .filter(|id| state.light(black_box(id)).is_some_and(|l| l.on.on))
+                .count()
+        })
+    });
+    c.bench_function("find a room by name in the state", |b| {
+        b.iter(|| {
+            state
+                .rooms()
+                .find(|room| room.metadata.name == black_box("Room 9"))
+                .map(|room| room.id.len())
+        })
+    });
+}
+
+fn bench_resolve_rooms(c: &mut Criterion) {
+    let count = ROOMS * LIGHTS_PER_ROOM;
+    let lights: HashMap<String, Light> = (0..count)
+        .map(|n| (id(1, n), serde_json::from_value(light(n)).unwrap()))
+        .collect();
+    let devices: HashMap<String, Device> = (0..count)
+        .map(|n| (id(2, n), serde_json::from_value(device(n)).unwrap()))
+        .collect();
+    let rooms: Vec<Room> = (0..ROOMS)
+        .map(|r| serde_json::from_value(room(r)).unwrap())
+        .collect();
+    c.bench_function("resolve 10 rooms of 10 lights", |b| {
+        b.iter_batched(
+            || rooms.clone(),
+            |rooms| {
+                rooms
+                    .into_iter()
+                    .map(|room| room.resolve(&devices, &lights))
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_apply_events(c: &mut Criterion) {
+    let mut generator = FakeEventGenerator::new(1);
+    let mut events = generator.morning_routine();
+    events.extend(generator.motion_burst(50));
+    events.extend(generator.button_presses(50));
+    c.bench_function("parse events", |b| {
+        b.iter(|| events.iter().map(|e| e.event()).collect::<Vec<_>>())
+    });
+    let parsed: Vec<HueEvent> = events.iter().map(|e| e.event()).collect();
+    c.bench_function("apply events to the health cache", |b| {
+        let mut cache = HealthCache::default();
+        b.iter(|| {
+            for event in &parsed {
+                cache.record_event(black_box(event));
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_deserialize_lights,
+    bench_deserialize_resources,
+    bench_state_lookups,
+    bench_resolve_rooms,
+    bench_apply_events
+);
+criterion_main!(benches);
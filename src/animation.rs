@@ -0,0 +1,277 @@
+//! Client-side animations, driven by repeatedly sending commands to the bridge.
+use crate::{Bridge, CommandLight, XY};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// What a command or an animation applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LightTarget {
+    /// A single light, by id.
+    Light(String),
+    /// A grouped light service, by id.
+    Group(String),
+}
+
+impl Bridge {
+    /// Sends `command` to a light or a grouped light, depending on `target`. Grouped lights
+    /// only get the fields of `command` they support, see [`crate::CommandGroupedLight`].
+    pub async fn set_target_state(
+        &self,
+        target: &LightTarget,
+        command: &CommandLight,
+    ) -> crate::Result<()> {
+        match target {
+            LightTarget::Light(id) => self.set_light_state(id, command).await,
+            LightTarget::Group(id) => self.set_group_state(id, &command.into()).await,
+        }
+    }
+}
+
+/// A handle on a background task such as an animation. Dropping the handle leaves the task
+/// running, call [`TaskHandle::stop`] or cancel its token to end it.
+#[derive(Debug)]
+pub struct TaskHandle {
+    token: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+impl TaskHandle {
+    pub(crate) fn spawn<F, Fut>(task: F) -> TaskHandle
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let token = CancellationToken::new();
+        TaskHandle {
+            task: tokio::spawn(task(token.clone())),
+            token,
+        }
+    }
+
+    /// Also stops the task when `cancel` is cancelled, typically an application wide shutdown
+    /// token.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let shutdown = hueclient::CancellationToken::new();
+    /// let target = hueclient::LightTarget::Group("3f2d8a7e-1b4c-4d5e-9f6a-7b8c9d0e1f2a".into());
+    /// bridge
+    ///     .start_color_loop(target, hueclient::ColorLoop::default())
+    ///     .with_cancellation(shutdown.clone());
+    /// // later, on shutdown
+    /// shutdown.cancel();
+    /// # })
+    /// ```
+    pub fn with_cancellation(self, cancel: CancellationToken) -> Self {
+        let token = self.token.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = cancel.cancelled() => token.cancel(),
+                _ = token.cancelled() => (),
+            }
+        });
+        self
+    }
+
+    /// Returns the token stopping this task when cancelled. Unlike [`TaskHandle::stop`],
+    /// cancelling it does not wait for the task to end.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Stops the task and waits for its last command to complete.
+    pub async fn stop(self) {
+        self.token.cancel();
+        let _ = self.task.await;
+    }
+
+    /// Returns true if the task is not running anymore.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+/// An emulation of the v1 `colorloop` effect: cycles through a palette of colors, fading from
+/// one to the next.
+#[derive(Debug, Clone)]
+pub struct ColorLoop {
+    /// The colors to cycle through.
+    pub palette: Vec<XY>,
+    /// How long it takes to fade from one color of the palette to the next, at least
+    /// [`ColorLoop::MIN_STEP`].
+    pub step: Duration,
+    /// An optional brightness to set along with each color.
+    pub brightness: Option<f32>,
+}
+
+impl Default for ColorLoop {
+    fn default() -> Self {
+        ColorLoop {
+            palette: vec![
+                XY { x: 0.675, y: 0.322 },
+                XY {
+                    x: 0.4317,
+                    y: 0.5007,
+                },
+                XY {
+                    x: 0.2151,
+                    y: 0.7106,
+                },
+                XY {
+                    x: 0.1607,
+                    y: 0.3423,
+                },
+                XY {
+                    x: 0.1532,
+                    y: 0.0475,
+                },
+                XY {
+                    x: 0.3833,
+                    y: 0.1591,
+                },
+            ],
+            step: Duration::from_secs(5),
+            brightness: None,
+        }
+    }
+}
+
+impl ColorLoop {
+    /// The shortest step, a loop with a shorter one runs at this pace instead. Faster loops
+    /// would exceed the rate of commands a bridge accepts.
+    pub const MIN_STEP: Duration = Duration::from_millis(100);
+
+    pub fn with_palette(self, palette: Vec<XY>) -> Self {
+        Self { palette, ..self }
+    }
+
+    /// Sets the step, raised to [`ColorLoop::MIN_STEP`] if shorter.
+    pub fn with_step(self, step: Duration) -> Self {
+        Self {
+            step: step.max(Self::MIN_STEP),
+            ..self
+        }
+    }
+
+    /// The commands of the loop, one per step, endlessly cycling through the palette.
+    fn commands(&self) -> impl Iterator<Item = CommandLight> + '_ {
+        let step_ms = self
+            .step
+            .max(Self::MIN_STEP)
+            .as_millis()
+            .min(u32::MAX as u128) as u32;
+        self.palette.iter().cycle().map(move |xy| {
+            let command = CommandLight::default()
+                .on()
+                .with_xy(xy.x, xy.y)
+                .with_transition_time(step_ms);
+            match self.brightness {
+                Some(brightness) => command.with_brightness(brightness),
+                None => command,
+            }
+        })
+    }
+
+    pub fn with_brightness(self, brightness: f32) -> Self {
+        Self {
+            brightness: Some(brightness),
+            ..self
+        }
+    }
+}
+
+impl Bridge {
+    /// Starts a color loop on a light or a group, running until the returned handle is stopped.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{ColorLoop, LightTarget};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let color_loop = bridge.start_color_loop(
+    ///     LightTarget::Group("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f".into()),
+    ///     ColorLoop::default().with_step(std::time::Duration::from_secs(2)),
+    /// );
+    /// tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    /// color_loop.stop().await;
+    /// # })
+    /// ```
+    pub fn start_color_loop(&self, target: LightTarget, color_loop: ColorLoop) -> TaskHandle {
+        let bridge = self.clone();
+        TaskHandle::spawn(move |stopped| async move {
+            let step = color_loop.step.max(ColorLoop::MIN_STEP);
+            for command in color_loop.commands() {
+                if let Err(e) = bridge.set_target_state(&target, &command).await {
+                    log::warn!("color loop on {target:?} failed to send command: {e}");
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(step) => (),
+                    _ = stopped.cancelled() => break,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn dropping_the_handle_leaves_the_task_running() {
+        let (done, finished) = tokio::sync::oneshot::channel();
+        let handle = TaskHandle::spawn(|token| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let _ = done.send(token.is_cancelled());
+        });
+        drop(handle);
+        assert_eq!(finished.await, Ok(false));
+    }
+
+    #[test]
+    fn color_loop_cycles_through_the_palette() {
+        let palette = vec![XY { x: 0.1, y: 0.2 }, XY { x: 0.3, y: 0.4 }];
+        let color_loop = ColorLoop::default()
+            .with_palette(palette)
+            .with_step(Duration::from_secs(2))
+            .with_brightness(50.);
+        let commands: Vec<_> = color_loop
+            .commands()
+            .take(3)
+            .map(|command| serde_json::to_value(command).unwrap())
+            .collect();
+        let command = |x: f32, y: f32| {
+            serde_json::json!({
+                "on": {"on": true},
+                "dimming": {"brightness": 50.0},
+                "color": {"xy": {"x": x, "y": y}},
+                "dynamics": {"duration": 2000},
+            })
+        };
+        assert_eq!(
+            commands,
+            vec![command(0.1, 0.2), command(0.3, 0.4), command(0.1, 0.2)]
+        );
+        assert!(ColorLoop::default()
+            .with_palette(vec![])
+            .commands()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn color_loop_steps_are_clamped() {
+        let color_loop = ColorLoop::default().with_step(Duration::from_millis(1));
+        assert_eq!(color_loop.step, ColorLoop::MIN_STEP);
+        // the field is public, so a shorter step set directly is clamped too
+        let color_loop = ColorLoop {
+            step: Duration::ZERO,
+            ..ColorLoop::default()
+        };
+        let command = serde_json::to_value(color_loop.commands().next().unwrap()).unwrap();
+        assert_eq!(command["dynamics"]["duration"], 100);
+    }
+}
@@ -0,0 +1,466 @@
+//! Behavior instances are the automations natively run by the bridge, such as the wake-up and
+//! go-to-sleep routines of the Hue app.
+use crate::{Bridge, BridgeResponseV2, ResourceIdentifier};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BehaviorScriptMetadata {
+    pub name: Option<String>,
+    pub category: Option<String>,
+}
+
+/// A script that behavior instances can be created from. The `configuration_schema` is a JSON
+/// schema describing the `configuration` expected by instances of this script.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BehaviorScript {
+    pub id: String,
+    pub description: String,
+    pub version: String,
+    pub metadata: BehaviorScriptMetadata,
+    pub configuration_schema: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BehaviorInstanceMetadata {
+    pub name: String,
+}
+
+string_enum! {
+    pub enum BehaviorInstanceStatus {
+        Initializing => "initializing",
+        Running => "running",
+        Disabled => "disabled",
+        Errored => "errored",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BehaviorInstance {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub script_id: String,
+    pub enabled: bool,
+    pub status: BehaviorInstanceStatus,
+    pub last_error: Option<String>,
+    pub metadata: BehaviorInstanceMetadata,
+    /// Script specific configuration, as described by the script `configuration_schema`.
+    pub configuration: Value,
+    pub state: Option<Value>,
+}
+
+/// The body of a behavior instance creation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewBehaviorInstance {
+    pub script_id: String,
+    pub enabled: bool,
+    pub metadata: BehaviorInstanceMetadata,
+    pub configuration: Value,
+}
+
+impl NewBehaviorInstance {
+    pub fn new(
+        script_id: impl Into<String>,
+        name: impl Into<String>,
+        configuration: Value,
+    ) -> Self {
+        NewBehaviorInstance {
+            script_id: script_id.into(),
+            enabled: true,
+            metadata: BehaviorInstanceMetadata { name: name.into() },
+            configuration,
+        }
+    }
+
+    pub fn disabled(self) -> Self {
+        Self {
+            enabled: false,
+            ..self
+        }
+    }
+}
+
+/// The id of the script of the wake-up routines of the Hue app, see [`WakeUpConfiguration`].
+pub const WAKE_UP_SCRIPT_ID: &str = "ff8957e3-2eb9-4699-a0c8-ad2cb3ede704";
+/// The id of the script of the go-to-sleep routines of the Hue app, see
+/// [`GoToSleepConfiguration`].
+pub const GO_TO_SLEEP_SCRIPT_ID: &str = "7e571ac6-f363-42e1-809a-4cbf6523ed72";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorDuration {
+    pub seconds: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorTime {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorTimePoint {
+    /// `time` for a fixed time of day.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub time: BehaviorTime,
+}
+
+/// When a routine runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorSchedule {
+    /// The days the routine runs, such as `monday`. Empty to run only once.
+    #[serde(default)]
+    pub recurrence_days: Vec<String>,
+    pub time_point: BehaviorTimePoint,
+}
+
+impl BehaviorSchedule {
+    /// Runs every day at `hour`:`minute`, local time.
+    pub fn daily(hour: u8, minute: u8) -> Self {
+        BehaviorSchedule {
+            recurrence_days: [
+                "monday",
+                "tuesday",
+                "wednesday",
+                "thursday",
+                "friday",
+                "saturday",
+                "sunday",
+            ]
+            .map(String::from)
+            .into(),
+            time_point: BehaviorTimePoint {
+                kind: "time".into(),
+                time: BehaviorTime { hour, minute },
+            },
+        }
+    }
+
+    /// Runs only on `days`, such as `monday`.
+    pub fn on_days(self, days: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            recurrence_days: days.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+}
+
+/// A room or zone a routine applies to, optionally restricted to some of its lights.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorLocation {
+    pub group: ResourceIdentifier,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<ResourceIdentifier>>,
+}
+
+impl BehaviorLocation {
+    /// All the lights of a room or a zone, `rtype` being `room` or `zone`.
+    pub fn group(rid: impl Into<String>, rtype: impl Into<String>) -> Self {
+        BehaviorLocation {
+            group: ResourceIdentifier {
+                rid: rid.into(),
+                rtype: rtype.into(),
+            },
+            items: None,
+        }
+    }
+}
+
+/// The configuration of a wake-up routine, slowly turning lights on before `when`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WakeUpConfiguration {
+    /// The brightness reached at the end of the fade in, in percent.
+    pub end_brightness: f32,
+    pub fade_in_duration: BehaviorDuration,
+    /// Turns the lights off again this long after the end of the fade in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_lights_off_after: Option<BehaviorDuration>,
+    /// `sunrise` or `basic`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    pub when: BehaviorSchedule,
+    #[serde(rename = "where")]
+    pub locations: Vec<BehaviorLocation>,
+}
+
+impl WakeUpConfiguration {
+    /// Fades the lights of `locations` in to full brightness over 30 minutes, to be fully on
+    /// at `when`.
+    pub fn new(when: BehaviorSchedule, locations: Vec<BehaviorLocation>) -> Self {
+        WakeUpConfiguration {
+            end_brightness: 100.,
+            fade_in_duration: BehaviorDuration { seconds: 30 * 60 },
+            turn_lights_off_after: None,
+            style: None,
+            when,
+            locations,
+        }
+    }
+
+    pub fn with_end_brightness(self, end_brightness: f32) -> Self {
+        Self {
+            end_brightness,
+            ..self
+        }
+    }
+
+    pub fn with_fade_in(self, duration: std::time::Duration) -> Self {
+        Self {
+            fade_in_duration: BehaviorDuration {
+                seconds: duration.as_secs() as u32,
+            },
+            ..self
+        }
+    }
+
+    pub fn with_lights_off_after(self, duration: std::time::Duration) -> Self {
+        Self {
+            turn_lights_off_after: Some(BehaviorDuration {
+                seconds: duration.as_secs() as u32,
+            }),
+            ..self
+        }
+    }
+}
+
+/// The configuration of a go-to-sleep routine, slowly dimming lights from `when`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoToSleepConfiguration {
+    /// What the lights do at the end of the fade out, such as `turn_off`.
+    pub end_state: String,
+    pub fade_out_duration: BehaviorDuration,
+    /// `basic`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    pub when: BehaviorSchedule,
+    #[serde(rename = "where")]
+    pub locations: Vec<BehaviorLocation>,
+}
+
+impl GoToSleepConfiguration {
+    /// Fades the lights of `locations` out over 30 minutes from `when`, then turns them off.
+    pub fn new(when: BehaviorSchedule, locations: Vec<BehaviorLocation>) -> Self {
+        GoToSleepConfiguration {
+            end_state: "turn_off".into(),
+            fade_out_duration: BehaviorDuration { seconds: 30 * 60 },
+            style: None,
+            when,
+            locations,
+        }
+    }
+
+    pub fn with_fade_out(self, duration: std::time::Duration) -> Self {
+        Self {
+            fade_out_duration: BehaviorDuration {
+                seconds: duration.as_secs() as u32,
+            },
+            ..self
+        }
+    }
+}
+
+impl NewBehaviorInstance {
+    /// A wake-up routine, as created by the Hue app.
+    pub fn wake_up(
+        name: impl Into<String>,
+        configuration: &WakeUpConfiguration,
+    ) -> crate::Result<Self> {
+        let configuration = serde_json::to_value(configuration)?;
+        Ok(Self::new(WAKE_UP_SCRIPT_ID, name, configuration))
+    }
+
+    /// A go-to-sleep routine, as created by the Hue app.
+    pub fn go_to_sleep(
+        name: impl Into<String>,
+        configuration: &GoToSleepConfiguration,
+    ) -> crate::Result<Self> {
+        let configuration = serde_json::to_value(configuration)?;
+        Ok(Self::new(GO_TO_SLEEP_SCRIPT_ID, name, configuration))
+    }
+}
+
+impl BehaviorInstance {
+    /// The configuration of this instance, if it is a wake-up routine.
+    pub fn wake_up_configuration(&self) -> Option<WakeUpConfiguration> {
+        if self.script_id != WAKE_UP_SCRIPT_ID {
+            return None;
+        }
+        serde_json::from_value(self.configuration.clone()).ok()
+    }
+
+    /// The configuration of this instance, if it is a go-to-sleep routine.
+    pub fn go_to_sleep_configuration(&self) -> Option<GoToSleepConfiguration> {
+        if self.script_id != GO_TO_SLEEP_SCRIPT_ID {
+            return None;
+        }
+        serde_json::from_value(self.configuration.clone()).ok()
+    }
+}
+
+/// A partial update of a behavior instance, only the fields that are set are sent.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommandBehaviorInstance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BehaviorInstanceMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub configuration: Option<Value>,
+}
+
+impl CommandBehaviorInstance {
+    pub fn enable(self) -> Self {
+        Self {
+            enabled: Some(true),
+            ..self
+        }
+    }
+
+    pub fn disable(self) -> Self {
+        Self {
+            enabled: Some(false),
+            ..self
+        }
+    }
+
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            metadata: Some(BehaviorInstanceMetadata { name: name.into() }),
+            ..self
+        }
+    }
+
+    pub fn with_configuration(self, configuration: Value) -> Self {
+        Self {
+            configuration: Some(configuration),
+            ..self
+        }
+    }
+}
+
+impl Bridge {
+    /// Returns a vector of all behavior scripts available on this `Bridge`, sorted by their id's.
+    pub async fn get_all_behavior_scripts(&self) -> crate::Result<Vec<BehaviorScript>> {
+        self.get_resources().await
+    }
+
+    /// Returns a vector of all behavior instances that are registered at this `Bridge`, sorted by
+    /// their id's.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for behavior in &bridge.get_all_behavior_instances().await.unwrap() {
+    ///     println!("{} {:?}", behavior.metadata.name, behavior.status);
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_behavior_instances(&self) -> crate::Result<Vec<BehaviorInstance>> {
+        self.get_resources().await
+    }
+
+    /// Creates a behavior instance and returns its identifier.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{BehaviorLocation, BehaviorSchedule, NewBehaviorInstance, WakeUpConfiguration};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let configuration = WakeUpConfiguration::new(
+    ///     BehaviorSchedule::daily(7, 0).on_days(["monday", "tuesday", "wednesday"]),
+    ///     vec![BehaviorLocation::group("3f4ac4e9-d67a-4dbd-8a16-5ea7e373f281", "room")],
+    /// );
+    /// let instance = NewBehaviorInstance::wake_up("Wake up", &configuration).unwrap();
+    /// let created = bridge.create_behavior_instance(&instance).await.unwrap();
+    /// println!("created {}", created.rid);
+    /// # })
+    /// ```
+    pub async fn create_behavior_instance(
+        &self,
+        instance: &NewBehaviorInstance,
+    ) -> crate::Result<ResourceIdentifier> {
+        let url = format!("https://{}/clip/v2/resource/behavior_instance", self.host());
+        let resp: BridgeResponseV2<ResourceIdentifier> =
+            self.send(self.client.post(&url).json(instance)).await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a created resource"))
+    }
+
+    pub async fn update_behavior_instance(
+        &self,
+        id: &str,
+        command: &CommandBehaviorInstance,
+    ) -> crate::Result<()> {
+        let url = format!(
+            "https://{}/clip/v2/resource/behavior_instance/{}",
+            self.host(),
+            id
+        );
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    pub async fn delete_behavior_instance(&self, id: &str) -> crate::Result<()> {
+        self.delete_resource("behavior_instance", id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn wake_up_configuration() {
+        let configuration = WakeUpConfiguration::new(
+            BehaviorSchedule::daily(6, 45).on_days(["saturday"]),
+            vec![BehaviorLocation::group("r1", "room")],
+        )
+        .with_fade_in(std::time::Duration::from_secs(600));
+        let instance = NewBehaviorInstance::wake_up("Weekend", &configuration).unwrap();
+        assert_eq!(instance.script_id, WAKE_UP_SCRIPT_ID);
+        assert_eq!(
+            instance.configuration,
+            json!({
+                "end_brightness": 100.0,
+                "fade_in_duration": { "seconds": 600 },
+                "when": {
+                    "recurrence_days": ["saturday"],
+                    "time_point": { "type": "time", "time": { "hour": 6, "minute": 45 } }
+                },
+                "where": [{ "group": { "rid": "r1", "rtype": "room" } }]
+            })
+        );
+        let created: BehaviorInstance = serde_json::from_value(json!({
+            "id": "b1",
+            "script_id": WAKE_UP_SCRIPT_ID,
+            "enabled": true,
+            "status": "running",
+            "metadata": { "name": "Weekend" },
+            "configuration": instance.configuration
+        }))
+        .unwrap();
+        assert_eq!(created.wake_up_configuration(), Some(configuration));
+        assert_eq!(created.go_to_sleep_configuration(), None);
+    }
+
+    #[test]
+    fn unknown_statuses() {
+        let instance: BehaviorInstance = serde_json::from_value(json!({
+            "id": "b1",
+            "script_id": GO_TO_SLEEP_SCRIPT_ID,
+            "enabled": true,
+            "status": "updating",
+            "metadata": { "name": "Bedtime" },
+            "configuration": {}
+        }))
+        .unwrap();
+        assert_eq!(
+            instance.status,
+            BehaviorInstanceStatus::Other("updating".into())
+        );
+    }
+}
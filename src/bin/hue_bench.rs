@@ -0,0 +1,94 @@
+extern crate hueclient;
+use futures::StreamExt;
+use hueclient::{CommandLight, EventData, HueEvent};
+use std::env;
+use std::time::{Duration, Instant};
+
+// how long to wait for the event confirming a command
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn print_percentiles(name: &str, samples: &mut [Duration]) {
+    if samples.is_empty() {
+        println!("{name}: no samples");
+        return;
+    }
+    samples.sort();
+    let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+    println!(
+        "{name} ({} samples): min {:?} p50 {:?} p90 {:?} p99 {:?} max {:?}",
+        samples.len(),
+        samples[0],
+        percentile(50),
+        percentile(90),
+        percentile(99),
+        samples[samples.len() - 1],
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    #[cfg(feature = "pretty_env_logger")]
+    pretty_env_logger::init_custom_env("HUE_LOG");
+
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        println!("usage : {:?} <username> <light_id> [count]", args[0]);
+        return;
+    }
+    let count: usize = args.get(3).map_or(20, |count| count.parse().unwrap());
+    let light = args[2].clone();
+    let bridge = hueclient::Bridge::discover_required()
+        .await
+        .with_user(args[1].to_string());
+
+    // timestamp the events as soon as they arrive, independently of the commands
+    let (sender, mut confirmations) = tokio::sync::mpsc::unbounded_channel();
+    let mut events = Box::pin(bridge.events().unwrap());
+    let watched = light.clone();
+    tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            let HueEvent::Update(data) = event else {
+                continue;
+            };
+            for data in data {
+                let EventData::Light(change) = data else {
+                    continue;
+                };
+                if let Some(dimming) = change.dimming.filter(|_| change.id == watched) {
+                    let _ = sender.send((Instant::now(), dimming.brightness));
+                }
+            }
+        }
+    });
+    // give the event stream time to connect
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let mut round_trips = vec![];
+    let mut propagations = vec![];
+    for n in 0..count {
+        let brightness = if n % 2 == 0 { 40. } else { 60. };
+        let command = CommandLight::default().on().with_brightness(brightness);
+        let sent = Instant::now();
+        if let Err(e) = bridge.set_light_state(&light, &command).await {
+            println!("command {n} failed: {e}");
+            continue;
+        }
+        round_trips.push(sent.elapsed());
+        let confirmed = tokio::time::timeout(CONFIRMATION_TIMEOUT, async {
+            while let Some((at, observed)) = confirmations.recv().await {
+                if (observed - brightness).abs() < 1. {
+                    return Some(at);
+                }
+            }
+            None
+        })
+        .await;
+        match confirmed {
+            Ok(Some(at)) => propagations.push(at - sent),
+            _ => println!("command {n} was not confirmed by an event"),
+        }
+    }
+
+    print_percentiles("command round trip", &mut round_trips);
+    print_percentiles("command to event", &mut propagations);
+}
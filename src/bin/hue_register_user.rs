@@ -1,8 +1,7 @@
 extern crate hueclient;
-use hueclient::HueError;
 use std::env;
+use std::time::Duration;
 
-#[allow(while_true)]
 #[allow(dead_code)]
 #[tokio::main]
 async fn main() {
@@ -15,20 +14,17 @@ async fn main() {
     } else {
         let bridge = hueclient::Bridge::discover_required().await;
         println!("posting user {:?} in {:?}", args[1], bridge);
-        loop {
-            let r = bridge.clone().register_application(&args[1]).await;
-            match r {
-                Ok(r) => {
-                    eprint!("done: ");
-                    println!("{}", r.application_key);
-                    break;
-                }
-                Err(HueError::BridgeError { code: 101, .. }) => {
-                    println!("Push the bridge button");
-                    std::thread::sleep(::std::time::Duration::from_secs(5));
-                }
-                Err(e) => panic!("error {e}"),
+        let bridge = bridge
+            .register_with_retries(&args[1], Duration::from_secs(60), |_| {
+                println!("Push the bridge button")
+            })
+            .await;
+        match bridge {
+            Ok(bridge) => {
+                eprint!("done: ");
+                println!("{}", bridge.application_key);
             }
+            Err(e) => panic!("error {e}"),
         }
     }
 }
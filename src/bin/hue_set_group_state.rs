@@ -12,7 +12,7 @@ async fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 4 {
         println!(
-            "usage : {:?} <username> <group_id>,<group_id>,... on|off|[bri]:[hue]:[sat]|[ct]MK:[bri]|[w]K:[bri]|[RR][GG][BB]:[bri]|[x,y]:[bri] [transition_time]",
+            "usage : {:?} <username> <group_id>,<group_id>,... on|off|[bri]:[hue]:[sat]|[ct]MK:[bri]|[w]K:[bri]|[RR][GG][BB]:[bri]|[x,y]:[bri]|<css color name> [transition_time]",
             args[0]
         );
         return;
@@ -22,7 +22,7 @@ async fn main() {
         .with_user(args[1].to_string());
     let group_ids = args[2].clone();
     let groups: Vec<&str> = group_ids.split(",").collect();
-    let parsed = hueclient::parse_command(args);
+    let parsed = hueclient::CommandGroupedLight::from(&hueclient::parse_command(args));
 
     println!("groups: {:?}", groups);
     for l in groups.iter() {
@@ -12,7 +12,7 @@ async fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 4 {
         println!(
-            "usage : {:?} <username> <light_id>,<light_id>,... on|off|[bri]:[hue]:[sat]|[ct]MK:[bri]|[w]K:[bri]|[RR][GG][BB]:[bri]|[x,y]:[bri] [transition_time]",
+            "usage : {:?} <username> <light_id>,<light_id>,... on|off|[bri]:[hue]:[sat]|[ct]MK:[bri]|[w]K:[bri]|[RR][GG][BB]:[bri]|[x,y]:[bri]|<css color name> [transition_time]",
             args[0]
         );
         return;
@@ -1,11 +1,8 @@
-use futures::Stream;
-use futures::StreamExt;
-use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ResourceIdentifier {
     pub rid: String,
     pub rtype: String,
@@ -15,6 +12,7 @@ pub struct ResourceIdentifier {
 pub struct Device {
     pub id: String,
     pub id_v1: Option<String>,
+    pub metadata: Metadata,
     pub services: Vec<ResourceIdentifier>,
 }
 
@@ -56,6 +54,16 @@ pub struct MirekSchema {
     pub mirek_maximum: u16,
 }
 
+impl MirekSchema {
+    /// Brings `mirek` within the range of color temperatures the light supports.
+    pub fn clamp(&self, mirek: u16) -> u16 {
+        mirek.clamp(
+            self.mirek_minimum,
+            self.mirek_maximum.max(self.mirek_minimum),
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColorTemperature {
     pub mirek: Option<u16>,
@@ -63,7 +71,7 @@ pub struct ColorTemperature {
     pub mirek_schema: MirekSchema,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct XY {
     pub x: f32,
     pub y: f32,
@@ -79,6 +87,122 @@ pub struct Gamut {
 pub struct Color {
     pub xy: XY,
     pub gamut: Option<Gamut>,
+    /// `A`, `B` or `C`, see [`Gamut::A`], or `other`.
+    pub gamut_type: Option<String>,
+}
+
+string_enum! {
+    /// A built-in effect of a light.
+    pub enum Effect {
+        NoEffect => "no_effect",
+        Candle => "candle",
+        Fire => "fire",
+        Sparkle => "sparkle",
+        Prism => "prism",
+        Opal => "opal",
+        Glisten => "glisten",
+        Underwater => "underwater",
+        Cosmos => "cosmos",
+        Sunbeam => "sunbeam",
+        Enchant => "enchant",
+    }
+}
+
+/// The effects a light supports, and the one it is playing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Effects {
+    pub status: Effect,
+    #[serde(default)]
+    pub status_values: Vec<Effect>,
+    /// The effects that can be sent with [`CommandLight::with_effect`].
+    #[serde(default)]
+    pub effect_values: Vec<Effect>,
+}
+
+impl Effects {
+    pub fn supports(&self, effect: Effect) -> bool {
+        self.effect_values.contains(&effect)
+    }
+}
+
+string_enum! {
+    /// An effect of a light that plays once over a duration, such as a sunrise.
+    pub enum TimedEffect {
+        NoEffect => "no_effect",
+        Sunrise => "sunrise",
+        Sunset => "sunset",
+    }
+}
+
+/// The timed effects a light supports, and the one it is playing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedEffects {
+    pub status: TimedEffect,
+    #[serde(default)]
+    pub status_values: Vec<TimedEffect>,
+    /// The timed effects that can be sent with [`CommandLight::with_timed_effect`].
+    #[serde(default)]
+    pub effect_values: Vec<TimedEffect>,
+}
+
+impl TimedEffects {
+    pub fn supports(&self, effect: TimedEffect) -> bool {
+        self.effect_values.contains(&effect)
+    }
+}
+
+string_enum! {
+    /// How the colors of a gradient are spread along the light.
+    pub enum GradientMode {
+        InterpolatedPalette => "interpolated_palette",
+        InterpolatedPaletteMirrored => "interpolated_palette_mirrored",
+        RandomPixelated => "random_pixelated",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradientPoint {
+    pub color: CommandLightColor,
+}
+
+/// The gradient of a light with several individually colored segments, such as a lightstrip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gradient {
+    pub points: Vec<GradientPoint>,
+    /// The most points the light accepts in a gradient.
+    pub points_capable: u32,
+    pub mode: Option<GradientMode>,
+    #[serde(default)]
+    pub mode_values: Vec<GradientMode>,
+    /// The number of segments of the light.
+    pub pixel_count: Option<u32>,
+}
+
+string_enum! {
+    /// A signal to draw attention to lights, such as for a door bell or an alarm.
+    pub enum Signal {
+        /// Stops the current signal.
+        NoSignal => "no_signal",
+        /// Toggles the lights on and off.
+        OnOff => "on_off",
+        /// Toggles the lights between off and one color.
+        OnOffColor => "on_off_color",
+        /// Alternates between two colors.
+        Alternating => "alternating",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightSignaling {
+    #[serde(default)]
+    pub signal_values: Vec<Signal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightAlert {
+    /// The alerts the light supports, such as `breathe`.
+    #[serde(default)]
+    pub action_values: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,6 +215,14 @@ pub struct Light {
     pub dimming: Option<Dimming>,
     pub color_temperature: Option<ColorTemperature>,
     pub color: Option<Color>,
+    /// Only set for lights that support gradients.
+    pub gradient: Option<Gradient>,
+    /// Only set for lights that support effects.
+    pub effects: Option<Effects>,
+    /// Only set for lights that support timed effects.
+    pub timed_effects: Option<TimedEffects>,
+    pub alert: Option<LightAlert>,
+    pub signaling: Option<LightSignaling>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,20 +231,98 @@ pub struct Metadata {
     pub archetype: String,
 }
 
+string_enum! {
+    /// The type of a room or a zone, which sets the icon shown in the Hue app. Values this
+    /// library does not know about, including the documented `other`, are kept as
+    /// [`RoomArchetype::Other`].
+    pub enum RoomArchetype {
+        LivingRoom => "living_room",
+        Kitchen => "kitchen",
+        Dining => "dining",
+        Bedroom => "bedroom",
+        KidsBedroom => "kids_bedroom",
+        Bathroom => "bathroom",
+        Nursery => "nursery",
+        Recreation => "recreation",
+        Office => "office",
+        Gym => "gym",
+        Hallway => "hallway",
+        Toilet => "toilet",
+        FrontDoor => "front_door",
+        Garage => "garage",
+        Terrace => "terrace",
+        Garden => "garden",
+        Driveway => "driveway",
+        Carport => "carport",
+        Home => "home",
+        Downstairs => "downstairs",
+        Upstairs => "upstairs",
+        TopFloor => "top_floor",
+        Attic => "attic",
+        GuestRoom => "guest_room",
+        Staircase => "staircase",
+        Lounge => "lounge",
+        ManCave => "man_cave",
+        Computer => "computer",
+        Studio => "studio",
+        Music => "music",
+        Tv => "tv",
+        Reading => "reading",
+        Closet => "closet",
+        Storage => "storage",
+        LaundryRoom => "laundry_room",
+        Balcony => "balcony",
+        Porch => "porch",
+        Barbecue => "barbecue",
+        Pool => "pool",
+    }
+}
+
+/// The metadata of a room or a zone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMetadata {
+    pub name: String,
+    pub archetype: RoomArchetype,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
     pub id: String,
     pub id_v1: Option<String>,
-    pub metadata: Metadata,
+    pub metadata: GroupMetadata,
     pub children: Vec<ResourceIdentifier>,
     pub services: Vec<ResourceIdentifier>,
 }
 
+impl Room {
+    /// Replaces the devices of this room by their lights, as returned by
+    /// [`Bridge::index_all_devices`] and [`Bridge::index_all_lights`].
+    pub fn resolve(
+        self,
+        devices: &HashMap<String, Device>,
+        lights: &HashMap<String, Light>,
+    ) -> ResolvedRoom {
+        ResolvedRoom {
+            children: self
+                .children
+                .iter()
+                .filter_map(|child| devices.get(&child.rid))
+                .flat_map(Device::get_lights)
+                .filter_map(|light_id| lights.get(light_id).cloned())
+                .collect(),
+            metadata: self.metadata,
+            id_v1: self.id_v1,
+            id: self.id,
+            services: self.services,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedRoom {
     pub id: String,
     pub id_v1: Option<String>,
-    pub metadata: Metadata,
+    pub metadata: GroupMetadata,
     pub children: Vec<Light>,
     pub services: Vec<ResourceIdentifier>,
 }
@@ -121,40 +331,264 @@ pub struct ResolvedRoom {
 pub struct Zone {
     pub id: String,
     pub id_v1: Option<String>,
-    pub metadata: Metadata,
+    pub metadata: GroupMetadata,
     pub children: Vec<ResourceIdentifier>,
     pub services: Vec<ResourceIdentifier>,
 }
 
+impl Zone {
+    /// Replaces the children of this zone by their lights, as returned by
+    /// [`Bridge::index_all_lights`].
+    pub fn resolve(self, lights: &HashMap<String, Light>) -> ResolvedZone {
+        ResolvedZone {
+            children: self
+                .children
+                .iter()
+                .filter_map(|child| lights.get(&child.rid).cloned())
+                .collect(),
+            metadata: self.metadata,
+            id_v1: self.id_v1,
+            id: self.id,
+            services: self.services,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedZone {
     pub id: String,
     pub id_v1: Option<String>,
-    pub metadata: Metadata,
+    pub metadata: GroupMetadata,
     pub children: Vec<Light>,
     pub services: Vec<ResourceIdentifier>,
 }
 
+/// The light service of a room or a zone, controlling all its lights at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedLight {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub owner: ResourceIdentifier,
+    pub on: Option<On>,
+    pub dimming: Option<CommandLightDimming>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntertainmentSegment {
+    pub start: u32,
+    pub length: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntertainmentSegments {
+    pub configurable: bool,
+    pub max_segments: u32,
+    pub segments: Vec<EntertainmentSegment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entertainment {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub owner: ResourceIdentifier,
+    /// Whether the device can render colors sent over an entertainment stream.
+    pub renderer: bool,
+    pub renderer_reference: Option<ResourceIdentifier>,
+    /// Whether the device can act as a proxy for an entertainment stream.
+    pub proxy: bool,
+    pub equalizer: Option<bool>,
+    pub max_streams: Option<u32>,
+    pub segments: Option<EntertainmentSegments>,
+}
+
+impl Entertainment {
+    /// Returns the id of the light rendering this entertainment service, if any.
+    pub fn get_light(&self) -> Option<&str> {
+        self.renderer_reference
+            .as_ref()
+            .filter(|reference| reference.rtype == "light")
+            .map(|reference| reference.rid.as_str())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SceneMetadata {
     pub name: String,
 }
 
+/// The state a scene sets on one of its lights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneAction {
+    pub target: ResourceIdentifier,
+    pub action: CommandLight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaletteColor {
+    pub color: CommandLightColor,
+    pub dimming: Option<CommandLightDimming>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaletteColorTemperature {
+    pub color_temperature: CommandLightColorTemperature,
+    pub dimming: Option<CommandLightDimming>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaletteEffect {
+    pub effect: Effect,
+}
+
+/// The colors, brightnesses and effects a scene cycles through when recalled dynamically.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScenePalette {
+    #[serde(default)]
+    pub color: Vec<PaletteColor>,
+    #[serde(default)]
+    pub dimming: Vec<CommandLightDimming>,
+    #[serde(default)]
+    pub color_temperature: Vec<PaletteColorTemperature>,
+    #[serde(default)]
+    pub effects: Vec<PaletteEffect>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Scene {
     pub id: String,
     pub id_v1: Option<String>,
     pub metadata: SceneMetadata,
+    /// The room or zone this scene belongs to.
+    pub group: ResourceIdentifier,
+    #[serde(default)]
+    pub actions: Vec<SceneAction>,
+    pub palette: Option<ScenePalette>,
+    /// How fast the palette of the scene cycles when recalled dynamically, between 0 and 1.
+    pub speed: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SceneRecall {
+    /// `active`, `dynamic_palette` or `static`.
     pub action: String,
+    /// The transition time, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+    /// Overrides the brightness of all the lights of the scene.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimming: Option<CommandLightDimming>,
 }
 
+/// A scene to create with [`Bridge::create_scene`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewScene {
+    pub metadata: SceneMetadata,
+    /// The room or zone the scene belongs to.
+    pub group: ResourceIdentifier,
+    pub actions: Vec<SceneAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub palette: Option<ScenePalette>,
+}
+
+impl NewScene {
+    /// A new scene for a room or a zone, with the given actions.
+    pub fn new(
+        name: impl Into<String>,
+        group: ResourceIdentifier,
+        actions: Vec<SceneAction>,
+    ) -> Self {
+        NewScene {
+            metadata: SceneMetadata { name: name.into() },
+            group,
+            actions,
+            palette: None,
+        }
+    }
+
+    /// Makes the scene dynamic, cycling through `palette` when recalled dynamically.
+    pub fn with_palette(self, palette: ScenePalette) -> Self {
+        Self {
+            palette: Some(palette),
+            ..self
+        }
+    }
+}
+
+/// A change to a scene, see [`Bridge::update_scene`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CommandScene {
-    recall: SceneRecall,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recall: Option<SceneRecall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<SceneMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actions: Option<Vec<SceneAction>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    palette: Option<ScenePalette>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speed: Option<f32>,
+}
+
+impl CommandScene {
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            metadata: Some(SceneMetadata { name: name.into() }),
+            ..self
+        }
+    }
+
+    /// Replaces all the actions of the scene.
+    pub fn with_actions(self, actions: Vec<SceneAction>) -> Self {
+        Self {
+            actions: Some(actions),
+            ..self
+        }
+    }
+
+    /// Replaces the palette of the scene.
+    pub fn with_palette(self, palette: ScenePalette) -> Self {
+        Self {
+            palette: Some(palette),
+            ..self
+        }
+    }
+
+    /// Sets how fast the palette cycles when the scene is recalled dynamically, between 0 and 1.
+    pub fn with_speed(self, speed: f32) -> Self {
+        Self {
+            speed: Some(speed.clamp(0., 1.)),
+            ..self
+        }
+    }
+
+    /// Recalls the scene, fading in over `duration`, with all its lights at `brightness` instead
+    /// of the brightness of the scene when it is set.
+    pub fn with_recall(
+        self,
+        duration: Option<std::time::Duration>,
+        brightness: Option<f32>,
+    ) -> Self {
+        Self {
+            recall: Some(SceneRecall {
+                action: "active".to_string(),
+                duration: duration.map(|d| d.as_millis() as u32),
+                dimming: brightness.map(|brightness| CommandLightDimming { brightness }),
+            }),
+            ..self
+        }
+    }
+
+    /// Recalls the scene, cycling through its palette, fading in over `duration`.
+    pub fn with_dynamic_recall(self, duration: Option<std::time::Duration>) -> Self {
+        Self {
+            recall: Some(SceneRecall {
+                action: "dynamic_palette".to_string(),
+                duration: duration.map(|d| d.as_millis() as u32),
+                dimming: None,
+            }),
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,12 +606,75 @@ pub struct CommandLightColor {
     pub xy: XY,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLightAlert {
+    /// Only `breathe` is supported for now.
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLightEffects {
+    pub effect: Effect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLightTimedEffects {
+    pub effect: TimedEffect,
+    /// The duration of the effect, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+}
+
+/// The direction of a relative change of a light.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeltaAction {
+    Up,
+    Down,
+    /// Stops a change that is still in progress, such as a long press on a dimmer.
+    Stop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLightDimmingDelta {
+    pub action: DeltaAction,
+    /// The change of brightness, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness_delta: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLightColorTemperatureDelta {
+    pub action: DeltaAction,
+    /// The change of color temperature, in mirek.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirek_delta: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLightSignaling {
+    pub signal: Signal,
+    /// How long to signal for, in milliseconds.
+    pub duration: u32,
+    #[serde(rename = "color", skip_serializing_if = "Vec::is_empty", default)]
+    pub colors: Vec<CommandLightColor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLightGradient {
+    pub points: Vec<GradientPoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<GradientMode>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CommandLightDynamics {
+    /// The duration of the transition, in milliseconds.
     #[serde(skip_serializing_if = "Option::is_none")]
-    duration: Option<u32>,
+    pub duration: Option<u32>,
+    /// How fast a dynamic scene plays on the light, between 0 and 1.
     #[serde(skip_serializing_if = "Option::is_none")]
-    speed: Option<f32>,
+    pub speed: Option<f32>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CommandLight {
@@ -191,6 +688,20 @@ pub struct CommandLight {
     pub color: Option<CommandLightColor>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dynamics: Option<CommandLightDynamics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<CommandLightAlert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gradient: Option<CommandLightGradient>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effects: Option<CommandLightEffects>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timed_effects: Option<CommandLightTimedEffects>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signaling: Option<CommandLightSignaling>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimming_delta: Option<CommandLightDimmingDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_temperature_delta: Option<CommandLightColorTemperatureDelta>,
 }
 
 impl CommandLight {
@@ -214,68 +725,553 @@ impl CommandLight {
         }
     }
 
-    pub fn with_mirek(self, mirek: u16) -> Self {
+    /// Changes the brightness relatively to the current one, by up to 100 percent. With
+    /// [`DeltaAction::Stop`], the delta is ignored and a change in progress is stopped.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandLight, DeltaAction};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let dimmer = CommandLight::default().with_dimming_delta(DeltaAction::Down, 10.);
+    /// bridge
+    ///     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &dimmer)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_dimming_delta(self, action: DeltaAction, brightness_delta: f32) -> Self {
+        let brightness_delta =
+            (action != DeltaAction::Stop).then(|| brightness_delta.clamp(0., 100.));
         Self {
-            color_temperature: Some(CommandLightColorTemperature { mirek }),
+            dimming_delta: Some(CommandLightDimmingDelta {
+                action,
+                brightness_delta,
+            }),
             ..self
         }
     }
 
-    pub fn with_xy(self, x: f32, y: f32) -> Self {
+    /// Changes the color temperature relatively to the current one, towards warmer colors
+    /// with [`DeltaAction::Up`]. With [`DeltaAction::Stop`], the delta is ignored.
+    pub fn with_color_temperature_delta(self, action: DeltaAction, mirek_delta: u16) -> Self {
+        let mirek_delta = (action != DeltaAction::Stop).then_some(mirek_delta);
         Self {
-            color: Some(CommandLightColor { xy: XY { x, y } }),
+            color_temperature_delta: Some(CommandLightColorTemperatureDelta {
+                action,
+                mirek_delta,
+            }),
             ..self
         }
     }
 
-    pub fn with_transition_time(self, ms: u32) -> Self {
+    /// Makes the light breathe a few times, for instance as a notification.
+    pub fn breathe(self) -> Self {
+        self.with_alert("breathe")
+    }
+
+    /// Plays an alert, one of the [`LightAlert::action_values`] of the light.
+    pub fn with_alert(self, action: impl Into<String>) -> Self {
         Self {
-            dynamics: Some(CommandLightDynamics {
-                duration: Some(ms),
-                ..Default::default()
+            alert: Some(CommandLightAlert {
+                action: action.into(),
             }),
             ..self
         }
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EventColorTemperature {
-    pub mirek: Option<u16>,
-    pub mirek_valid: bool,
-}
+    /// Signals for `duration`, with the `(x, y)` colors the signal needs: one for
+    /// [`Signal::OnOffColor`] and two for [`Signal::Alternating`].
+    pub fn with_signaling(
+        self,
+        signal: Signal,
+        duration: std::time::Duration,
+        colors: impl IntoIterator<Item = (f32, f32)>,
+    ) -> Self {
+        let colors = colors
+            .into_iter()
+            .map(|(x, y)| CommandLightColor { xy: XY { x, y } })
+            .collect();
+        Self {
+            signaling: Some(CommandLightSignaling {
+                signal,
+                duration: duration.as_millis() as u32,
+                colors,
+            }),
+            ..self
+        }
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Event {
-    pub id: String,
-    pub id_v1: Option<String>,
-    pub on: Option<On>,
-    pub dimming: Option<CommandLightDimming>,
-    pub color_temperature: Option<EventColorTemperature>,
-    pub color: Option<CommandLightColor>,
-}
+    pub fn with_mirek(self, mirek: u16) -> Self {
+        Self {
+            color_temperature: Some(CommandLightColorTemperature { mirek }),
+            ..self
+        }
+    }
 
-/// An unauthenticated bridge is a bridge that has not
-#[derive(Debug, Clone)]
-pub struct UnauthBridge {
-    /// The IP-address of the bridge.
-    pub ip: std::net::IpAddr,
-    client: reqwest::Client,
-}
+    /// Same as [`CommandLight::with_mirek`], but brings `mirek` within the
+    /// [`MirekSchema`] of `light` first, since the bridge rejects color temperatures out of it.
+    /// Lights without color temperature get `mirek` unchanged.
+    pub fn with_mirek_clamped(self, light: &Light, mirek: u16) -> Self {
+        let mirek = match &light.color_temperature {
+            Some(ct) => ct.mirek_schema.clamp(mirek),
+            None => mirek,
+        };
+        self.with_mirek(mirek)
+    }
 
-impl UnauthBridge {
-    /// Consumes the bridge and returns a new one with a configured username.
+    pub fn with_xy(self, x: f32, y: f32) -> Self {
+        Self {
+            color: Some(CommandLightColor { xy: XY { x, y } }),
+            ..self
+        }
+    }
+
+    /// Colors a light with several segments with a gradient going through the `(x, y)` points,
+    /// at most [`Gradient::points_capable`] of them. The bridge picks the mode when it is `None`.
     /// ### Example
     /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandLight, GradientMode};
     /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
-    ///     .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
-    /// ```
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let sunset = [(0.6, 0.35), (0.5, 0.4), (0.3, 0.15)];
+    /// let command = CommandLight::default()
+    ///     .on()
+    ///     .with_gradient(sunset, Some(GradientMode::InterpolatedPaletteMirrored));
+    /// bridge
+    ///     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &command)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_gradient(
+        self,
+        points: impl IntoIterator<Item = (f32, f32)>,
+        mode: Option<GradientMode>,
+    ) -> Self {
+        let points = points
+            .into_iter()
+            .map(|(x, y)| GradientPoint {
+                color: CommandLightColor { xy: XY { x, y } },
+            })
+            .collect();
+        Self {
+            gradient: Some(CommandLightGradient { points, mode }),
+            ..self
+        }
+    }
+
+    /// Plays a built-in effect, or stops it with [`Effect::NoEffect`]. Check
+    /// [`Effects::supports`] first, lights reject the effects they do not have.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandLight, Effect};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for light in bridge.get_all_lights().await.unwrap() {
+    ///     if light.effects.is_some_and(|e| e.supports(Effect::Candle)) {
+    ///         let command = CommandLight::default().on().with_effect(Effect::Candle);
+    ///         bridge.set_light_state(&light.id, &command).await.unwrap();
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub fn with_effect(self, effect: Effect) -> Self {
+        Self {
+            effects: Some(CommandLightEffects { effect }),
+            ..self
+        }
+    }
+
+    /// Plays a timed effect over `duration`, or the default duration of the light when `None`.
+    /// The light fades by itself, without further commands.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandLight, TimedEffect};
+    /// use std::time::Duration;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let sunrise = CommandLight::default()
+    ///     .with_timed_effect(TimedEffect::Sunrise, Some(Duration::from_secs(30 * 60)));
+    /// bridge
+    ///     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &sunrise)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_timed_effect(
+        self,
+        effect: TimedEffect,
+        duration: Option<std::time::Duration>,
+    ) -> Self {
+        Self {
+            timed_effects: Some(CommandLightTimedEffects {
+                effect,
+                duration: duration.map(|d| d.as_millis() as u32),
+            }),
+            ..self
+        }
+    }
+
+    pub fn with_transition_time(self, ms: u32) -> Self {
+        Self {
+            dynamics: Some(CommandLightDynamics {
+                duration: Some(ms),
+                ..self.dynamics.unwrap_or_default()
+            }),
+            ..self
+        }
+    }
+
+    /// Sets how fast a dynamic scene plays on the light, between 0 and 1. It can be combined
+    /// with [`CommandLight::with_transition_time`].
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::CommandLight;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let command = CommandLight::default()
+    ///     .with_transition_time(2000)
+    ///     .with_dynamics_speed(0.8);
+    /// bridge
+    ///     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &command)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_dynamics_speed(self, speed: f32) -> Self {
+        Self {
+            dynamics: Some(CommandLightDynamics {
+                speed: Some(speed.clamp(0., 1.)),
+                ..self.dynamics.unwrap_or_default()
+            }),
+            ..self
+        }
+    }
+
+    /// The command reproducing the current state of a light: on, brightness, and color or color
+    /// temperature depending on the color mode the light is in, and gradient. It is a starting
+    /// point to change one property while keeping the others.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::CommandLight;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let light = bridge
+    ///     .get_light("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f")
+    ///     .await
+    ///     .unwrap();
+    /// let brighter = CommandLight::from_light(&light).with_brightness(100.);
+    /// bridge.set_light_state(&light.id, &brighter).await.unwrap();
+    /// # })
+    /// ```
+    pub fn from_light(light: &Light) -> Self {
+        let mut command = CommandLight {
+            on: Some(light.on.clone()),
+            dimming: light.dimming.as_ref().map(|dimming| CommandLightDimming {
+                brightness: dimming.brightness,
+            }),
+            ..CommandLight::default()
+        };
+        match (&light.color_temperature, &light.color) {
+            (
+                Some(ColorTemperature {
+                    mirek: Some(mirek),
+                    mirek_valid: true,
+                    ..
+                }),
+                _,
+            ) => command.color_temperature = Some(CommandLightColorTemperature { mirek: *mirek }),
+            (_, Some(color)) => {
+                command.color = Some(CommandLightColor {
+                    xy: color.xy.clone(),
+                })
+            }
+            _ => (),
+        }
+        if let Some(gradient) = light.gradient.as_ref().filter(|g| !g.points.is_empty()) {
+            command.gradient = Some(CommandLightGradient {
+                points: gradient.points.clone(),
+                mode: gradient.mode.clone(),
+            });
+        }
+        command
+    }
+
+    /// Overlays the fields `other` sets onto this command, for instance a temporary override
+    /// onto the state of a scene. A color or a color temperature set by `other` replaces the
+    /// color mode of this command, since a light can only be in one of them.
+    /// ### Example
+    /// ```
+    /// use hueclient::CommandLight;
+    /// let base = CommandLight::default().on().with_brightness(40.).with_mirek(370);
+    /// let alert = CommandLight::default().with_xy(0.675, 0.322);
+    /// let merged = base.merge(alert);
+    /// assert_eq!(merged.dimming.unwrap().brightness, 40.);
+    /// assert!(merged.color_temperature.is_none());
+    /// assert!(merged.color.is_some());
+    /// ```
+    pub fn merge(self, other: CommandLight) -> Self {
+        let color_mode_changed = other.color.is_some() || other.color_temperature.is_some();
+        let (color, color_temperature) = if color_mode_changed {
+            (other.color, other.color_temperature)
+        } else {
+            (self.color, self.color_temperature)
+        };
+        CommandLight {
+            on: other.on.or(self.on),
+            dimming: other.dimming.or(self.dimming),
+            color_temperature,
+            color,
+            dynamics: other.dynamics.or(self.dynamics),
+            alert: other.alert.or(self.alert),
+            gradient: other.gradient.or(self.gradient),
+            effects: other.effects.or(self.effects),
+            timed_effects: other.timed_effects.or(self.timed_effects),
+            signaling: other.signaling.or(self.signaling),
+            dimming_delta: other.dimming_delta.or(self.dimming_delta),
+            color_temperature_delta: other
+                .color_temperature_delta
+                .or(self.color_temperature_delta),
+        }
+    }
+}
+
+impl From<&Light> for CommandLight {
+    fn from(light: &Light) -> Self {
+        CommandLight::from_light(light)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandGroupedLightDynamics {
+    /// The duration of the transition, in milliseconds.
+    pub duration: u32,
+}
+
+/// A command for all the lights of a room or a zone, sent with [`Bridge::set_group_state`].
+/// Grouped lights support fewer fields than lights: there are no gradients or effects, and
+/// dynamics only have a duration.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommandGroupedLight {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on: Option<On>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimming: Option<CommandLightDimming>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimming_delta: Option<CommandLightDimmingDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_temperature: Option<CommandLightColorTemperature>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_temperature_delta: Option<CommandLightColorTemperatureDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<CommandLightColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<CommandLightAlert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signaling: Option<CommandLightSignaling>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamics: Option<CommandGroupedLightDynamics>,
+}
+
+impl CommandGroupedLight {
+    pub fn on(self) -> Self {
+        Self {
+            on: Some(On { on: true }),
+            ..self
+        }
+    }
+    pub fn off(self) -> Self {
+        Self {
+            on: Some(On { on: false }),
+            ..self
+        }
+    }
+
+    pub fn with_brightness(self, brightness: f32) -> Self {
+        Self {
+            dimming: Some(CommandLightDimming { brightness }),
+            ..self
+        }
+    }
+
+    /// Same as [`CommandLight::with_dimming_delta`], for all the lights of the group.
+    pub fn with_dimming_delta(self, action: DeltaAction, brightness_delta: f32) -> Self {
+        Self {
+            dimming_delta: CommandLight::default()
+                .with_dimming_delta(action, brightness_delta)
+                .dimming_delta,
+            ..self
+        }
+    }
+
+    /// Same as [`CommandLight::with_color_temperature_delta`], for all the lights of the group.
+    pub fn with_color_temperature_delta(self, action: DeltaAction, mirek_delta: u16) -> Self {
+        Self {
+            color_temperature_delta: CommandLight::default()
+                .with_color_temperature_delta(action, mirek_delta)
+                .color_temperature_delta,
+            ..self
+        }
+    }
+
+    pub fn with_mirek(self, mirek: u16) -> Self {
+        Self {
+            color_temperature: Some(CommandLightColorTemperature { mirek }),
+            ..self
+        }
+    }
+
+    pub fn with_xy(self, x: f32, y: f32) -> Self {
+        Self {
+            color: Some(CommandLightColor { xy: XY { x, y } }),
+            ..self
+        }
+    }
+
+    /// Makes all the lights of the group breathe a few times, for instance as a notification.
+    pub fn breathe(self) -> Self {
+        self.with_alert("breathe")
+    }
+
+    /// Plays an alert on all the lights of the group.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::CommandGroupedLight;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let alert = CommandGroupedLight::default().with_alert("breathe");
+    /// bridge
+    ///     .set_group_state("5b6c7d8e-9f0a-4b1c-8d2e-3f4a5b6c7d8e", &alert)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_alert(self, action: impl Into<String>) -> Self {
+        Self {
+            alert: Some(CommandLightAlert {
+                action: action.into(),
+            }),
+            ..self
+        }
+    }
+
+    /// Makes all the lights of the group signal together, see [`CommandLight::with_signaling`].
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandGroupedLight, Signal};
+    /// use std::time::Duration;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let alarm = CommandGroupedLight::default().with_signaling(
+    ///     Signal::Alternating,
+    ///     Duration::from_secs(60),
+    ///     [(0.675, 0.322), (0.167, 0.04)],
+    /// );
+    /// bridge
+    ///     .set_group_state("5b6c7d8e-9f0a-4b1c-8d2e-3f4a5b6c7d8e", &alarm)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_signaling(
+        self,
+        signal: Signal,
+        duration: std::time::Duration,
+        colors: impl IntoIterator<Item = (f32, f32)>,
+    ) -> Self {
+        Self {
+            signaling: CommandLight::default()
+                .with_signaling(signal, duration, colors)
+                .signaling,
+            ..self
+        }
+    }
+
+    pub fn with_transition_time(self, ms: u32) -> Self {
+        Self {
+            dynamics: Some(CommandGroupedLightDynamics { duration: ms }),
+            ..self
+        }
+    }
+}
+
+/// Keeps the fields of a light command that grouped lights support, and drops the others.
+impl From<&CommandLight> for CommandGroupedLight {
+    fn from(command: &CommandLight) -> Self {
+        CommandGroupedLight {
+            on: command.on.clone(),
+            dimming: command.dimming.clone(),
+            dimming_delta: command.dimming_delta.clone(),
+            color_temperature: command.color_temperature.clone(),
+            color_temperature_delta: command.color_temperature_delta.clone(),
+            color: command.color.clone(),
+            alert: command.alert.clone(),
+            signaling: command.signaling.clone(),
+            dynamics: command
+                .dynamics
+                .as_ref()
+                .and_then(|d| d.duration)
+                .map(|duration| CommandGroupedLightDynamics { duration }),
+        }
+    }
+}
+
+/// An IP address formatted as the host of a URL, with brackets around IPv6 addresses.
+pub(crate) struct UrlHost(std::net::IpAddr);
+
+impl std::fmt::Display for UrlHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            std::net::IpAddr::V4(ip) => write!(f, "{ip}"),
+            std::net::IpAddr::V6(ip) => write!(f, "[{ip}]"),
+        }
+    }
+}
+
+// how often registration is retried while waiting for the link button
+const LINK_BUTTON_POLL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// An unauthenticated bridge is a bridge that has not
+#[derive(Debug, Clone)]
+pub struct UnauthBridge {
+    /// The IP-address of the bridge.
+    pub ip: std::net::IpAddr,
+    pub(crate) client: reqwest::Client,
+    headers: reqwest::header::HeaderMap,
+    pub(crate) tls: crate::tls::TlsOptions,
+}
+
+impl UnauthBridge {
+    pub(crate) fn host(&self) -> UrlHost {
+        UrlHost(self.ip)
+    }
+
+    /// Consumes the bridge and returns a new one with a configured username.
+    /// ### Example
+    /// ```no_run
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///     .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// ```
     pub fn with_user(self, username: impl Into<String>) -> Bridge {
         let username = username.into();
         Bridge {
             ip: self.ip,
-            client: create_reqwest_client(Some(&username)),
+            client: create_reqwest_client(Some(&username), &self.headers, &self.tls),
+            headers: self.headers,
+            tls: self.tls,
             application_key: username,
+            body_logging: Default::default(),
+            #[cfg(feature = "fault-injection")]
+            faults: Default::default(),
+            version: None,
         }
     }
 
@@ -292,19 +1288,96 @@ impl UnauthBridge {
     /// # })
     /// ```
     pub async fn register_application(self, name: &str) -> crate::Result<Bridge> {
+        Ok(self.register(name, false).await?.0)
+    }
+
+    /// Same as [`UnauthBridge::register_application`], but retries while the link button of the
+    /// bridge has not been pressed, for up to `timeout`. `on_waiting` is called before each retry
+    /// with the time left, typically to ask the user to press the button.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use std::time::Duration;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///     .register_with_retries("mylaptop", Duration::from_secs(30), |left| {
+    ///         println!("press the link button of the bridge, {}s left", left.as_secs())
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// println!("the application key is {}", bridge.application_key);
+    /// # })
+    /// ```
+    pub async fn register_with_retries(
+        self,
+        name: &str,
+        timeout: std::time::Duration,
+        mut on_waiting: impl FnMut(std::time::Duration),
+    ) -> crate::Result<Bridge> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            match self.clone().register_application(name).await {
+                Err(crate::HueError::BridgeError { code: 101, .. }) => {
+                    let left = deadline.saturating_duration_since(tokio::time::Instant::now());
+                    if left.is_zero() {
+                        return Err(crate::HueError::Timeout {
+                            msg: format!("the link button was not pressed within {timeout:?}"),
+                        });
+                    }
+                    on_waiting(left);
+                    tokio::time::sleep(LINK_BUTTON_POLL.min(left)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Same as [`UnauthBridge::register_application`], but also asks the bridge for the client
+    /// key of the application, which is required to stream to entertainment areas. The bridge only
+    /// hands out this key at registration, so it should be stored along with the application key.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4]);
+    /// let (auth_bridge, client_key) = bridge
+    ///     .register_entertainment_application("mylaptop")
+    ///     .await
+    ///     .unwrap();
+    /// // now both keys can be stored and reused
+    /// # })
+    /// ```
+    pub async fn register_entertainment_application(
+        self,
+        name: &str,
+    ) -> crate::Result<(Bridge, String)> {
+        let (bridge, client_key) = self.register(name, true).await?;
+        let client_key = client_key.ok_or_else(|| crate::HueError::ProtocolError {
+            msg: "the bridge did not return a client key".into(),
+        })?;
+        Ok((bridge, client_key))
+    }
+
+    async fn register(
+        self,
+        name: &str,
+        generate_client_key: bool,
+    ) -> crate::Result<(Bridge, Option<String>)> {
         #[derive(Serialize)]
         struct PostApi {
             devicetype: String,
+            #[serde(skip_serializing_if = "std::ops::Not::not")]
+            generateclientkey: bool,
         }
         #[derive(Debug, Deserialize)]
         struct Username {
             username: String,
+            clientkey: Option<String>,
         }
         let obtain = PostApi {
             devicetype: name.to_string(),
+            generateclientkey: generate_client_key,
         };
-        let url = format!("https://{}/api", self.ip);
-        let resp: BridgeResponse<SuccessResponse<Username>> = self
+        let url = format!("https://{}/api", self.host());
+        let resp: BridgeResponse<Username> = self
             .client
             .post(&url)
             .json(&obtain)
@@ -312,54 +1385,144 @@ impl UnauthBridge {
             .await?
             .json()
             .await?;
-        let resp = resp.get()?;
-
-        let username = resp.success.username;
-        Ok(Bridge {
+        let Username {
+            username,
+            clientkey,
+        } = resp.get()?;
+        let bridge = Bridge {
             ip: self.ip,
-            client: create_reqwest_client(Some(&username)),
+            client: create_reqwest_client(Some(&username), &self.headers, &self.tls),
+            headers: self.headers,
+            tls: self.tls,
             application_key: username,
-        })
+            body_logging: Default::default(),
+            #[cfg(feature = "fault-injection")]
+            faults: Default::default(),
+            version: None,
+        };
+        Ok((bridge, clientkey))
+    }
+
+    /// Adds a header sent with every request, for instance to authenticate with a reverse proxy
+    /// or a tunnel in front of the bridge. It is kept when the bridge is authenticated.
+    /// ### Example
+    /// ```no_run
+    /// use reqwest::header::{HeaderName, HeaderValue};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///     .with_default_header(
+    ///         HeaderName::from_static("x-tunnel-token"),
+    ///         HeaderValue::from_static("secret"),
+    ///     )
+    ///     .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// ```
+    pub fn with_default_header(
+        self,
+        name: reqwest::header::HeaderName,
+        value: reqwest::header::HeaderValue,
+    ) -> UnauthBridge {
+        let mut headers = self.headers;
+        headers.insert(name, value);
+        UnauthBridge {
+            client: create_reqwest_client(None, &headers, &self.tls),
+            headers,
+            ..self
+        }
+    }
+
+    /// Sets the id of the bridge, as found in [`crate::BridgeConfig::bridge_id`] or in the
+    /// discovery results. The certificate of the bridge must then be issued to this id, either by
+    /// the Hue root CA or, for older bridges, by the bridge itself. Without it, only certificates
+    /// issued by the Hue root CA are accepted, which older bridges do not have.
+    /// ### Example
+    /// ```no_run
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///     .with_bridge_id("ecb5fafffe0a1b2c")
+    ///     .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// ```
+    pub fn with_bridge_id(self, bridge_id: &str) -> UnauthBridge {
+        let tls = crate::tls::TlsOptions {
+            bridge_id: Some(bridge_id.to_lowercase()),
+            ..self.tls
+        };
+        UnauthBridge {
+            client: create_reqwest_client(None, &self.headers, &tls),
+            tls,
+            ..self
+        }
+    }
+
+    /// Accepts any certificate from the bridge, as versions of this library before certificates
+    /// were validated did. This makes the connection vulnerable to anyone able to intercept the
+    /// traffic on the local network, and should only be a last resort for bridges whose
+    /// certificate cannot be validated.
+    /// ### Example
+    /// ```no_run
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///     .with_accept_invalid_certs(true)
+    ///     .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// ```
+    pub fn with_accept_invalid_certs(self, accept_invalid_certs: bool) -> UnauthBridge {
+        if accept_invalid_certs && !self.tls.accept_invalid_certs {
+            log::warn!(
+                "the certificate of the bridge at {} is not validated",
+                self.ip
+            );
+        }
+        let tls = crate::tls::TlsOptions {
+            accept_invalid_certs,
+            ..self.tls
+        };
+        UnauthBridge {
+            client: create_reqwest_client(None, &self.headers, &tls),
+            tls,
+            ..self
+        }
+    }
+
+    /// Whether any certificate is accepted from the bridge, see
+    /// [`UnauthBridge::with_accept_invalid_certs`].
+    pub fn accepts_invalid_certs(&self) -> bool {
+        self.tls.accept_invalid_certs
     }
 }
 
 /// The bridge is the central access point of the lamps is a Hue setup, and also the central access
 /// point of this library.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Bridge {
     /// The IP-address of the bridge.
     pub ip: std::net::IpAddr,
     /// This is the username of the currently logged in user.
     pub application_key: String,
-    client: reqwest::Client,
+    pub(crate) client: reqwest::Client,
+    // the extra headers sent with every request, besides the application key
+    pub(crate) headers: reqwest::header::HeaderMap,
+    pub(crate) tls: crate::tls::TlsOptions,
+    pub(crate) body_logging: crate::BodyLogging,
+    #[cfg(feature = "fault-injection")]
+    pub(crate) faults: crate::FaultInjection,
+    pub(crate) version: Option<crate::BridgeVersion>,
 }
 
-fn create_reqwest_client(application_key: Option<&str>) -> reqwest::Client {
-    reqwest::Client::builder()
-        // see https://developers.meethue.com/develop/application-design-guidance/using-https/
-        .add_root_certificate(
-            reqwest::Certificate::from_pem(
-                b"-----BEGIN CERTIFICATE-----
-MIICMjCCAdigAwIBAgIUO7FSLbaxikuXAljzVaurLXWmFw4wCgYIKoZIzj0EAwIw
-OTELMAkGA1UEBhMCTkwxFDASBgNVBAoMC1BoaWxpcHMgSHVlMRQwEgYDVQQDDAty
-b290LWJyaWRnZTAiGA8yMDE3MDEwMTAwMDAwMFoYDzIwMzgwMTE5MDMxNDA3WjA5
-MQswCQYDVQQGEwJOTDEUMBIGA1UECgwLUGhpbGlwcyBIdWUxFDASBgNVBAMMC3Jv
-b3QtYnJpZGdlMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEjNw2tx2AplOf9x86
-aTdvEcL1FU65QDxziKvBpW9XXSIcibAeQiKxegpq8Exbr9v6LBnYbna2VcaK0G22
-jOKkTqOBuTCBtjAPBgNVHRMBAf8EBTADAQH/MA4GA1UdDwEB/wQEAwIBhjAdBgNV
-HQ4EFgQUZ2ONTFrDT6o8ItRnKfqWKnHFGmQwdAYDVR0jBG0wa4AUZ2ONTFrDT6o8
-ItRnKfqWKnHFGmShPaQ7MDkxCzAJBgNVBAYTAk5MMRQwEgYDVQQKDAtQaGlsaXBz
-IEh1ZTEUMBIGA1UEAwwLcm9vdC1icmlkZ2WCFDuxUi22sYpLlwJY81Wrqy11phcO
-MAoGCCqGSM49BAMCA0gAMEUCIEBYYEOsa07TH7E5MJnGw557lVkORgit2Rm1h3B2
-sFgDAiEA1Fj/C3AN5psFMjo0//mrQebo0eKd3aWRx+pQY08mk48=
------END CERTIFICATE-----",
+fn create_reqwest_client(
+    application_key: Option<&str>,
+    headers: &reqwest::header::HeaderMap,
+    tls: &crate::tls::TlsOptions,
+) -> reqwest::Client {
+    let builder = if tls.accept_invalid_certs {
+        reqwest::Client::builder()
+            .add_root_certificate(
+                reqwest::Certificate::from_pem(crate::tls::HUE_ROOT_CA)
+                    .expect("using rustls and this hardcoded certificate should never fail"),
             )
-            .expect("using rustls and this hardcoded certificate should never fail"),
-        )
-        // TODO properly handle older bridges that still use a self-signed certificate
-        .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_certs(true)
+    } else {
+        // see https://developers.meethue.com/develop/application-design-guidance/using-https/
+        reqwest::Client::builder().use_preconfigured_tls(crate::tls::client_config(tls))
+    };
+    builder
         .default_headers({
-            let mut headers = reqwest::header::HeaderMap::new();
+            let mut headers = headers.clone();
             if let Some(key) = application_key {
                 headers.insert(
                     reqwest::header::HeaderName::from_static("hue-application-key"),
@@ -374,6 +1537,21 @@ sFgDAiEA1Fj/C3AN5psFMjo0//mrQebo0eKd3aWRx+pQY08mk48=
 }
 
 impl Bridge {
+    pub(crate) fn host(&self) -> UrlHost {
+        UrlHost(self.ip)
+    }
+
+    /// The same bridge without an application key, keeping the headers and the certificate
+    /// validation, for instance to register another application.
+    pub(crate) fn unauth(&self) -> UnauthBridge {
+        UnauthBridge {
+            ip: self.ip,
+            client: create_reqwest_client(None, &self.headers, &self.tls),
+            headers: self.headers.clone(),
+            tls: self.tls.clone(),
+        }
+    }
+
     /// Create a bridge at this IP. If you know the IP-address, this is the fastest option. Note
     /// that this function does not validate whether a bridge is really present at the IP-address.
     /// ### Example
@@ -383,10 +1561,13 @@ impl Bridge {
     pub fn for_ip(ip: impl Into<std::net::IpAddr>) -> UnauthBridge {
         UnauthBridge {
             ip: ip.into(),
-            client: create_reqwest_client(None),
+            client: create_reqwest_client(None, &Default::default(), &Default::default()),
+            headers: Default::default(),
+            tls: Default::default(),
         }
     }
 
+    #[cfg(feature = "discovery")]
     /// Scans the current network for Bridges, and if there is at least one, returns the first one
     /// that was found.
     /// ### Example
@@ -397,12 +1578,96 @@ impl Bridge {
         crate::disco::discover_hue_bridge()
             .await
             .ok()
-            .map(|ip| UnauthBridge {
-                ip,
-                client: create_reqwest_client(None),
+            .map(|bridge| bridge.unauth_bridge())
+    }
+
+    #[cfg(feature = "discovery")]
+    /// Scans the current network for 5 seconds, over IPv4 and IPv6, and returns all the bridges
+    /// that were found, for homes with several bridges. Bridges only known to the Hue discovery
+    /// service come last.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// for bridge in hueclient::Bridge::discover_all().await {
+    ///     println!("found a bridge at {}", bridge.ip);
+    /// }
+    /// # })
+    /// ```
+    pub async fn discover_all() -> Vec<UnauthBridge> {
+        Self::discover_all_info()
+            .await
+            .into_iter()
+            .map(|bridge| bridge.unauth_bridge())
+            .collect()
+    }
+
+    #[cfg(feature = "discovery")]
+    /// Same as [`Bridge::discover_all`], but returns what each bridge advertises about itself,
+    /// such as its id and model, to tell them apart before connecting to them.
+    pub async fn discover_all_info() -> Vec<crate::BridgeInfo> {
+        crate::disco::discover_all_hue_bridges(std::time::Duration::from_secs(5)).await
+    }
+
+    #[cfg(feature = "discovery")]
+    /// Same as [`Bridge::discover_all_info`], but only returns the bridges that answer at their
+    /// address with the id they advertised, see [`crate::BridgeInfo::verify`].
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// for bridge in hueclient::Bridge::discover_all_verified().await {
+    ///     println!("{:?} is at {}", bridge.bridge_id, bridge.ip);
+    /// }
+    /// # })
+    /// ```
+    pub async fn discover_all_verified() -> Vec<crate::BridgeInfo> {
+        let bridges = Self::discover_all_info().await;
+        let verified = futures::future::join_all(bridges.into_iter().map(|bridge| bridge.verify()));
+        verified
+            .await
+            .into_iter()
+            .filter_map(|bridge| {
+                bridge
+                    .inspect_err(|e| log::info!("ignoring a discovered bridge: {e}"))
+                    .ok()
             })
+            .collect()
+    }
+
+    #[cfg(feature = "discovery")]
+    /// Scans the current network for the bridge with the given id, as found in
+    /// [`crate::BridgeConfig::bridge_id`], for homes with several bridges where a user is only
+    /// known to one of them. The bridge is verified to answer with this id at its address, and
+    /// bridges that did not advertise their id are asked for it.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::discover_by_id("ecb5fafffe0a1b2c")
+    ///     .await
+    ///     .expect("the bridge is not on this network")
+    ///     .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// # })
+    /// ```
+    pub async fn discover_by_id(bridge_id: &str) -> Option<UnauthBridge> {
+        let bridge_id = bridge_id.to_lowercase();
+        let candidates = Self::discover_all_info()
+            .await
+            .into_iter()
+            .filter(|bridge| {
+                bridge.bridge_id.is_none() || bridge.bridge_id.as_ref() == Some(&bridge_id)
+            });
+        for candidate in candidates {
+            match candidate.verify().await {
+                Ok(bridge) if bridge.bridge_id.as_ref() == Some(&bridge_id) => {
+                    return Some(bridge.unauth_bridge())
+                }
+                Ok(_) => (),
+                Err(e) => log::info!("ignoring a discovered bridge: {e}"),
+            }
+        }
+        None
     }
 
+    #[cfg(feature = "discovery")]
     /// A convience wrapper around `Bridge::disover`, but panics if there is no bridge present.
     /// ### Example
     /// ```no_run
@@ -421,11 +1686,65 @@ impl Bridge {
     ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
     /// ```
     pub fn with_application_key(self, appplication_key: impl Into<String>) -> Bridge {
+        let application_key = appplication_key.into();
         Bridge {
-            ip: self.ip,
-            application_key: appplication_key.into(),
-            client: self.client,
+            client: create_reqwest_client(Some(&application_key), &self.headers, &self.tls),
+            application_key,
+            ..self
+        }
+    }
+
+    /// Adds a header sent with every request, see [`UnauthBridge::with_default_header`].
+    pub fn with_default_header(
+        self,
+        name: reqwest::header::HeaderName,
+        value: reqwest::header::HeaderValue,
+    ) -> Bridge {
+        let mut headers = self.headers;
+        headers.insert(name, value);
+        Bridge {
+            client: create_reqwest_client(Some(&self.application_key), &headers, &self.tls),
+            headers,
+            ..self
+        }
+    }
+
+    /// Sets the id of the bridge, see [`UnauthBridge::with_bridge_id`].
+    pub fn with_bridge_id(self, bridge_id: &str) -> Bridge {
+        let tls = crate::tls::TlsOptions {
+            bridge_id: Some(bridge_id.to_lowercase()),
+            ..self.tls
+        };
+        Bridge {
+            client: create_reqwest_client(Some(&self.application_key), &self.headers, &tls),
+            tls,
+            ..self
+        }
+    }
+
+    /// Accepts any certificate from the bridge, see [`UnauthBridge::with_accept_invalid_certs`].
+    pub fn with_accept_invalid_certs(self, accept_invalid_certs: bool) -> Bridge {
+        if accept_invalid_certs && !self.tls.accept_invalid_certs {
+            log::warn!(
+                "the certificate of the bridge at {} is not validated",
+                self.ip
+            );
         }
+        let tls = crate::tls::TlsOptions {
+            accept_invalid_certs,
+            ..self.tls
+        };
+        Bridge {
+            client: create_reqwest_client(Some(&self.application_key), &self.headers, &tls),
+            tls,
+            ..self
+        }
+    }
+
+    /// Whether any certificate is accepted from the bridge, see
+    /// [`UnauthBridge::with_accept_invalid_certs`].
+    pub fn accepts_invalid_certs(&self) -> bool {
+        self.tls.accept_invalid_certs
     }
 
     /// This function registers a new application at the provided bridge, using `name` as an
@@ -454,22 +1773,10 @@ impl Bridge {
         let obtain = PostApi {
             devicetype: name.to_string(),
         };
-        let url = format!("https://{}/api", self.ip);
-        let resp: BridgeResponse<SuccessResponse<Username>> = self
-            .client
-            .post(&url)
-            .json(&obtain)
-            .send()
-            .await?
-            .json()
-            .await?;
-        let resp = resp.get()?;
-
-        Ok(Bridge {
-            ip: self.ip,
-            application_key: resp.success.username,
-            client: self.client,
-        })
+        let url = format!("https://{}/api", self.host());
+        let resp: BridgeResponse<Username> =
+            self.send(self.client.post(&url).json(&obtain)).await?;
+        Ok(self.with_application_key(resp.get()?.username))
     }
 
     /// Returns a vector of all devices that are registered at this `Bridge`, sorted by their id's.
@@ -486,11 +1793,7 @@ impl Bridge {
     /// # })
     /// ```
     pub async fn get_all_devices(&self) -> crate::Result<Vec<Device>> {
-        let url = format!("https://{}/clip/v2/resource/device", self.ip);
-        let resp: BridgeResponseV2<Device> = self.client.get(&url).send().await?.json().await?;
-        let mut devices = resp.get()?;
-        devices.sort_by(|a, b| a.id.cmp(&b.id));
-        Ok(devices)
+        self.get_resources().await
     }
 
     pub async fn index_all_devices(&self) -> crate::Result<HashMap<String, Device>> {
@@ -501,6 +1804,66 @@ impl Bridge {
             .collect())
     }
 
+    /// Returns the device with the given id, without downloading all the others.
+    pub async fn get_device(&self, id: &str) -> crate::Result<Device> {
+        self.get_resource(id).await
+    }
+
+    /// Removes a device, such as a decommissioned bulb or sensor, from this `Bridge`. It has to be
+    /// paired again to be used.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// bridge
+    ///     .delete_device("0b6c2d3e-4f5a-4b6c-9d7e-8f9a0b1c2d3e")
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub async fn delete_device(&self, id: &str) -> crate::Result<()> {
+        self.delete_resource("device", id).await
+    }
+
+    /// Renames a device, and optionally changes its archetype, such as `sultan_bulb` or
+    /// `ceiling_round`, which sets the icon shown in the Hue app.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for (n, device) in bridge.get_all_devices().await.unwrap().iter().enumerate() {
+    ///     let name = format!("Hallway {}", n + 1);
+    ///     bridge.update_device_metadata(&device.id, &name, None).await.unwrap();
+    /// }
+    /// # })
+    /// ```
+    pub async fn update_device_metadata(
+        &self,
+        id: &str,
+        name: &str,
+        archetype: Option<&str>,
+    ) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct DeviceMetadata<'a> {
+            name: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            archetype: Option<&'a str>,
+        }
+        #[derive(Serialize)]
+        struct PutDevice<'a> {
+            metadata: DeviceMetadata<'a>,
+        }
+        let url = format!("https://{}/clip/v2/resource/device/{}", self.host(), id);
+        let command = PutDevice {
+            metadata: DeviceMetadata { name, archetype },
+        };
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(&command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
     /// Returns a vector of all lights that are registered at this `Bridge`, sorted by their id's.
     /// This function returns an error if `bridge.username` is `None`.
     ///
@@ -515,21 +1878,33 @@ impl Bridge {
     /// # })
     /// ```
     pub async fn get_all_lights(&self) -> crate::Result<Vec<Light>> {
-        let url = format!("https://{}/clip/v2/resource/light", self.ip);
-        let resp: BridgeResponseV2<Light> = self.client.get(&url).send().await?.json().await?;
-        let mut lights = resp.get()?;
-        lights.sort_by(|a, b| a.id.cmp(&b.id));
-        Ok(lights)
+        self.get_resources().await
     }
 
     pub async fn index_all_lights(&self) -> crate::Result<HashMap<String, Light>> {
         let lights = self.get_all_lights().await?;
         Ok(lights
             .into_iter()
-            .fold(HashMap::new(), |mut map: HashMap<String, Light>, light| {
-                map.insert(light.id.clone(), light);
-                map
-            }))
+            .map(|light| (light.id.clone(), light))
+            .collect())
+    }
+
+    /// Returns the light with the given id, without downloading all the others. This is
+    /// cheaper than [`Bridge::get_all_lights`] for polling a single light.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let light = bridge
+    ///     .get_light("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f")
+    ///     .await
+    ///     .unwrap();
+    /// println!("{} is on: {}", light.metadata.name, light.on.on);
+    /// # })
+    /// ```
+    pub async fn get_light(&self, id: &str) -> crate::Result<Light> {
+        self.get_resource(id).await
     }
 
     /// Returns a vector of all rooms that are registered at this `Bridge`, sorted by their id's.
@@ -545,11 +1920,12 @@ impl Bridge {
     /// # })
     /// ```
     pub async fn get_all_rooms(&self) -> crate::Result<Vec<Room>> {
-        let url = format!("https://{}/clip/v2/resource/room", self.ip);
-        let resp: BridgeResponseV2<Room> = self.client.get(&url).send().await?.json().await?;
-        let mut groups = resp.get()?;
-        groups.sort_by(|a, b| a.id.cmp(&b.id));
-        Ok(groups)
+        self.get_resources().await
+    }
+
+    /// Returns the room with the given id, without downloading all the others.
+    pub async fn get_room(&self, id: &str) -> crate::Result<Room> {
+        self.get_resource(id).await
     }
 
     pub async fn resolve_all_rooms(&self) -> crate::Result<Vec<ResolvedRoom>> {
@@ -560,24 +1936,7 @@ impl Bridge {
 
         Ok(rooms
             .into_iter()
-            .map(|room: Room| ResolvedRoom {
-                metadata: room.metadata,
-                children: room
-                    .children
-                    .into_iter()
-                    .flat_map(|child| {
-                        indexed_devices.get(&child.rid).map_or(vec![], |device| {
-                            device
-                                .get_lights()
-                                .filter_map(|light_id| indexed_lights.get(light_id).cloned())
-                                .collect()
-                        })
-                    })
-                    .collect(),
-                id_v1: room.id_v1,
-                id: room.id,
-                services: room.services,
-            })
+            .map(|room| room.resolve(&indexed_devices, &indexed_lights))
             .collect())
     }
 
@@ -594,11 +1953,12 @@ impl Bridge {
     /// # })
     /// ```
     pub async fn get_all_zones(&self) -> crate::Result<Vec<Zone>> {
-        let url = format!("https://{}/clip/v2/resource/zone", self.ip);
-        let resp: BridgeResponseV2<Zone> = self.client.get(&url).send().await?.json().await?;
-        let mut groups = resp.get()?;
-        groups.sort_by(|a, b| a.id.cmp(&b.id));
-        Ok(groups)
+        self.get_resources().await
+    }
+
+    /// Returns the zone with the given id, without downloading all the others.
+    pub async fn get_zone(&self, id: &str) -> crate::Result<Zone> {
+        self.get_resource(id).await
     }
 
     pub async fn resolve_all_zones(&self) -> crate::Result<Vec<ResolvedZone>> {
@@ -608,17 +1968,7 @@ impl Bridge {
 
         Ok(zones
             .into_iter()
-            .map(|zone: Zone| ResolvedZone {
-                metadata: zone.metadata,
-                children: zone
-                    .children
-                    .into_iter()
-                    .filter_map(|child| indexed_lights.get(&child.rid).cloned())
-                    .collect(),
-                id_v1: zone.id_v1,
-                id: zone.id,
-                services: zone.services,
-            })
+            .map(|zone| zone.resolve(&indexed_lights))
             .collect())
     }
 
@@ -635,125 +1985,428 @@ impl Bridge {
     /// # })
     /// ```
     pub async fn get_all_scenes(&self) -> crate::Result<Vec<Scene>> {
-        let url = format!("https://{}/clip/v2/resource/scene", self.ip);
-        let resp: BridgeResponseV2<Scene> = self.client.get(&url).send().await?.json().await?;
-        let mut scenes = resp.get()?;
-        scenes.sort_by(|a, b| a.id.cmp(&b.id));
-        Ok(scenes)
+        self.get_resources().await
+    }
+
+    /// Returns the scene with the given id, without downloading all the others.
+    pub async fn get_scene(&self, id: &str) -> crate::Result<Scene> {
+        self.get_resource(id).await
+    }
+
+    /// Returns a vector of all grouped lights that are registered at this `Bridge`, sorted by their
+    /// id's. There is one grouped light per room and per zone.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for group in &bridge.get_all_grouped_lights().await.unwrap() {
+    ///     println!("{} owned by {}", group.id, group.owner.rid);
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_grouped_lights(&self) -> crate::Result<Vec<GroupedLight>> {
+        self.get_resources().await
+    }
+
+    /// Returns a vector of all entertainment services that are registered at this `Bridge`,
+    /// sorted by their id's.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for entertainment in &bridge.get_all_entertainment_services().await.unwrap() {
+    ///     println!("{:?} -> {:?}", entertainment.id, entertainment.get_light());
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_entertainment_services(&self) -> crate::Result<Vec<Entertainment>> {
+        self.get_resources().await
     }
 
     pub async fn set_scene(&self, scene: String) -> crate::Result<()> {
-        let url = format!("https://{}/clip/v2/resource/scene/{}", self.ip, scene);
-        let resp: BridgeResponseV2<Value> = self
-            .client
-            .put(&url)
-            .json(&CommandScene {
-                recall: SceneRecall {
-                    action: "active".to_string(),
-                },
-            })
-            .send()
-            .await?
-            .json()
-            .await?;
-        resp.get()?;
+        self.recall_scene(&scene, None, None).await
+    }
+
+    /// Recalls a scene, fading in over `duration`, and optionally with all its lights at
+    /// `brightness` instead of the brightness saved in the scene.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// // the evening scene, at 30% over 10 seconds
+    /// bridge
+    ///     .recall_scene(
+    ///         "0b6c2d3e-4f5a-4b6c-9d7e-8f9a0b1c2d3e",
+    ///         Some(std::time::Duration::from_secs(10)),
+    ///         Some(30.),
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub async fn recall_scene(
+        &self,
+        scene: &str,
+        duration: Option<std::time::Duration>,
+        brightness: Option<f32>,
+    ) -> crate::Result<()> {
+        let command = CommandScene::default().with_recall(duration, brightness);
+        self.update_scene(scene, &command).await
+    }
+
+    /// Recalls a scene dynamically: its lights cycle through the colors of its palette at
+    /// `speed`, between 0 and 1, after fading in over `duration`.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// bridge
+    ///     .recall_scene_dynamic(
+    ///         "0b6c2d3e-4f5a-4b6c-9d7e-8f9a0b1c2d3e",
+    ///         0.2,
+    ///         Some(std::time::Duration::from_secs(5)),
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub async fn recall_scene_dynamic(
+        &self,
+        scene: &str,
+        speed: f32,
+        duration: Option<std::time::Duration>,
+    ) -> crate::Result<()> {
+        let command = CommandScene::default()
+            .with_speed(speed)
+            .with_dynamic_recall(duration);
+        self.update_scene(scene, &command).await
+    }
+
+    /// Creates a scene in a room or a zone, and returns its identifier.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandLight, NewScene, ResourceIdentifier, SceneAction};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let office = ResourceIdentifier {
+    ///     rid: "3f2d8a7e-1b4c-4d5e-9f6a-7b8c9d0e1f2a".into(),
+    ///     rtype: "room".into(),
+    /// };
+    /// let desk_lamp = SceneAction {
+    ///     target: ResourceIdentifier {
+    ///         rid: "9a8b7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d".into(),
+    ///         rtype: "light".into(),
+    ///     },
+    ///     action: CommandLight::default().on().with_brightness(80.),
+    /// };
+    /// let scene = bridge
+    ///     .create_scene(&NewScene::new("Focus", office, vec![desk_lamp]))
+    ///     .await
+    ///     .unwrap();
+    /// println!("created scene {}", scene.rid);
+    /// # })
+    /// ```
+    pub async fn create_scene(&self, scene: &NewScene) -> crate::Result<ResourceIdentifier> {
+        let url = format!("https://{}/clip/v2/resource/scene", self.host());
+        let resp: BridgeResponseV2<ResourceIdentifier> =
+            self.send(self.client.post(&url).json(scene)).await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a created resource"))
+    }
+
+    /// Same as [`Bridge::create_scene`], but if the group already has a scene with the same name,
+    /// its actions and palette are replaced instead of creating a duplicate. Setup scripts can run
+    /// it any number of times.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{NewScene, ResourceIdentifier};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let office = ResourceIdentifier {
+    ///     rid: "3f2d8a7e-1b4c-4d5e-9f6a-7b8c9d0e1f2a".into(),
+    ///     rtype: "room".into(),
+    /// };
+    /// let scene = NewScene::new("Focus", office, vec![]);
+    /// let first = bridge.ensure_scene(&scene).await.unwrap();
+    /// let second = bridge.ensure_scene(&scene).await.unwrap();
+    /// assert_eq!(first.rid, second.rid);
+    /// # })
+    /// ```
+    pub async fn ensure_scene(&self, scene: &NewScene) -> crate::Result<ResourceIdentifier> {
+        let existing = self.get_all_scenes().await?.into_iter().find(|s| {
+            s.group.rid == scene.group.rid
+                && crate::names_match(&s.metadata.name, &scene.metadata.name)
+        });
+        match existing {
+            Some(existing) => {
+                let command = CommandScene {
+                    palette: scene.palette.clone(),
+                    ..CommandScene::default().with_actions(scene.actions.clone())
+                };
+                self.update_scene(&existing.id, &command).await?;
+                Ok(ResourceIdentifier {
+                    rid: existing.id,
+                    rtype: "scene".into(),
+                })
+            }
+            None => self.create_scene(scene).await,
+        }
+    }
 
+    /// Changes the name and/or the actions of a scene.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let command = hueclient::CommandScene::default().with_name("Late evening");
+    /// bridge
+    ///     .update_scene("4f3e2d1c-0b9a-4876-9543-210fedcba987", &command)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub async fn update_scene(&self, scene: &str, command: &CommandScene) -> crate::Result<()> {
+        let url = format!("https://{}/clip/v2/resource/scene/{}", self.host(), scene);
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(command)).await?;
+        resp.get()?;
         Ok(())
     }
 
-    pub async fn set_group_state(&self, group: &str, command: &CommandLight) -> crate::Result<()> {
+    /// Replaces the actions of a scene by the current state of the lights of its room or zone,
+    /// like "save current state" in the Hue app. Fails without touching the scene if its room or
+    /// zone is gone or has no lights.
+    pub async fn store_current_state_in_scene(&self, scene: &str) -> crate::Result<()> {
+        let scene = self
+            .get_all_scenes()
+            .await?
+            .into_iter()
+            .find(|s| s.id == scene)
+            .ok_or_else(|| crate::HueError::protocol_err(format!("unknown scene {scene}")))?;
+        let lights = self.index_all_lights().await?;
+        let members: Vec<&Light> = match scene.group.rtype.as_str() {
+            "room" => {
+                let devices = self.index_all_devices().await?;
+                let room = self
+                    .get_all_rooms()
+                    .await?
+                    .into_iter()
+                    .find(|room| room.id == scene.group.rid)
+                    .ok_or_else(|| {
+                        crate::HueError::protocol_err(format!("unknown room {}", scene.group.rid))
+                    })?;
+                room.children
+                    .iter()
+                    .filter_map(|child| devices.get(&child.rid))
+                    .flat_map(Device::get_lights)
+                    .filter_map(|id| lights.get(id))
+                    .collect()
+            }
+            _ => {
+                let zone = self
+                    .get_all_zones()
+                    .await?
+                    .into_iter()
+                    .find(|zone| zone.id == scene.group.rid)
+                    .ok_or_else(|| {
+                        crate::HueError::protocol_err(format!("unknown zone {}", scene.group.rid))
+                    })?;
+                zone.children
+                    .iter()
+                    .filter_map(|child| lights.get(&child.rid))
+                    .collect()
+            }
+        };
+        if members.is_empty() {
+            return Err(crate::HueError::protocol_err(format!(
+                "{} {} has no lights",
+                scene.group.rtype, scene.group.rid
+            )));
+        }
+        let actions = members
+            .into_iter()
+            .map(|light| SceneAction {
+                target: ResourceIdentifier {
+                    rid: light.id.clone(),
+                    rtype: "light".into(),
+                },
+                action: CommandLight::from_light(light),
+            })
+            .collect();
+        self.update_scene(&scene.id, &CommandScene::default().with_actions(actions))
+            .await
+    }
+
+    pub async fn set_group_state(
+        &self,
+        group: &str,
+        command: &CommandGroupedLight,
+    ) -> crate::Result<()> {
         let url = format!(
             "https://{}/clip/v2/resource/grouped_light/{}",
-            self.ip, group
+            self.host(),
+            group
         );
-        let resp: BridgeResponseV2<Value> = self
-            .client
-            .put(&url)
-            .json(command)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(command)).await?;
         resp.get()?;
         Ok(())
     }
 
     pub async fn set_light_state(&self, light: &str, command: &CommandLight) -> crate::Result<()> {
-        let url = format!("https://{}/clip/v2/resource/light/{}", self.ip, light);
-        let resp: BridgeResponseV2<Value> = self
-            .client
-            .put(&url)
-            .json(&command)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let url = format!("https://{}/clip/v2/resource/light/{}", self.host(), light);
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(&command)).await?;
         resp.get()?;
         Ok(())
     }
 
-    pub fn events(&self) -> crate::Result<impl Stream<Item = HueEvent>> {
-        let request_builder = self.client.request(
-            Method::GET,
-            format!("https://{}/eventstream/clip/v2", self.ip),
-        );
-        Ok(
-            reqwest_eventsource::EventSource::new(request_builder)?.filter_map(|event| async {
-                log::debug!("event {:?}", event);
-                match event {
-                    Ok(reqwest_eventsource::Event::Message(msg)) => {
-                        log::debug!("message {:?}", msg.data);
-                        match serde_json::from_str::<Vec<EventEnvelope>>(&msg.data) {
-                            Ok(mut event) => Some(HueEvent::Event {
-                                data: event.pop().unwrap().data,
-                            }),
-                            Err(e) => Some(HueEvent::Error(format!("{:?}", e))),
-                        }
-                    }
-                    Ok(reqwest_eventsource::Event::Open) => None,
-                    Err(e) => Some(HueEvent::Error(format!("{:?}", e))),
-                }
-            }),
-        )
+    /// Makes a light blink, to help finding it physically, for instance while setting up a room.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for light in bridge.get_all_lights().await.unwrap() {
+    ///     bridge.identify_light(&light.id).await.unwrap();
+    ///     println!("is {} blinking?", light.metadata.name);
+    /// #   break;
+    /// }
+    /// # })
+    /// ```
+    pub async fn identify_light(&self, light: &str) -> crate::Result<()> {
+        let url = format!("https://{}/clip/v2/resource/light/{}", self.host(), light);
+        #[derive(Serialize)]
+        struct Identify {
+            action: &'static str,
+        }
+        #[derive(Serialize)]
+        struct PutIdentify {
+            identify: Identify,
+        }
+        let command = PutIdentify {
+            identify: Identify { action: "identify" },
+        };
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(&command)).await?;
+        resp.get()?;
+        Ok(())
     }
-}
 
-#[derive(Debug, Clone, serde::Deserialize)]
-struct EventEnvelope {
-    data: Vec<Event>,
-}
+    /// Downloads the whole `/clip/v2/resource` list, as raw JSON. The body is streamed, calling
+    /// `progress` with the number of bytes received so far and the expected total, if the bridge
+    /// announced it. The download fails with `HueError::Timeout` if it is not complete within
+    /// `deadline`, however large the home is.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let resources = bridge
+    ///     .get_all_resources_raw(std::time::Duration::from_secs(30), |received, total| {
+    ///         println!("{received} / {total:?} bytes");
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// println!("{} resources", resources.len());
+    /// # })
+    /// ```
+    pub async fn get_all_resources_raw(
+        &self,
+        deadline: std::time::Duration,
+        progress: impl FnMut(u64, Option<u64>),
+    ) -> crate::Result<Vec<Value>> {
+        let url = format!("https://{}/clip/v2/resource", self.host());
+        let resp: BridgeResponseV2<Value> = self.get_streamed(&url, deadline, progress).await?;
+        resp.get()
+    }
 
-#[derive(Debug, Clone)]
-pub enum HueEvent {
-    Event { data: Vec<Event> },
-    Error(String),
+    pub(crate) async fn get_streamed<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        deadline: std::time::Duration,
+        mut progress: impl FnMut(u64, Option<u64>),
+    ) -> crate::Result<T> {
+        let read = |mut response: reqwest::Response| async move {
+            let total = response.content_length();
+            let mut body = Vec::with_capacity(total.unwrap_or(0).min(MAX_PREALLOCATION) as usize);
+            while let Some(chunk) = response.chunk().await? {
+                body.extend_from_slice(&chunk);
+                progress(body.len() as u64, total);
+            }
+            Ok(body)
+        };
+        let download = self.send_reading(self.client.get(url), read);
+        tokio::time::timeout(deadline, download)
+            .await
+            .map_err(|_| crate::HueError::Timeout {
+                msg: format!("{url} was not downloaded within {deadline:?}"),
+            })?
+    }
 }
 
+// the most memory reserved upfront for a streamed download, whatever its announced length
+const MAX_PREALLOCATION: u64 = 8 * 1024 * 1024;
+
+/// A response of the v1 API. Commands are answered with a list holding a success or an error per
+/// change, which can be empty, mixed, or hold several successes. Reads are answered with the
+/// resource itself.
 #[derive(Debug, serde::Deserialize)]
 #[serde(untagged)]
-enum BridgeResponse<T> {
+pub(crate) enum BridgeResponse<T> {
+    List(Vec<BridgeResponseItem<T>>),
     Element(T),
-    List(Vec<T>),
-    Errors(Vec<BridgeError>),
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub(crate) enum BridgeResponseItem<T> {
+    Success { success: T },
+    Error(BridgeError),
 }
 
 impl<T> BridgeResponse<T> {
-    fn get(self) -> crate::Result<T> {
-        match self {
-            BridgeResponse::Element(t) => Ok(t),
-            BridgeResponse::List(mut ts) => ts
-                .pop()
-                .ok_or_else(|| crate::HueError::protocol_err("expected non-empty array")),
-            BridgeResponse::Errors(mut es) => {
-                // it is safe to unwrap here, since any empty lists will be treated as the
-                // `BridgeResponse::List` case.
-                let BridgeError { error } = es.pop().unwrap();
-                Err(crate::HueError::BridgeError {
-                    code: error.r#type,
-                    msg: error.description,
-                })
+    /// Splits the response into its successes and its errors, in the order of the bridge.
+    pub(crate) fn partition(self) -> (Vec<T>, Vec<crate::HueError>) {
+        let items = match self {
+            BridgeResponse::Element(t) => return (vec![t], vec![]),
+            BridgeResponse::List(items) => items,
+        };
+        let mut successes = vec![];
+        let mut errors = vec![];
+        for item in items {
+            match item {
+                BridgeResponseItem::Success { success } => successes.push(success),
+                BridgeResponseItem::Error(BridgeError { error }) => {
+                    errors.push(crate::HueError::BridgeError {
+                        code: error.r#type,
+                        msg: error.description,
+                    })
+                }
             }
         }
+        (successes, errors)
+    }
+
+    /// Returns all the successes, which may be none, or the first error if there is any.
+    pub(crate) fn get_all(self) -> crate::Result<Vec<T>> {
+        let (successes, errors) = self.partition();
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(successes),
+        }
+    }
+
+    /// Returns the first success, or the first error if there is any.
+    pub(crate) fn get(self) -> crate::Result<T> {
+        self.get_all()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a success in the response"))
     }
 }
 
@@ -763,13 +2416,13 @@ struct BridgeErrorV2 {
 }
 
 #[derive(Debug, serde::Deserialize)]
-struct BridgeResponseV2<T> {
+pub(crate) struct BridgeResponseV2<T> {
     errors: Vec<BridgeErrorV2>,
     data: Vec<T>,
 }
 
 impl<T> BridgeResponseV2<T> {
-    fn get(mut self) -> crate::Result<Vec<T>> {
+    pub(crate) fn get(mut self) -> crate::Result<Vec<T>> {
         if let Some(error) = self.errors.pop() {
             Err(crate::HueError::BridgeErrorV2 {
                 description: error.description,
@@ -781,7 +2434,7 @@ impl<T> BridgeResponseV2<T> {
 }
 
 #[derive(Debug, serde::Deserialize)]
-struct BridgeError {
+pub(crate) struct BridgeError {
     error: BridgeErrorInner,
 }
 
@@ -793,7 +2446,187 @@ struct BridgeErrorInner {
     r#type: usize,
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct SuccessResponse<T> {
-    success: T,
+/// A light for tests, with the properties of `overrides` replacing the ones of a mixed bulb.
+#[cfg(test)]
+pub(crate) fn test_light(overrides: serde_json::Value) -> Light {
+    let mut light = serde_json::json!({
+        "id": "l1",
+        "metadata": { "name": "Lamp", "archetype": "sultan_bulb", "function": "mixed" },
+        "service_id": 0,
+        "on": { "on": true }
+    });
+    for (key, value) in overrides.as_object().expect("overrides are an object") {
+        light[key] = value.clone();
+    }
+    serde_json::from_value(light).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ipv6_hosts() {
+        let v4 = Bridge::for_ip([192u8, 168, 0, 4]);
+        let v6 = Bridge::for_ip("fd00::4".parse::<std::net::IpAddr>().unwrap());
+        assert_eq!(v4.host().to_string(), "192.168.0.4");
+        assert_eq!(v6.host().to_string(), "[fd00::4]");
+    }
+
+    #[test]
+    fn invalid_certs_are_refused_by_default() {
+        let bridge = Bridge::for_ip([192u8, 168, 0, 4]);
+        assert!(!bridge.accepts_invalid_certs());
+        let bridge = bridge.with_accept_invalid_certs(true).with_user("key");
+        assert!(bridge.accepts_invalid_certs());
+        assert!(!bridge
+            .with_accept_invalid_certs(false)
+            .accepts_invalid_certs());
+    }
+
+    #[test]
+    fn unauth_keeps_tls_and_headers() {
+        let bridge = Bridge::for_ip([192u8, 168, 0, 4])
+            .with_bridge_id("ECB5FAFFFE0A1B2C")
+            .with_accept_invalid_certs(true)
+            .with_default_header(
+                reqwest::header::HeaderName::from_static("x-tunnel-token"),
+                reqwest::header::HeaderValue::from_static("secret"),
+            )
+            .with_user("key");
+        let unauth = bridge.unauth();
+        assert_eq!(unauth.tls.bridge_id.as_deref(), Some("ecb5fafffe0a1b2c"));
+        assert!(unauth.accepts_invalid_certs());
+        assert_eq!(unauth.headers["x-tunnel-token"], "secret");
+    }
+
+    fn parse(value: serde_json::Value) -> BridgeResponse<Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn v1_response_shapes() {
+        assert!(parse(json!([])).get_all().unwrap().is_empty());
+        assert!(parse(json!([])).get().is_err());
+        let successes = json!([{ "success": { "username": "a" } }, { "success": "b" }]);
+        assert_eq!(parse(successes).get().unwrap(), json!({ "username": "a" }));
+        let mixed = json!([
+            { "success": "/lights/1/state/on" },
+            { "error": { "type": 201, "address": "/lights/1/state/hue", "description": "off" } }
+        ]);
+        let (successes, errors) = parse(mixed.clone()).partition();
+        assert_eq!((successes.len(), errors.len()), (1, 1));
+        assert!(matches!(
+            parse(mixed).get_all(),
+            Err(crate::HueError::BridgeError { code: 201, .. })
+        ));
+        assert!(parse(json!({ "name": "Bridge" })).get().is_ok());
+    }
+
+    #[test]
+    fn mirek_clamping() {
+        let light = test_light(json!({
+            "color_temperature": {
+                "mirek": null,
+                "mirek_valid": false,
+                "mirek_schema": { "mirek_minimum": 153, "mirek_maximum": 454 }
+            }
+        }));
+        let mirek = |m| {
+            CommandLight::default()
+                .with_mirek_clamped(&light, m)
+                .color_temperature
+                .unwrap()
+                .mirek
+        };
+        assert_eq!(mirek(100), 153);
+        assert_eq!(mirek(300), 300);
+        assert_eq!(mirek(500), 454);
+    }
+
+    #[test]
+    fn unknown_gradient_modes_are_sent_back_unchanged() {
+        let light = test_light(json!({
+            "gradient": {
+                "points": [
+                    { "color": { "xy": { "x": 0.6, "y": 0.3 } } },
+                    { "color": { "xy": { "x": 0.2, "y": 0.1 } } }
+                ],
+                "points_capable": 5,
+                "mode": "segmented_palette",
+                "mode_values": ["interpolated_palette", "segmented_palette"]
+            }
+        }));
+        let value = serde_json::to_value(CommandLight::from_light(&light)).unwrap();
+        assert_eq!(value["gradient"]["points"].as_array().unwrap().len(), 2);
+        assert_eq!(value["gradient"]["mode"], json!("segmented_palette"));
+    }
+
+    #[test]
+    fn signaling_colors() {
+        let command = CommandLight::default().with_signaling(
+            Signal::Alternating,
+            std::time::Duration::from_secs(5),
+            [(0.6, 0.3), (0.2, 0.1)],
+        );
+        let value = serde_json::to_value(&command).unwrap();
+        assert_eq!(value["signaling"]["signal"], json!("alternating"));
+        assert_eq!(value["signaling"]["duration"], json!(5000));
+        assert_eq!(value["signaling"]["color"].as_array().unwrap().len(), 2);
+        assert!(value["signaling"].get("colors").is_none());
+        let command = CommandLight::default().with_signaling(
+            Signal::OnOff,
+            std::time::Duration::from_secs(1),
+            [],
+        );
+        let value = serde_json::to_value(&command).unwrap();
+        assert!(value["signaling"].get("color").is_none());
+        let signaling: LightSignaling =
+            serde_json::from_value(json!({ "signal_values": ["no_signal", "flash"] })).unwrap();
+        assert_eq!(signaling.signal_values[1], Signal::Other("flash".into()));
+    }
+
+    #[test]
+    fn room_archetypes() {
+        let archetypes: Vec<RoomArchetype> =
+            serde_json::from_value(json!(["living_room", "other", "sauna"])).unwrap();
+        assert_eq!(archetypes[0], RoomArchetype::LivingRoom);
+        assert_eq!(archetypes[1], RoomArchetype::Other("other".into()));
+        assert_eq!(
+            serde_json::to_value(&archetypes).unwrap(),
+            json!(["living_room", "other", "sauna"])
+        );
+    }
+
+    #[test]
+    fn unknown_effects_are_sent_back_unchanged() {
+        let effects: Effects = serde_json::from_value(json!({
+            "status": "aurora",
+            "status_values": ["no_effect", "candle", "aurora"],
+            "effect_values": ["no_effect", "candle", "aurora"]
+        }))
+        .unwrap();
+        assert_eq!(effects.status, Effect::Other("aurora".into()));
+        assert!(effects.supports(Effect::Candle));
+        let command = CommandLight::default().with_effect(effects.status);
+        let value = serde_json::to_value(&command).unwrap();
+        assert_eq!(value["effects"]["effect"], json!("aurora"));
+    }
+
+    #[test]
+    fn unknown_timed_effects_are_sent_back_unchanged() {
+        let effects: TimedEffects = serde_json::from_value(json!({
+            "status": "moonrise",
+            "status_values": ["no_effect", "sunrise", "moonrise"],
+            "effect_values": ["no_effect", "sunrise", "moonrise"]
+        }))
+        .unwrap();
+        assert_eq!(effects.status, TimedEffect::Other("moonrise".into()));
+        assert!(effects.supports(TimedEffect::Sunrise));
+        assert_eq!(
+            serde_json::to_value(&effects.effect_values).unwrap(),
+            json!(["no_effect", "sunrise", "moonrise"])
+        );
+    }
 }
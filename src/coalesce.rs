@@ -0,0 +1,227 @@
+//! Coalescing bursts of events, such as the incremental updates of a light during a transition.
+use crate::{EventData, EventGroup, EventLight, EventMetadata, EventScene, HueEvent};
+use futures::{stream, Stream, StreamExt};
+use std::mem::discriminant;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::time::Instant;
+
+struct Coalescing {
+    events: Pin<Box<dyn Stream<Item = HueEvent> + Send>>,
+    window: Duration,
+    pending: Vec<EventData>,
+    deadline: Option<Instant>,
+    // an event received while changes were pending, yielded right after them
+    queued: Option<HueEvent>,
+    ended: bool,
+}
+
+fn merge_light(current: &mut EventLight, newer: EventLight) {
+    current.on = newer.on.or(current.on.take());
+    current.dimming = newer.dimming.or(current.dimming.take());
+    current.color_temperature = newer.color_temperature.or(current.color_temperature.take());
+    current.color = newer.color.or(current.color.take());
+}
+
+fn merge_group(current: &mut EventGroup, newer: EventGroup) {
+    current.metadata = match (current.metadata.take(), newer.metadata) {
+        (Some(current), Some(newer)) => Some(EventMetadata {
+            name: newer.name.or(current.name),
+            archetype: newer.archetype.or(current.archetype),
+        }),
+        (current, newer) => newer.or(current),
+    };
+    current.children = newer.children.or(current.children.take());
+}
+
+fn merge_scene(current: &mut EventScene, newer: EventScene) {
+    current.metadata = newer.metadata.or(current.metadata.take());
+    current.status = newer.status.or(current.status.take());
+}
+
+impl Coalescing {
+    fn add(&mut self, data: EventData) {
+        // every button press matters, they are never coalesced
+        let coalesced = match (&data, data.id()) {
+            (EventData::Button(_), _) | (_, None) => None,
+            (_, Some(id)) => self.pending.iter_mut().find(|pending| {
+                discriminant(*pending) == discriminant(&data) && pending.id() == Some(id)
+            }),
+        };
+        match (coalesced, data) {
+            (Some(EventData::Light(current)), EventData::Light(newer))
+            | (Some(EventData::GroupedLight(current)), EventData::GroupedLight(newer)) => {
+                merge_light(current, newer)
+            }
+            (Some(EventData::Room(current)), EventData::Room(newer))
+            | (Some(EventData::Zone(current)), EventData::Zone(newer)) => {
+                merge_group(current, newer)
+            }
+            (Some(EventData::Scene(current)), EventData::Scene(newer)) => {
+                merge_scene(current, newer)
+            }
+            (Some(current), newer) => *current = newer,
+            (None, data) => self.pending.push(data),
+        }
+    }
+
+    fn flush(&mut self) -> HueEvent {
+        self.deadline = None;
+        HueEvent::Update(std::mem::take(&mut self.pending))
+    }
+
+    async fn next(mut self) -> Option<(HueEvent, Self)> {
+        loop {
+            if let Some(event) = self.queued.take() {
+                return Some((event, self));
+            }
+            if self.ended {
+                return None;
+            }
+            let next = match self.deadline {
+                Some(deadline) => tokio::select! {
+                    _ = tokio::time::sleep_until(deadline) => return Some((self.flush(), self)),
+                    event = self.events.next() => event,
+                },
+                None => self.events.next().await,
+            };
+            match next {
+                Some(HueEvent::Update(data)) => {
+                    for data in data {
+                        self.add(data);
+                    }
+                    if self.deadline.is_none() && !self.pending.is_empty() {
+                        self.deadline = Some(Instant::now() + self.window);
+                    }
+                }
+                Some(event) if self.pending.is_empty() => return Some((event, self)),
+                // the pending changes happened first, an update must not follow a deletion
+                Some(event) => {
+                    self.queued = Some(event);
+                    return Some((self.flush(), self));
+                }
+                None => {
+                    self.ended = true;
+                    if !self.pending.is_empty() {
+                        return Some((self.flush(), self));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Coalesces the changes of each resource over `window` into a single event. The changes of a
+/// light are merged, so an event with a new brightness followed by an event with a new color
+/// results in a single change with both, and so are the changes of rooms, zones and scenes.
+/// Other resources only keep their last change, except buttons whose events are all kept.
+/// Additions, deletions and errors are not delayed, the pending changes are sent right before
+/// them to keep the events in order.
+///
+/// The first change of a burst is delayed by at most `window`, which should stay well below the
+/// time a user notices, typically 100 to 300 milliseconds.
+/// ### Example
+/// ```no_run
+/// # #[cfg(feature = "events")]
+/// # tokio_test::block_on(async {
+/// use futures::StreamExt;
+/// use std::time::Duration;
+/// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+/// let events = hueclient::coalesce_events(bridge.events().unwrap(), Duration::from_millis(200));
+/// let mut events = Box::pin(events);
+/// while let Some(event) = events.next().await {
+///     println!("{event:?}");
+/// }
+/// # })
+/// ```
+pub fn coalesce_events(
+    events: impl Stream<Item = HueEvent> + Send + 'static,
+    window: Duration,
+) -> impl Stream<Item = HueEvent> + Send {
+    let coalescing = Coalescing {
+        events: Box::pin(events),
+        window,
+        pending: vec![],
+        deadline: None,
+        queued: None,
+        ended: false,
+    };
+    stream::unfold(coalescing, Coalescing::next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(data: serde_json::Value) -> HueEvent {
+        HueEvent::Update(vec![serde_json::from_value(data).unwrap()])
+    }
+
+    #[tokio::test]
+    async fn bursts_are_merged() {
+        let mut burst = vec![event(
+            json!({ "type": "light", "id": "l1", "on": { "on": true } }),
+        )];
+        for brightness in 1..=10 {
+            burst.push(event(json!({
+                "type": "light", "id": "l1", "dimming": { "brightness": brightness as f32 * 10. }
+            })));
+        }
+        burst.push(event(
+            json!({ "type": "light", "id": "l2", "on": { "on": false } }),
+        ));
+        let events = coalesce_events(stream::iter(burst), Duration::from_millis(50));
+        let events: Vec<HueEvent> = events.collect().await;
+        assert_eq!(events.len(), 1);
+        let HueEvent::Update(data) = &events[0] else {
+            panic!("expected an event");
+        };
+        assert_eq!(data.len(), 2);
+        let EventData::Light(light) = &data[0] else {
+            panic!("expected a light");
+        };
+        assert!(light.on.as_ref().unwrap().on);
+        assert_eq!(light.dimming.as_ref().unwrap().brightness, 100.);
+    }
+
+    #[tokio::test]
+    async fn scene_and_room_bursts_are_merged() {
+        let burst = vec![
+            event(json!({ "type": "room", "id": "r1", "metadata": { "name": "Den" } })),
+            event(json!({ "type": "room", "id": "r1", "children": [] })),
+            event(json!({ "type": "scene", "id": "s1", "status": { "active": "static" } })),
+            event(json!({ "type": "scene", "id": "s1", "metadata": { "name": "Relax" } })),
+        ];
+        let events = coalesce_events(stream::iter(burst), Duration::from_millis(50));
+        let events: Vec<HueEvent> = events.collect().await;
+        let [HueEvent::Update(data)] = &events[..] else {
+            panic!("expected a single event");
+        };
+        let [EventData::Room(room), EventData::Scene(scene)] = &data[..] else {
+            panic!("expected a room and a scene");
+        };
+        assert_eq!(room.metadata.as_ref().unwrap().name.as_deref(), Some("Den"));
+        assert!(room.children.as_ref().unwrap().is_empty());
+        assert_eq!(scene.status.as_ref().unwrap().active, "static");
+        assert_eq!(scene.metadata.as_ref().unwrap().name, "Relax");
+    }
+
+    #[tokio::test]
+    async fn pending_changes_come_before_deletions() {
+        let burst = vec![
+            event(json!({ "type": "light", "id": "l1", "on": { "on": true } })),
+            HueEvent::Delete(vec![crate::ResourceIdentifier {
+                rid: "l1".into(),
+                rtype: "light".into(),
+            }]),
+        ];
+        let events = coalesce_events(stream::iter(burst), Duration::from_secs(60));
+        let events: Vec<HueEvent> = events.collect().await;
+        assert!(matches!(
+            &events[..],
+            [HueEvent::Update(_), HueEvent::Delete(_)]
+        ));
+    }
+}
@@ -0,0 +1,536 @@
+//! Color gamuts and conversions between color spaces.
+use crate::{Color, ColorTemperature, CommandLight, Gamut, Light, MirekSchema, XY};
+
+impl Gamut {
+    /// The gamut of the first color bulbs and of LivingColors lamps.
+    pub const A: Gamut = Gamut {
+        red: XY { x: 0.704, y: 0.296 },
+        green: XY {
+            x: 0.2151,
+            y: 0.7106,
+        },
+        blue: XY { x: 0.138, y: 0.08 },
+    };
+    /// The gamut of the first generation of Hue color bulbs.
+    pub const B: Gamut = Gamut {
+        red: XY { x: 0.675, y: 0.322 },
+        green: XY { x: 0.409, y: 0.518 },
+        blue: XY { x: 0.167, y: 0.04 },
+    };
+    /// The gamut of recent Hue color bulbs and lightstrips.
+    pub const C: Gamut = Gamut {
+        red: XY {
+            x: 0.6915,
+            y: 0.3083,
+        },
+        green: XY { x: 0.17, y: 0.7 },
+        blue: XY {
+            x: 0.1532,
+            y: 0.0475,
+        },
+    };
+
+    /// Whether the light can reproduce the color `point`.
+    pub fn contains(&self, point: &XY) -> bool {
+        let side = |a: &XY, b: &XY| (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x);
+        let sides = [
+            side(&self.red, &self.green),
+            side(&self.green, &self.blue),
+            side(&self.blue, &self.red),
+        ];
+        sides.iter().all(|s| *s >= 0.) || sides.iter().all(|s| *s <= 0.)
+    }
+
+    /// Returns `point` if it is in the gamut, or else the closest color on its edges.
+    pub fn clamp(&self, point: &XY) -> XY {
+        if self.contains(point) {
+            return point.clone();
+        }
+        [
+            closest_on_segment(&self.red, &self.green, point),
+            closest_on_segment(&self.green, &self.blue, point),
+            closest_on_segment(&self.blue, &self.red, point),
+        ]
+        .into_iter()
+        .min_by(|a, b| distance(a, point).total_cmp(&distance(b, point)))
+        .unwrap()
+    }
+}
+
+fn distance(a: &XY, b: &XY) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+fn closest_on_segment(a: &XY, b: &XY, point: &XY) -> XY {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let t = ((point.x - a.x) * dx + (point.y - a.y) * dy) / (dx * dx + dy * dy);
+    let t = t.clamp(0., 1.);
+    XY {
+        x: a.x + t * dx,
+        y: a.y + t * dy,
+    }
+}
+
+/// The D65 white point, used for black which has no chromaticity.
+const WHITE: XY = XY {
+    x: 0.3127,
+    y: 0.329,
+};
+
+fn gamma_expand(v: f32) -> f32 {
+    if v > 0.04045 {
+        ((v + 0.055) / 1.055).powf(2.4)
+    } else {
+        v / 12.92
+    }
+}
+
+fn gamma_compress(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        12.92 * v
+    } else {
+        1.055 * v.powf(1. / 2.4) - 0.055
+    }
+}
+
+impl XY {
+    /// The chromaticity of an sRGB color, without its brightness.
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> XY {
+        let [r, g, b] = [r, g, b].map(|v| gamma_expand(v as f32 / 255.));
+        let x = r * 0.664511 + g * 0.154324 + b * 0.162028;
+        let y = r * 0.283881 + g * 0.668433 + b * 0.047685;
+        let z = r * 0.000088 + g * 0.07231 + b * 0.986039;
+        let sum = x + y + z;
+        if sum == 0. {
+            return WHITE;
+        }
+        XY {
+            x: x / sum,
+            y: y / sum,
+        }
+    }
+
+    /// The sRGB color of a light at this chromaticity and `brightness`, in percent, as reported
+    /// by [`crate::Light::color`] and [`crate::Light::dimming`]. Colors that sRGB cannot
+    /// represent are brought within it.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for light in bridge.get_all_lights().await.unwrap() {
+    ///     if let (Some(color), Some(dimming)) = (&light.color, &light.dimming) {
+    ///         let [r, g, b] = color.xy.to_rgb(dimming.brightness);
+    ///         println!("{}: #{r:02x}{g:02x}{b:02x}", light.metadata.name);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub fn to_rgb(&self, brightness: f32) -> [u8; 3] {
+        if self.y <= 0. {
+            return [0, 0, 0];
+        }
+        let luminance = (brightness / 100.).clamp(0., 1.);
+        let x = luminance / self.y * self.x;
+        let z = luminance / self.y * (1. - self.x - self.y);
+        let rgb = [
+            x * 1.656492 - luminance * 0.354851 - z * 0.255038,
+            -x * 0.707196 + luminance * 1.655397 + z * 0.036152,
+            x * 0.051713 - luminance * 0.121364 + z * 1.01153,
+        ]
+        .map(|v| v.max(0.));
+        let max = rgb.iter().cloned().fold(0., f32::max);
+        let scale = if max > 1. { 1. / max } else { 1. };
+        rgb.map(|v| (gamma_compress(v * scale).clamp(0., 1.) * 255.).round() as u8)
+    }
+}
+
+/// Converts a color from HSV to sRGB: `hue` in degrees, `saturation` and `value` between 0 and 1.
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let hue = hue.rem_euclid(360.) / 60.;
+    let (saturation, value) = (saturation.clamp(0., 1.), value.clamp(0., 1.));
+    let chroma = value * saturation;
+    let second = chroma * (1. - (hue % 2. - 1.).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, second, 0.),
+        1 => (second, chroma, 0.),
+        2 => (0., chroma, second),
+        3 => (0., second, chroma),
+        4 => (second, 0., chroma),
+        _ => (chroma, 0., second),
+    };
+    let m = value - chroma;
+    [r, g, b].map(|v| ((v + m) * 255.).round() as u8)
+}
+
+/// Converts a color from HSL to HSV: `hue` is unchanged, the others are between 0 and 1.
+fn hsl_to_hsv(saturation: f32, lightness: f32) -> (f32, f32) {
+    let (saturation, lightness) = (saturation.clamp(0., 1.), lightness.clamp(0., 1.));
+    let value = lightness + saturation * lightness.min(1. - lightness);
+    let saturation = if value == 0. {
+        0.
+    } else {
+        2. * (1. - lightness / value)
+    };
+    (saturation, value)
+}
+
+/// Converts a color from HSL to sRGB: `hue` in degrees, `saturation` and `lightness` between 0
+/// and 1.
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let (saturation, value) = hsl_to_hsv(saturation, lightness);
+    hsv_to_rgb(hue, saturation, value)
+}
+
+/// Converts a color temperature from kelvin to mirek, the unit of the API.
+pub fn kelvin_to_mirek(kelvin: u16) -> u16 {
+    (1_000_000. / kelvin.max(1) as f32)
+        .round()
+        .min(u16::MAX as f32) as u16
+}
+
+/// Converts a color temperature from mirek to kelvin.
+pub fn mirek_to_kelvin(mirek: u16) -> u16 {
+    (1_000_000. / mirek.max(1) as f32)
+        .round()
+        .min(u16::MAX as f32) as u16
+}
+
+impl ColorTemperature {
+    /// The color temperature of the light in kelvin, if it is in color temperature mode.
+    pub fn kelvin(&self) -> Option<u16> {
+        self.mirek.filter(|_| self.mirek_valid).map(mirek_to_kelvin)
+    }
+}
+
+impl MirekSchema {
+    /// The warmest color temperature the light supports, in kelvin.
+    pub fn kelvin_minimum(&self) -> u16 {
+        mirek_to_kelvin(self.mirek_maximum)
+    }
+
+    /// The coolest color temperature the light supports, in kelvin.
+    pub fn kelvin_maximum(&self) -> u16 {
+        mirek_to_kelvin(self.mirek_minimum)
+    }
+}
+
+/// How [`ColorState::interpolate`] goes from a color to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Straight lines in the XY color space and in brightness.
+    Linear,
+    /// Straight lines in the CIE 1976 u'v' color space and in CIE lightness, where equal steps
+    /// look equally large, so a fade does not seem to rush through some colors or brightnesses.
+    #[default]
+    Perceptual,
+}
+
+/// A color and a brightness in percent.
+#[derive(Debug, Clone)]
+pub struct ColorState {
+    pub xy: XY,
+    pub brightness: f32,
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn xy_to_uv(xy: &XY) -> (f32, f32) {
+    let d = -2. * xy.x + 12. * xy.y + 3.;
+    (4. * xy.x / d, 9. * xy.y / d)
+}
+
+fn uv_to_xy(u: f32, v: f32) -> XY {
+    let d = 6. * u - 16. * v + 12.;
+    XY {
+        x: 9. * u / d,
+        y: 4. * v / d,
+    }
+}
+
+/// The CIE lightness, between 0 and 100, of a brightness in percent taken as the luminance.
+fn lightness(brightness: f32) -> f32 {
+    let y = (brightness / 100.).clamp(0., 1.);
+    if y > 0.008856 {
+        116. * y.cbrt() - 16.
+    } else {
+        903.3 * y
+    }
+}
+
+fn brightness(lightness: f32) -> f32 {
+    let y = if lightness > 8. {
+        ((lightness + 16.) / 116.).powi(3)
+    } else {
+        lightness / 903.3
+    };
+    y * 100.
+}
+
+impl ColorState {
+    pub fn new(x: f32, y: f32, brightness: f32) -> Self {
+        ColorState {
+            xy: XY { x, y },
+            brightness,
+        }
+    }
+
+    /// The state at `t`, between 0 for this state and 1 for `to`.
+    pub fn interpolate(&self, to: &ColorState, t: f32, interpolation: Interpolation) -> ColorState {
+        let t = t.clamp(0., 1.);
+        match interpolation {
+            Interpolation::Linear => ColorState::new(
+                lerp(self.xy.x, to.xy.x, t),
+                lerp(self.xy.y, to.xy.y, t),
+                lerp(self.brightness, to.brightness, t),
+            ),
+            Interpolation::Perceptual => {
+                let ((u1, v1), (u2, v2)) = (xy_to_uv(&self.xy), xy_to_uv(&to.xy));
+                ColorState {
+                    xy: uv_to_xy(lerp(u1, u2, t), lerp(v1, v2, t)),
+                    brightness: brightness(lerp(
+                        lightness(self.brightness),
+                        lightness(to.brightness),
+                        t,
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Splits the fade from this state to `to` into `steps` commands, to send one every `step`:
+    /// each has a transition time of `step`, so the light fades smoothly between them. It makes
+    /// fades longer than the bridge transition limit, or following a perceptual path.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{ColorState, Interpolation};
+    /// use std::time::Duration;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let night = ColorState::new(0.5612, 0.4042, 5.);
+    /// let day = ColorState::new(0.3227, 0.329, 100.);
+    /// // a one hour sunrise, in one minute steps
+    /// let step = Duration::from_secs(60);
+    /// for command in night.fade_to(&day, 60, step, Interpolation::Perceptual) {
+    ///     bridge
+    ///         .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &command.on())
+    ///         .await
+    ///         .unwrap();
+    ///     tokio::time::sleep(step).await;
+    /// }
+    /// # })
+    /// ```
+    pub fn fade_to(
+        &self,
+        to: &ColorState,
+        steps: u32,
+        step: std::time::Duration,
+        interpolation: Interpolation,
+    ) -> Fade {
+        Fade {
+            from: self.clone(),
+            to: to.clone(),
+            steps,
+            next: 1,
+            step_ms: step.as_millis().min(u32::MAX as u128) as u32,
+            interpolation,
+        }
+    }
+}
+
+/// The commands of a fade, see [`ColorState::fade_to`]. The last one is exactly the target.
+#[derive(Debug, Clone)]
+pub struct Fade {
+    from: ColorState,
+    to: ColorState,
+    steps: u32,
+    next: u32,
+    step_ms: u32,
+    interpolation: Interpolation,
+}
+
+impl Iterator for Fade {
+    type Item = CommandLight;
+
+    fn next(&mut self) -> Option<CommandLight> {
+        if self.next > self.steps {
+            return None;
+        }
+        let t = self.next as f32 / self.steps as f32;
+        self.next += 1;
+        let state = self.from.interpolate(&self.to, t, self.interpolation);
+        Some(
+            CommandLight::default()
+                .with_xy(state.xy.x, state.xy.y)
+                .with_brightness(state.brightness)
+                .with_transition_time(self.step_ms),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.steps + 1).saturating_sub(self.next) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Fade {}
+
+impl Color {
+    /// The gamut of the light, either reported as is or as its gamut type.
+    pub fn gamut(&self) -> Option<Gamut> {
+        if let Some(gamut) = &self.gamut {
+            return Some(gamut.clone());
+        }
+        match self.gamut_type.as_deref() {
+            Some("A") => Some(Gamut::A),
+            Some("B") => Some(Gamut::B),
+            Some("C") => Some(Gamut::C),
+            _ => None,
+        }
+    }
+}
+
+impl CommandLight {
+    /// Same as [`CommandLight::with_xy`], but brings the color within the gamut of `light` first,
+    /// so that the same color looks the same on bulbs with different gamuts. Lights without a
+    /// known gamut get the color unchanged.
+    pub fn with_xy_clamped(self, light: &Light, x: f32, y: f32) -> Self {
+        let point = XY { x, y };
+        let XY { x, y } = match light.color.as_ref().and_then(|c| c.gamut()) {
+            Some(gamut) => gamut.clamp(&point),
+            None => point,
+        };
+        self.with_xy(x, y)
+    }
+
+    /// Sets the color temperature in kelvin, such as 2700 for a warm white or 6500 for daylight.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::CommandLight;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let warm = CommandLight::default().on().with_kelvin(2700);
+    /// bridge
+    ///     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &warm)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_kelvin(self, kelvin: u16) -> Self {
+        self.with_mirek(kelvin_to_mirek(kelvin))
+    }
+
+    /// Sets the color from HSV, the model of most color pickers: `hue` in degrees, `saturation`
+    /// and `value` between 0 and 1. The value becomes the brightness of the light.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::CommandLight;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let orange = CommandLight::default().on().with_hsv(30., 1., 0.8);
+    /// bridge
+    ///     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &orange)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_hsv(self, hue: f32, saturation: f32, value: f32) -> Self {
+        let [r, g, b] = hsv_to_rgb(hue, saturation, 1.);
+        let XY { x, y } = XY::from_rgb(r, g, b);
+        self.with_xy(x, y)
+            .with_brightness(value.clamp(0., 1.) * 100.)
+    }
+
+    /// Same as [`CommandLight::with_hsv`], from HSL: `lightness` between 0 and 1.
+    pub fn with_hsl(self, hue: f32, saturation: f32, lightness: f32) -> Self {
+        let (saturation, value) = hsl_to_hsv(saturation, lightness);
+        self.with_hsv(hue, saturation, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_round_trip() {
+        let red = XY::from_rgb(255, 0, 0);
+        assert!(
+            distance(
+                &red,
+                &XY {
+                    x: 0.7006,
+                    y: 0.2993
+                }
+            ) < 0.001
+        );
+        assert_eq!(red.to_rgb(100.), [255, 0, 0]);
+        let white = XY::from_rgb(255, 255, 255);
+        let [r, g, b] = white.to_rgb(100.);
+        assert!(r >= 250 && g >= 250 && b >= 250);
+        assert_eq!(white.to_rgb(0.), [0, 0, 0]);
+    }
+
+    #[test]
+    fn hsv_and_hsl() {
+        assert_eq!(hsv_to_rgb(0., 1., 1.), [255, 0, 0]);
+        assert_eq!(hsv_to_rgb(120., 1., 1.), [0, 255, 0]);
+        assert_eq!(hsv_to_rgb(-120., 1., 1.), [0, 0, 255]);
+        assert_eq!(hsv_to_rgb(30., 0., 0.5), [128, 128, 128]);
+        assert_eq!(hsl_to_rgb(0., 1., 0.5), [255, 0, 0]);
+        assert_eq!(hsl_to_rgb(60., 1., 0.25), [128, 128, 0]);
+    }
+
+    #[test]
+    fn kelvin_and_mirek() {
+        assert_eq!(kelvin_to_mirek(2700), 370);
+        assert_eq!(kelvin_to_mirek(6500), 154);
+        assert_eq!(mirek_to_kelvin(153), 6536);
+        assert_eq!(mirek_to_kelvin(0), u16::MAX);
+    }
+
+    #[test]
+    fn fades() {
+        let from = ColorState::new(0.6, 0.35, 10.);
+        let to = ColorState::new(0.3, 0.3, 90.);
+        for interpolation in [Interpolation::Linear, Interpolation::Perceptual] {
+            let start = from.interpolate(&to, 0., interpolation);
+            assert!(distance(&start.xy, &from.xy) < 0.0001);
+            assert!((start.brightness - 10.).abs() < 0.01);
+            let commands: Vec<_> = from
+                .fade_to(&to, 4, std::time::Duration::from_secs(1), interpolation)
+                .collect();
+            assert_eq!(commands.len(), 4);
+            let last = commands.last().unwrap();
+            assert!(distance(&last.color.as_ref().unwrap().xy, &to.xy) < 0.0001);
+            assert!((last.dimming.as_ref().unwrap().brightness - 90.).abs() < 0.01);
+        }
+        // perceptually, half way is much darker than the average brightness
+        let half = from.interpolate(&to, 0.5, Interpolation::Perceptual);
+        assert!(half.brightness < 40.);
+    }
+
+    #[test]
+    fn gamut_clamping() {
+        let inside = XY { x: 0.3, y: 0.3 };
+        assert!(Gamut::B.contains(&inside));
+        let clamped = Gamut::B.clamp(&inside);
+        assert_eq!((clamped.x, clamped.y), (0.3, 0.3));
+
+        // a green that only gamut C bulbs can reproduce
+        let green = XY { x: 0.17, y: 0.7 };
+        assert!(Gamut::C.contains(&green));
+        assert!(!Gamut::B.contains(&green));
+        let clamped = Gamut::B.clamp(&green);
+        assert!(distance(&clamped, &Gamut::B.green) < 0.01);
+
+        // beyond the red corner
+        let clamped = Gamut::A.clamp(&XY { x: 0.8, y: 0.25 });
+        assert!(distance(&clamped, &Gamut::A.red) < 0.05);
+    }
+}
@@ -0,0 +1,809 @@
+//! The CSS named colors, converted to the XY color space of the API. Only their chromaticity is
+//! kept: black, the grays and white are all the white point, set the brightness separately.
+//! ### Example
+//! ```no_run
+//! # tokio_test::block_on(async {
+//! use hueclient::{colors, CommandLight};
+//! let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+//!    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+//! let command = CommandLight::default()
+//!     .on()
+//!     .with_xy(colors::CORAL.x, colors::CORAL.y);
+//! bridge
+//!     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &command)
+//!     .await
+//!     .unwrap();
+//! # })
+//! ```
+use crate::{CommandLight, XY};
+
+pub const ALICEBLUE: XY = XY {
+    x: 0.3095,
+    y: 0.3223,
+};
+pub const ANTIQUEWHITE: XY = XY {
+    x: 0.3547,
+    y: 0.3488,
+};
+pub const AQUA: XY = XY {
+    x: 0.1513,
+    y: 0.3425,
+};
+pub const AQUAMARINE: XY = XY {
+    x: 0.2134,
+    y: 0.4035,
+};
+pub const AZURE: XY = XY {
+    x: 0.3069,
+    y: 0.3303,
+};
+pub const BEIGE: XY = XY {
+    x: 0.3401,
+    y: 0.3559,
+};
+pub const BISQUE: XY = XY {
+    x: 0.3794,
+    y: 0.3599,
+};
+pub const BLACK: XY = XY {
+    x: 0.3127,
+    y: 0.329,
+};
+pub const BLANCHEDALMOND: XY = XY {
+    x: 0.3681,
+    y: 0.3585,
+};
+pub const BLUE: XY = XY {
+    x: 0.1355,
+    y: 0.0399,
+};
+pub const BLUEVIOLET: XY = XY {
+    x: 0.2524,
+    y: 0.1063,
+};
+pub const BROWN: XY = XY {
+    x: 0.635,
+    y: 0.3045,
+};
+pub const BURLYWOOD: XY = XY {
+    x: 0.4241,
+    y: 0.3821,
+};
+pub const CADETBLUE: XY = XY {
+    x: 0.2225,
+    y: 0.3327,
+};
+pub const CHARTREUSE: XY = XY {
+    x: 0.2694,
+    y: 0.6646,
+};
+pub const CHOCOLATE: XY = XY {
+    x: 0.6003,
+    y: 0.3691,
+};
+pub const CORAL: XY = XY {
+    x: 0.5754,
+    y: 0.348,
+};
+pub const CORNFLOWERBLUE: XY = XY {
+    x: 0.1913,
+    y: 0.1978,
+};
+pub const CORNSILK: XY = XY {
+    x: 0.3499,
+    y: 0.3575,
+};
+pub const CRIMSON: XY = XY {
+    x: 0.6548,
+    y: 0.2841,
+};
+pub const CYAN: XY = XY {
+    x: 0.1513,
+    y: 0.3425,
+};
+pub const DARKBLUE: XY = XY {
+    x: 0.1355,
+    y: 0.0399,
+};
+pub const DARKCYAN: XY = XY {
+    x: 0.1513,
+    y: 0.3425,
+};
+pub const DARKGOLDENROD: XY = XY {
+    x: 0.5295,
+    y: 0.4398,
+};
+pub const DARKGRAY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const DARKGREEN: XY = XY {
+    x: 0.1724,
+    y: 0.7468,
+};
+pub const DARKGREY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const DARKKHAKI: XY = XY {
+    x: 0.402,
+    y: 0.4324,
+};
+pub const DARKMAGENTA: XY = XY {
+    x: 0.3855,
+    y: 0.1546,
+};
+pub const DARKOLIVEGREEN: XY = XY {
+    x: 0.3482,
+    y: 0.4982,
+};
+pub const DARKORANGE: XY = XY {
+    x: 0.5958,
+    y: 0.3881,
+};
+pub const DARKORCHID: XY = XY {
+    x: 0.2987,
+    y: 0.1335,
+};
+pub const DARKRED: XY = XY {
+    x: 0.7006,
+    y: 0.2993,
+};
+pub const DARKSALMON: XY = XY {
+    x: 0.485,
+    y: 0.3476,
+};
+pub const DARKSEAGREEN: XY = XY {
+    x: 0.2932,
+    y: 0.4111,
+};
+pub const DARKSLATEBLUE: XY = XY {
+    x: 0.2236,
+    y: 0.1503,
+};
+pub const DARKSLATEGRAY: XY = XY {
+    x: 0.2291,
+    y: 0.3364,
+};
+pub const DARKSLATEGREY: XY = XY {
+    x: 0.2291,
+    y: 0.3364,
+};
+pub const DARKTURQUOISE: XY = XY {
+    x: 0.151,
+    y: 0.3369,
+};
+pub const DARKVIOLET: XY = XY {
+    x: 0.2853,
+    y: 0.1086,
+};
+pub const DEEPPINK: XY = XY {
+    x: 0.5468,
+    y: 0.232,
+};
+pub const DEEPSKYBLUE: XY = XY {
+    x: 0.1459,
+    y: 0.2382,
+};
+pub const DIMGRAY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const DIMGREY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const DODGERBLUE: XY = XY {
+    x: 0.1466,
+    y: 0.1631,
+};
+pub const FIREBRICK: XY = XY {
+    x: 0.6603,
+    y: 0.3025,
+};
+pub const FLORALWHITE: XY = XY {
+    x: 0.3349,
+    y: 0.3389,
+};
+pub const FORESTGREEN: XY = XY { x: 0.2, y: 0.6703 };
+pub const FUCHSIA: XY = XY {
+    x: 0.3855,
+    y: 0.1546,
+};
+pub const GAINSBORO: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const GHOSTWHITE: XY = XY {
+    x: 0.31804,
+    y: 0.3218,
+};
+pub const GOLD: XY = XY {
+    x: 0.4942,
+    y: 0.4741,
+};
+pub const GOLDENROD: XY = XY {
+    x: 0.5165,
+    y: 0.4428,
+};
+pub const GRAY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const GREEN: XY = XY {
+    x: 0.1724,
+    y: 0.7468,
+};
+pub const GREENYELLOW: XY = XY {
+    x: 0.3294,
+    y: 0.5948,
+};
+pub const GREY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const HONEYDEW: XY = XY {
+    x: 0.3165,
+    y: 0.3464,
+};
+pub const HOTPINK: XY = XY {
+    x: 0.4691,
+    y: 0.2469,
+};
+pub const INDIANRED: XY = XY {
+    x: 0.5475,
+    y: 0.3113,
+};
+pub const INDIGO: XY = XY {
+    x: 0.2485,
+    y: 0.0918,
+};
+pub const IVORY: XY = XY {
+    x: 0.3327,
+    y: 0.3444,
+};
+pub const KHAKI: XY = XY {
+    x: 0.4023,
+    y: 0.4268,
+};
+pub const LAVENDER: XY = XY {
+    x: 0.3086,
+    y: 0.3071,
+};
+pub const LAVENDERBLUSH: XY = XY {
+    x: 0.3358,
+    y: 0.3226,
+};
+pub const LAWNGREEN: XY = XY {
+    x: 0.2675,
+    y: 0.6663,
+};
+pub const LEMONCHIFFON: XY = XY {
+    x: 0.3595,
+    y: 0.3757,
+};
+pub const LIGHTBLUE: XY = XY {
+    x: 0.2615,
+    y: 0.3144,
+};
+pub const LIGHTCORAL: XY = XY {
+    x: 0.5066,
+    y: 0.3146,
+};
+pub const LIGHTCYAN: XY = XY {
+    x: 0.2901,
+    y: 0.3316,
+};
+pub const LIGHTGOLDENRODYELLOW: XY = XY {
+    x: 0.3503,
+    y: 0.3715,
+};
+pub const LIGHTGRAY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const LIGHTGREEN: XY = XY {
+    x: 0.2659,
+    y: 0.487,
+};
+pub const LIGHTGREY: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const LIGHTPINK: XY = XY {
+    x: 0.4106,
+    y: 0.3102,
+};
+pub const LIGHTSALMON: XY = XY {
+    x: 0.5016,
+    y: 0.3531,
+};
+pub const LIGHTSEAGREEN: XY = XY {
+    x: 0.1606,
+    y: 0.3594,
+};
+pub const LIGHTSKYBLUE: XY = XY {
+    x: 0.2135,
+    y: 0.2737,
+};
+pub const LIGHTSLATEGRAY: XY = XY {
+    x: 0.2734,
+    y: 0.299,
+};
+pub const LIGHTSLATEGREY: XY = XY {
+    x: 0.2734,
+    y: 0.299,
+};
+pub const LIGHTSTEELBLUE: XY = XY {
+    x: 0.2765,
+    y: 0.2962,
+};
+pub const LIGHTYELLOW: XY = XY {
+    x: 0.3436,
+    y: 0.3612,
+};
+pub const LIME: XY = XY {
+    x: 0.1724,
+    y: 0.7468,
+};
+pub const LIMEGREEN: XY = XY {
+    x: 0.1961,
+    y: 0.6809,
+};
+pub const LINEN: XY = XY {
+    x: 0.3411,
+    y: 0.3386,
+};
+pub const MAGENTA: XY = XY {
+    x: 0.3855,
+    y: 0.1546,
+};
+pub const MAROON: XY = XY {
+    x: 0.7006,
+    y: 0.2993,
+};
+pub const MEDIUMAQUAMARINE: XY = XY {
+    x: 0.2148,
+    y: 0.4032,
+};
+pub const MEDIUMBLUE: XY = XY {
+    x: 0.1355,
+    y: 0.0399,
+};
+pub const MEDIUMORCHID: XY = XY {
+    x: 0.3363,
+    y: 0.1743,
+};
+pub const MEDIUMPURPLE: XY = XY {
+    x: 0.263,
+    y: 0.1773,
+};
+pub const MEDIUMSEAGREEN: XY = XY {
+    x: 0.1963,
+    y: 0.5002,
+};
+pub const MEDIUMSLATEBLUE: XY = XY {
+    x: 0.2185,
+    y: 0.1421,
+};
+pub const MEDIUMSPRINGGREEN: XY = XY {
+    x: 0.1609,
+    y: 0.5269,
+};
+pub const MEDIUMTURQUOISE: XY = XY {
+    x: 0.1767,
+    y: 0.3496,
+};
+pub const MEDIUMVIOLETRED: XY = XY {
+    x: 0.5051,
+    y: 0.2151,
+};
+pub const MIDNIGHTBLUE: XY = XY {
+    x: 0.1616,
+    y: 0.0802,
+};
+pub const MINTCREAM: XY = XY {
+    x: 0.3154,
+    y: 0.3351,
+};
+pub const MISTYROSE: XY = XY {
+    x: 0.3563,
+    y: 0.3296,
+};
+pub const MOCCASIN: XY = XY {
+    x: 0.3913,
+    y: 0.3756,
+};
+pub const NAVAJOWHITE: XY = XY {
+    x: 0.402,
+    y: 0.3768,
+};
+pub const NAVY: XY = XY {
+    x: 0.1355,
+    y: 0.0399,
+};
+pub const OLDLACE: XY = XY {
+    x: 0.342,
+    y: 0.3439,
+};
+pub const OLIVE: XY = XY {
+    x: 0.4442,
+    y: 0.5166,
+};
+pub const OLIVEDRAB: XY = XY {
+    x: 0.3539,
+    y: 0.5561,
+};
+pub const ORANGE: XY = XY {
+    x: 0.5622,
+    y: 0.4166,
+};
+pub const ORANGERED: XY = XY {
+    x: 0.6725,
+    y: 0.3231,
+};
+pub const ORCHID: XY = XY {
+    x: 0.3716,
+    y: 0.2103,
+};
+pub const PALEGOLDENROD: XY = XY {
+    x: 0.3763,
+    y: 0.3979,
+};
+pub const PALEGREEN: XY = XY {
+    x: 0.2658,
+    y: 0.4874,
+};
+pub const PALETURQUOISE: XY = XY {
+    x: 0.2531,
+    y: 0.3345,
+};
+pub const PALEVIOLETRED: XY = XY {
+    x: 0.4658,
+    y: 0.2773,
+};
+pub const PAPAYAWHIP: XY = XY {
+    x: 0.3598,
+    y: 0.3547,
+};
+pub const PEACHPUFF: XY = XY {
+    x: 0.3948,
+    y: 0.3597,
+};
+pub const PERU: XY = XY {
+    x: 0.5303,
+    y: 0.3919,
+};
+pub const PINK: XY = XY {
+    x: 0.3948,
+    y: 0.3114,
+};
+pub const PLUM: XY = XY {
+    x: 0.3495,
+    y: 0.2545,
+};
+pub const POWDERBLUE: XY = XY {
+    x: 0.2626,
+    y: 0.3255,
+};
+pub const PURPLE: XY = XY {
+    x: 0.3855,
+    y: 0.1546,
+};
+pub const REBECCAPURPLE: XY = XY {
+    x: 0.2703,
+    y: 0.1398,
+};
+pub const RED: XY = XY {
+    x: 0.7006,
+    y: 0.2993,
+};
+pub const ROSYBROWN: XY = XY {
+    x: 0.4005,
+    y: 0.3229,
+};
+pub const ROYALBLUE: XY = XY {
+    x: 0.1661,
+    y: 0.135,
+};
+pub const SADDLEBROWN: XY = XY {
+    x: 0.5943,
+    y: 0.3706,
+};
+pub const SALMON: XY = XY {
+    x: 0.5348,
+    y: 0.3256,
+};
+pub const SANDYBROWN: XY = XY {
+    x: 0.5092,
+    y: 0.3838,
+};
+pub const SEAGREEN: XY = XY {
+    x: 0.198,
+    y: 0.4984,
+};
+pub const SEASHELL: XY = XY {
+    x: 0.3386,
+    y: 0.3353,
+};
+pub const SIENNA: XY = XY {
+    x: 0.5697,
+    y: 0.3576,
+};
+pub const SILVER: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const SKYBLUE: XY = XY {
+    x: 0.2201,
+    y: 0.2934,
+};
+pub const SLATEBLUE: XY = XY {
+    x: 0.2196,
+    y: 0.1444,
+};
+pub const SLATEGRAY: XY = XY {
+    x: 0.2736,
+    y: 0.2992,
+};
+pub const SLATEGREY: XY = XY {
+    x: 0.2736,
+    y: 0.2992,
+};
+pub const SNOW: XY = XY {
+    x: 0.3281,
+    y: 0.3286,
+};
+pub const SPRINGGREEN: XY = XY {
+    x: 0.1643,
+    y: 0.5906,
+};
+pub const STEELBLUE: XY = XY {
+    x: 0.1855,
+    y: 0.2344,
+};
+pub const TAN: XY = XY {
+    x: 0.4059,
+    y: 0.3754,
+};
+pub const TEAL: XY = XY {
+    x: 0.1513,
+    y: 0.3425,
+};
+pub const THISTLE: XY = XY {
+    x: 0.3342,
+    y: 0.2971,
+};
+pub const TOMATO: XY = XY {
+    x: 0.6112,
+    y: 0.3261,
+};
+pub const TURQUOISE: XY = XY {
+    x: 0.1709,
+    y: 0.3693,
+};
+pub const VIOLET: XY = XY {
+    x: 0.3645,
+    y: 0.2128,
+};
+pub const WHEAT: XY = XY {
+    x: 0.3849,
+    y: 0.3735,
+};
+pub const WHITE: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const WHITESMOKE: XY = XY {
+    x: 0.3227,
+    y: 0.329,
+};
+pub const YELLOW: XY = XY {
+    x: 0.4442,
+    y: 0.5166,
+};
+pub const YELLOWGREEN: XY = XY {
+    x: 0.3525,
+    y: 0.5626,
+};
+
+/// All the named colors, by their lowercase CSS name.
+pub const NAMED_COLORS: &[(&str, XY)] = &[
+    ("aliceblue", ALICEBLUE),
+    ("antiquewhite", ANTIQUEWHITE),
+    ("aqua", AQUA),
+    ("aquamarine", AQUAMARINE),
+    ("azure", AZURE),
+    ("beige", BEIGE),
+    ("bisque", BISQUE),
+    ("black", BLACK),
+    ("blanchedalmond", BLANCHEDALMOND),
+    ("blue", BLUE),
+    ("blueviolet", BLUEVIOLET),
+    ("brown", BROWN),
+    ("burlywood", BURLYWOOD),
+    ("cadetblue", CADETBLUE),
+    ("chartreuse", CHARTREUSE),
+    ("chocolate", CHOCOLATE),
+    ("coral", CORAL),
+    ("cornflowerblue", CORNFLOWERBLUE),
+    ("cornsilk", CORNSILK),
+    ("crimson", CRIMSON),
+    ("cyan", CYAN),
+    ("darkblue", DARKBLUE),
+    ("darkcyan", DARKCYAN),
+    ("darkgoldenrod", DARKGOLDENROD),
+    ("darkgray", DARKGRAY),
+    ("darkgreen", DARKGREEN),
+    ("darkgrey", DARKGREY),
+    ("darkkhaki", DARKKHAKI),
+    ("darkmagenta", DARKMAGENTA),
+    ("darkolivegreen", DARKOLIVEGREEN),
+    ("darkorange", DARKORANGE),
+    ("darkorchid", DARKORCHID),
+    ("darkred", DARKRED),
+    ("darksalmon", DARKSALMON),
+    ("darkseagreen", DARKSEAGREEN),
+    ("darkslateblue", DARKSLATEBLUE),
+    ("darkslategray", DARKSLATEGRAY),
+    ("darkslategrey", DARKSLATEGREY),
+    ("darkturquoise", DARKTURQUOISE),
+    ("darkviolet", DARKVIOLET),
+    ("deeppink", DEEPPINK),
+    ("deepskyblue", DEEPSKYBLUE),
+    ("dimgray", DIMGRAY),
+    ("dimgrey", DIMGREY),
+    ("dodgerblue", DODGERBLUE),
+    ("firebrick", FIREBRICK),
+    ("floralwhite", FLORALWHITE),
+    ("forestgreen", FORESTGREEN),
+    ("fuchsia", FUCHSIA),
+    ("gainsboro", GAINSBORO),
+    ("ghostwhite", GHOSTWHITE),
+    ("gold", GOLD),
+    ("goldenrod", GOLDENROD),
+    ("gray", GRAY),
+    ("green", GREEN),
+    ("greenyellow", GREENYELLOW),
+    ("grey", GREY),
+    ("honeydew", HONEYDEW),
+    ("hotpink", HOTPINK),
+    ("indianred", INDIANRED),
+    ("indigo", INDIGO),
+    ("ivory", IVORY),
+    ("khaki", KHAKI),
+    ("lavender", LAVENDER),
+    ("lavenderblush", LAVENDERBLUSH),
+    ("lawngreen", LAWNGREEN),
+    ("lemonchiffon", LEMONCHIFFON),
+    ("lightblue", LIGHTBLUE),
+    ("lightcoral", LIGHTCORAL),
+    ("lightcyan", LIGHTCYAN),
+    ("lightgoldenrodyellow", LIGHTGOLDENRODYELLOW),
+    ("lightgray", LIGHTGRAY),
+    ("lightgreen", LIGHTGREEN),
+    ("lightgrey", LIGHTGREY),
+    ("lightpink", LIGHTPINK),
+    ("lightsalmon", LIGHTSALMON),
+    ("lightseagreen", LIGHTSEAGREEN),
+    ("lightskyblue", LIGHTSKYBLUE),
+    ("lightslategray", LIGHTSLATEGRAY),
+    ("lightslategrey", LIGHTSLATEGREY),
+    ("lightsteelblue", LIGHTSTEELBLUE),
+    ("lightyellow", LIGHTYELLOW),
+    ("lime", LIME),
+    ("limegreen", LIMEGREEN),
+    ("linen", LINEN),
+    ("magenta", MAGENTA),
+    ("maroon", MAROON),
+    ("mediumaquamarine", MEDIUMAQUAMARINE),
+    ("mediumblue", MEDIUMBLUE),
+    ("mediumorchid", MEDIUMORCHID),
+    ("mediumpurple", MEDIUMPURPLE),
+    ("mediumseagreen", MEDIUMSEAGREEN),
+    ("mediumslateblue", MEDIUMSLATEBLUE),
+    ("mediumspringgreen", MEDIUMSPRINGGREEN),
+    ("mediumturquoise", MEDIUMTURQUOISE),
+    ("mediumvioletred", MEDIUMVIOLETRED),
+    ("midnightblue", MIDNIGHTBLUE),
+    ("mintcream", MINTCREAM),
+    ("mistyrose", MISTYROSE),
+    ("moccasin", MOCCASIN),
+    ("navajowhite", NAVAJOWHITE),
+    ("navy", NAVY),
+    ("oldlace", OLDLACE),
+    ("olive", OLIVE),
+    ("olivedrab", OLIVEDRAB),
+    ("orange", ORANGE),
+    ("orangered", ORANGERED),
+    ("orchid", ORCHID),
+    ("palegoldenrod", PALEGOLDENROD),
+    ("palegreen", PALEGREEN),
+    ("paleturquoise", PALETURQUOISE),
+    ("palevioletred", PALEVIOLETRED),
+    ("papayawhip", PAPAYAWHIP),
+    ("peachpuff", PEACHPUFF),
+    ("peru", PERU),
+    ("pink", PINK),
+    ("plum", PLUM),
+    ("powderblue", POWDERBLUE),
+    ("purple", PURPLE),
+    ("rebeccapurple", REBECCAPURPLE),
+    ("red", RED),
+    ("rosybrown", ROSYBROWN),
+    ("royalblue", ROYALBLUE),
+    ("saddlebrown", SADDLEBROWN),
+    ("salmon", SALMON),
+    ("sandybrown", SANDYBROWN),
+    ("seagreen", SEAGREEN),
+    ("seashell", SEASHELL),
+    ("sienna", SIENNA),
+    ("silver", SILVER),
+    ("skyblue", SKYBLUE),
+    ("slateblue", SLATEBLUE),
+    ("slategray", SLATEGRAY),
+    ("slategrey", SLATEGREY),
+    ("snow", SNOW),
+    ("springgreen", SPRINGGREEN),
+    ("steelblue", STEELBLUE),
+    ("tan", TAN),
+    ("teal", TEAL),
+    ("thistle", THISTLE),
+    ("tomato", TOMATO),
+    ("turquoise", TURQUOISE),
+    ("violet", VIOLET),
+    ("wheat", WHEAT),
+    ("white", WHITE),
+    ("whitesmoke", WHITESMOKE),
+    ("yellow", YELLOW),
+    ("yellowgreen", YELLOWGREEN),
+];
+
+/// Returns the named color called `name`, ignoring case, spaces, dashes and underscores, so that
+/// `Light Sea Green` is found as well as `lightseagreen`.
+pub fn lookup(name: &str) -> Option<XY> {
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    NAMED_COLORS
+        .iter()
+        .find(|(named, _)| *named == name)
+        .map(|(_, xy)| xy.clone())
+}
+
+impl CommandLight {
+    /// Sets the color to a CSS named color, see [`lookup`]. Returns `None` for unknown names.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::CommandLight;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let command = CommandLight::default().on().with_named_color("coral").unwrap();
+    /// bridge
+    ///     .set_light_state("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &command)
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn with_named_color(self, name: &str) -> Option<Self> {
+        lookup(name).map(|XY { x, y }| self.with_xy(x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_colors() {
+        let coral = lookup("Coral").unwrap();
+        assert_eq!((coral.x, coral.y), (CORAL.x, CORAL.y));
+        assert!(lookup("light-sea green").is_some());
+        assert!(lookup("not a color").is_none());
+        let red = XY::from_rgb(255, 0, 0);
+        assert!((red.x - RED.x).abs() < 0.0001 && (red.y - RED.y).abs() < 0.0001);
+    }
+}
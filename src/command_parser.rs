@@ -14,13 +14,18 @@ pub fn parse_command(args: Vec<String>) -> CommandLight {
         "off" => CommandLight::default().off(),
         _ if re_triplet.is_match(command) => {
             log::debug!("HSV triplet: {command}");
-            todo!("HSV support");
-            // let caps = re_triplet.captures(command).unwrap();
-            // let mut command = LightPut::default().on();
-            // command.bri = caps.get(1).and_then(|s| s.as_str().parse::<u8>().ok());
-            // command.hue = caps.get(2).and_then(|s| s.as_str().parse::<u16>().ok());
-            // command.sat = caps.get(3).and_then(|s| s.as_str().parse::<u8>().ok());
-            // command
+            let caps = re_triplet.captures(command).unwrap();
+            let component = |i: usize, max: f32| {
+                caps.get(i)
+                    .and_then(|s| s.as_str().parse::<f32>().ok())
+                    .map_or(1., |v| v / max)
+            };
+            // the brightness, hue and saturation ranges of the v1 API
+            let (value, hue, saturation) =
+                (component(1, 254.), component(2, 65535.), component(3, 254.));
+            CommandLight::default()
+                .on()
+                .with_hsv(hue * 360., saturation, value)
         }
         _ if re_mired.is_match(command) => {
             log::debug!("Mired: {command}");
@@ -38,13 +43,8 @@ pub fn parse_command(args: Vec<String>) -> CommandLight {
             log::debug!("Kelvin: {command}");
             let caps = re_kelvin.captures(command).unwrap();
             let mut command = CommandLight::default().on();
-            if let Some(mirek) = caps.get(1).and_then(|s| {
-                s.as_str()
-                    .parse::<u32>()
-                    .ok()
-                    .map(|k| (1000000u32 / k) as u16)
-            }) {
-                command = command.with_mirek(mirek)
+            if let Some(kelvin) = caps.get(1).and_then(|s| s.as_str().parse::<u16>().ok()) {
+                command = command.with_kelvin(kelvin)
             }
             if let Some(brightness) = caps.get(2).and_then(|s| s.as_str().parse::<f32>().ok()) {
                 command = command.with_brightness(brightness)
@@ -53,19 +53,17 @@ pub fn parse_command(args: Vec<String>) -> CommandLight {
         }
         _ if re_rrggbb.is_match(command) => {
             log::debug!("RRGGBB: {command}");
-            todo!("HSV support");
-            // let caps = re_rrggbb.captures(command).unwrap();
-            // let mut command = LightPut::default().on();
-            // let rgb: Vec<u8> = [caps.get(1), caps.get(2), caps.get(3)]
-            //     .iter()
-            //     .map(|s| u8::from_str_radix(s.unwrap().as_str(), 16).unwrap())
-            //     .collect();
-            // let hsv = rgb_to_hsv(rgb[0], rgb[1], rgb[2]);
-            // println!("{:?}", hsv);
-            // command.hue = Some((hsv.0 * 65535f64) as u16);
-            // command.sat = Some((hsv.1 * 255f64) as u8);
-            // command.bri = Some((hsv.2 * 255f64) as u8);
-            // command
+            let caps = re_rrggbb.captures(command).unwrap();
+            let rgb: Vec<u8> = [caps.get(1), caps.get(2), caps.get(3)]
+                .iter()
+                .map(|s| u8::from_str_radix(s.unwrap().as_str(), 16).unwrap())
+                .collect();
+            let (hue, saturation, value) = rgb_to_hsv(rgb[0], rgb[1], rgb[2]);
+            CommandLight::default().on().with_hsv(
+                hue as f32 * 360.,
+                saturation as f32,
+                value as f32,
+            )
         }
         _ if re_xy.is_match(command) => {
             log::debug!("XY: {command}");
@@ -79,6 +77,13 @@ pub fn parse_command(args: Vec<String>) -> CommandLight {
             }
             command
         }
+        _ if crate::colors::lookup(command).is_some() => {
+            log::debug!("Named color: {command}");
+            CommandLight::default()
+                .on()
+                .with_named_color(command)
+                .unwrap()
+        }
         _ => panic!("can not understand command {:?}", command),
     };
     if args.len() == 5 {
@@ -89,7 +94,6 @@ pub fn parse_command(args: Vec<String>) -> CommandLight {
     parsed
 }
 
-#[allow(dead_code)]
 fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
     let r = r as f64 / 255f64;
     let g = g as f64 / 255f64;
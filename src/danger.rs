@@ -0,0 +1,119 @@
+//! Disruptive operations on the bridge, kept apart from the rest of the API so that they are
+//! never called by accident.
+use crate::{Bridge, BridgeResponse};
+use serde::Serialize;
+use serde_json::Value;
+
+/// The explicit acknowledgement that an operation of [`DangerZone`] is disruptive. It can only
+/// be created with [`DangerConfirmation::i_know_what_i_am_doing`], and every operation consumes
+/// one, so each call site has to confirm on its own.
+#[derive(Debug)]
+pub struct DangerConfirmation(());
+
+impl DangerConfirmation {
+    pub fn i_know_what_i_am_doing() -> Self {
+        DangerConfirmation(())
+    }
+}
+
+/// The disruptive operations of a bridge, see [`Bridge::danger_zone`].
+#[derive(Debug, Clone, Copy)]
+pub struct DangerZone<'a> {
+    bridge: &'a Bridge,
+}
+
+impl Bridge {
+    /// Gives access to operations that disrupt the whole home, such as rebooting the bridge.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::DangerConfirmation;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// bridge
+    ///     .danger_zone()
+    ///     .reboot(DangerConfirmation::i_know_what_i_am_doing())
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn danger_zone(&self) -> DangerZone<'_> {
+        DangerZone { bridge: self }
+    }
+}
+
+impl DangerZone<'_> {
+    async fn put_config(&self, command: &impl Serialize) -> crate::Result<()> {
+        let bridge = self.bridge;
+        let url = format!(
+            "https://{}/api/{}/config",
+            bridge.host(),
+            bridge.application_key
+        );
+        let resp: BridgeResponse<Value> =
+            bridge.send(bridge.client.put(&url).json(command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Reboots the bridge. Lights keep their state, but nothing can be controlled for about a
+    /// minute and the event streams are closed. Firmwares that do not allow it return a
+    /// `HueError::BridgeError`.
+    pub async fn reboot(&self, _: DangerConfirmation) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct Reboot {
+            reboot: bool,
+        }
+        log::warn!("rebooting the bridge at {}", self.bridge.ip);
+        self.put_config(&Reboot { reboot: true }).await
+    }
+
+    /// Starts a touchlink: lights within about 30 cm of the bridge are reset to their factory
+    /// settings, losing their pairing with any other bridge or remote, then paired to this bridge.
+    pub async fn touchlink(&self, _: DangerConfirmation) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct Touchlink {
+            touchlink: bool,
+        }
+        log::warn!("starting a touchlink on the bridge at {}", self.bridge.ip);
+        self.put_config(&Touchlink { touchlink: true }).await
+    }
+
+    /// Resets the bridge to its factory settings: every device, room, zone, scene, automation
+    /// and application key is removed, including the key of this `Bridge`. The API has no
+    /// documented command for this, firmwares that do not accept it return a
+    /// `HueError::BridgeError`, and the button at the back of the bridge has to be used instead.
+    pub async fn factory_reset(&self, _: DangerConfirmation) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct FactoryReset {
+            factoryreset: bool,
+        }
+        log::warn!(
+            "resetting the bridge at {} to its factory settings",
+            self.bridge.ip
+        );
+        self.put_config(&FactoryReset { factoryreset: true }).await
+    }
+
+    /// Deletes every device but the bridge itself. They have to be reset and paired again to be
+    /// used, and the rooms, zones and scenes referring to them are emptied. A device that cannot
+    /// be deleted does not stop the others from being deleted, the ids of the failed devices are
+    /// returned with their error.
+    pub async fn delete_all_devices(
+        &self,
+        _: DangerConfirmation,
+    ) -> crate::Result<Vec<(String, crate::HueError)>> {
+        log::warn!("deleting all devices of the bridge at {}", self.bridge.ip);
+        let mut failures = vec![];
+        for device in self.bridge.get_all_devices().await? {
+            if device.services.iter().any(|s| s.rtype == "bridge") {
+                continue;
+            }
+            if let Err(e) = self.bridge.delete_device(&device.id).await {
+                log::warn!("could not delete device {}: {e}", device.id);
+                failures.push((device.id, e));
+            }
+        }
+        Ok(failures)
+    }
+}
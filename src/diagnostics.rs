@@ -0,0 +1,146 @@
+//! Checking that a light does what it is told.
+use crate::{Bridge, CommandLight, Light};
+use std::time::Duration;
+
+// how long to wait for a light to settle before reading its state back
+const PAUSE: Duration = Duration::from_secs(2);
+const XY_TOLERANCE: f32 = 0.01;
+const BRIGHTNESS_TOLERANCE: f32 = 1.;
+
+/// A step of [`Bridge::run_light_test`] where the light did not end up in the requested state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightTestMismatch {
+    pub step: String,
+    pub expected: String,
+    pub observed: String,
+}
+
+#[derive(Debug, Clone)]
+enum Expected {
+    Brightness(f32),
+    Mirek(u16),
+    Xy(f32, f32),
+}
+
+impl Expected {
+    fn check(&self, light: &Light) -> Option<String> {
+        match self {
+            Expected::Brightness(expected) => {
+                let observed = light.dimming.as_ref().map(|d| d.brightness);
+                match observed {
+                    Some(b) if (b - expected).abs() <= BRIGHTNESS_TOLERANCE => None,
+                    _ => Some(format!("brightness {observed:?}")),
+                }
+            }
+            Expected::Mirek(expected) => {
+                let observed = light.color_temperature.as_ref().and_then(|ct| ct.mirek);
+                (observed != Some(*expected)).then(|| format!("mirek {observed:?}"))
+            }
+            Expected::Xy(x, y) => {
+                let observed = light.color.as_ref().map(|c| (c.xy.x, c.xy.y));
+                match observed {
+                    Some((ox, oy))
+                        if (ox - x).abs() <= XY_TOLERANCE && (oy - y).abs() <= XY_TOLERANCE =>
+                    {
+                        None
+                    }
+                    _ => Some(format!("xy {observed:?}")),
+                }
+            }
+        }
+    }
+}
+
+/// The steps to run on `light`, depending on its capabilities.
+fn steps(light: &Light) -> Vec<(String, CommandLight, Expected)> {
+    let mut steps = vec![];
+    if let Some(dimming) = &light.dimming {
+        let min = dimming.min_dim_level.unwrap_or(0.).max(1.);
+        for brightness in [100., min] {
+            steps.push((
+                format!("brightness {brightness}"),
+                CommandLight::default().on().with_brightness(brightness),
+                Expected::Brightness(brightness),
+            ));
+        }
+    }
+    if let Some(ct) = &light.color_temperature {
+        let schema = &ct.mirek_schema;
+        // cool daylight, neutral and candle light white points, and the extremes
+        let mut mireks = vec![153, 250, 454, schema.mirek_minimum, schema.mirek_maximum];
+        mireks.sort_unstable();
+        mireks.dedup();
+        for mirek in mireks {
+            if (schema.mirek_minimum..=schema.mirek_maximum).contains(&mirek) {
+                steps.push((
+                    format!("white point {mirek} mirek"),
+                    CommandLight::default().on().with_mirek(mirek),
+                    Expected::Mirek(mirek),
+                ));
+            }
+        }
+    }
+    if light.color.is_some() {
+        let primaries = [
+            ("red", 0.675, 0.322),
+            ("green", 0.4091, 0.518),
+            ("blue", 0.167, 0.04),
+        ];
+        for (name, x, y) in primaries {
+            steps.push((
+                format!("primary {name}"),
+                CommandLight::default().on().with_xy(x, y),
+                Expected::Xy(x, y),
+            ));
+        }
+    }
+    steps
+}
+
+impl Bridge {
+    /// Cycles a light through its minimum and maximum brightness, white points and primary
+    /// colors, reading its state back after each step, then restores its initial state, even if a
+    /// step fails. Returns the steps where the light did not reach the requested state, typically
+    /// because of a capability it does not have, such as a smaller color gamut.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let mismatches = bridge
+    ///     .run_light_test("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f")
+    ///     .await
+    ///     .unwrap();
+    /// for mismatch in mismatches {
+    ///     println!("{}: expected {}, got {}", mismatch.step, mismatch.expected, mismatch.observed);
+    /// }
+    /// # })
+    /// ```
+    pub async fn run_light_test(&self, light: &str) -> crate::Result<Vec<LightTestMismatch>> {
+        let initial = self.get_light(light).await?;
+        let tested = async {
+            let mut mismatches = vec![];
+            for (step, command, expected) in steps(&initial) {
+                self.set_light_state(light, &command).await?;
+                tokio::time::sleep(PAUSE).await;
+                let observed = self.get_light(light).await?;
+                if let Some(observed) = expected.check(&observed) {
+                    mismatches.push(LightTestMismatch {
+                        step,
+                        expected: format!("{expected:?}"),
+                        observed,
+                    });
+                }
+            }
+            Ok::<_, crate::HueError>(mismatches)
+        }
+        .await;
+        // restored even when a step failed, whose error is the one returned
+        let restored = self
+            .set_light_state(light, &CommandLight::from_light(&initial))
+            .await;
+        let mismatches = tested?;
+        restored?;
+        Ok(mismatches)
+    }
+}
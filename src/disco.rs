@@ -1,19 +1,19 @@
-use crate::{HueError, HueError::DiscoveryError};
-use futures::executor::block_on;
+use crate::{Bridge, HueError, HueError::DiscoveryError, UnauthBridge};
+use dns_parser::{QueryClass, QueryType};
 use futures_util::{pin_mut, stream::StreamExt};
-use mdns::{Record, RecordKind};
+use mdns::{Record, RecordKind, Response};
 use serde_json::{Map, Value};
-use std::{net::IpAddr, time::Duration};
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
 
 // As Per instrucitons at
 // https://developers.meethue.com/develop/application-design-guidance/hue-bridge-discovery/
-pub async fn discover_hue_bridge() -> Result<IpAddr, HueError> {
-    let bridge_ftr = discover_hue_bridge_m_dns();
-    let bridge = block_on(bridge_ftr);
+pub async fn discover_hue_bridge() -> Result<BridgeInfo, HueError> {
+    let bridge = discover_hue_bridge_m_dns().await;
     match bridge {
-        Ok(bridge_ip) => {
-            log::info!("discovered bridge at {bridge_ip} using mDNS");
-            Ok(bridge_ip)
+        Ok(bridge) => {
+            log::info!("discovered bridge at {} using mDNS", bridge.ip);
+            Ok(bridge)
         }
         Err(mdns_error) => {
             log::debug!(
@@ -22,9 +22,9 @@ pub async fn discover_hue_bridge() -> Result<IpAddr, HueError> {
             );
             let n_upnp_result = discover_hue_bridge_n_upnp().await;
             match n_upnp_result {
-                Ok(bridge_ip) => {
-                    log::info!("discovered bridge at {bridge_ip} using n-upnp");
-                    Ok(bridge_ip)
+                Ok(bridge) => {
+                    log::info!("discovered bridge at {} using n-upnp", bridge.ip);
+                    Ok(bridge)
                 }
                 Err(nupnp_error) => {
                     log::debug!("Failed to discover bridge using or n-upnp: {nupnp_error}");
@@ -37,35 +37,151 @@ pub async fn discover_hue_bridge() -> Result<IpAddr, HueError> {
     }
 }
 
-pub async fn discover_hue_bridge_n_upnp() -> Result<IpAddr, HueError> {
+/// A bridge found on the network, with what it advertises about itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeInfo {
+    pub ip: IpAddr,
+    /// The unique id of the bridge, such as `ecb5fafffe123456`, in lowercase.
+    pub bridge_id: Option<String>,
+    /// The model of the bridge, such as `BSB002` for the square second generation bridge.
+    pub model_id: Option<String>,
+    /// The host name of the bridge, from mDNS.
+    pub host_name: Option<String>,
+    /// The HTTPS port of the bridge, from mDNS or the discovery service.
+    pub port: Option<u16>,
+}
+
+impl BridgeInfo {
+    /// Reads the configuration of the bridge at this address, and checks that it is the bridge
+    /// that was advertised, which stale discovery service entries pointing at an address reused
+    /// by another device or bridge are not. The missing id and model are filled in.
+    pub async fn verify(mut self) -> Result<BridgeInfo, HueError> {
+        let bridge = self.unauth_bridge();
+        let config = tokio::time::timeout(VERIFY_TIMEOUT, bridge.get_config())
+            .await
+            .map_err(|_| DiscoveryError {
+                msg: format!("no bridge answered at {}", self.ip),
+            })??;
+        let bridge_id = config.bridge_id.to_lowercase();
+        if let Some(advertised) = &self.bridge_id {
+            if *advertised != bridge_id {
+                return Err(DiscoveryError {
+                    msg: format!(
+                        "expected bridge {advertised} at {}, found {bridge_id}",
+                        self.ip
+                    ),
+                });
+            }
+        }
+        self.bridge_id = Some(bridge_id);
+        self.model_id = self.model_id.or(Some(config.model_id));
+        Ok(self)
+    }
+
+    /// A bridge at this address, validating its certificate with the advertised id if any.
+    pub fn unauth_bridge(&self) -> UnauthBridge {
+        let bridge = Bridge::for_ip(self.ip);
+        match &self.bridge_id {
+            Some(bridge_id) => bridge.with_bridge_id(bridge_id),
+            None => bridge,
+        }
+    }
+
+    fn is_same_bridge(&self, other: &BridgeInfo) -> bool {
+        match (
+            &self.bridge_id,
+            &other.bridge_id,
+            &self.host_name,
+            &other.host_name,
+        ) {
+            (Some(id), Some(other_id), _, _) => id == other_id,
+            (_, _, Some(host), Some(other_host)) => host == other_host,
+            _ => self.ip == other.ip,
+        }
+    }
+}
+
+fn push_unique(bridges: &mut Vec<BridgeInfo>, bridge: BridgeInfo) {
+    if !bridges.iter().any(|known| known.is_same_bridge(&bridge)) {
+        bridges.push(bridge);
+    }
+}
+
+/// Returns the bridges found with mDNS within `timeout`, followed by the ones only known to the
+/// n-upnp discovery service.
+pub async fn discover_all_hue_bridges(timeout: Duration) -> Vec<BridgeInfo> {
+    let mut bridges = discover_hue_bridges_m_dns(timeout)
+        .await
+        .unwrap_or_else(|e| {
+            log::debug!("Error in mDNS discovery: {e}");
+            vec![]
+        });
+    match discover_hue_bridges_n_upnp().await {
+        Ok(n_upnp_bridges) => {
+            for bridge in n_upnp_bridges {
+                push_unique(&mut bridges, bridge);
+            }
+        }
+        Err(e) => log::debug!("Error in n-upnp discovery: {e}"),
+    }
+    log::info!("discovered bridges {bridges:?}");
+    bridges
+}
+
+pub async fn discover_hue_bridges_n_upnp() -> Result<Vec<BridgeInfo>, HueError> {
     let objects: Vec<Map<String, Value>> = reqwest::get("https://discovery.meethue.com/")
         .await?
         .json()
         .await?;
+    parse_n_upnp(&objects)
+}
 
-    if objects.is_empty() {
-        Err(DiscoveryError {
-            msg: "expected non-empty array".into(),
-        })?
-    }
-    let object = &objects[0];
+fn parse_n_upnp(objects: &[Map<String, Value>]) -> Result<Vec<BridgeInfo>, HueError> {
+    objects
+        .iter()
+        .map(|object| {
+            let ip = object
+                .get("internalipaddress")
+                .and_then(Value::as_str)
+                .ok_or(DiscoveryError {
+                    msg: "expect a string in internalipaddress".into(),
+                })?
+                .parse()?;
+            Ok(BridgeInfo {
+                ip,
+                bridge_id: object
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .map(str::to_lowercase),
+                model_id: None,
+                host_name: None,
+                port: object
+                    .get("port")
+                    .and_then(Value::as_u64)
+                    .and_then(|port| port.try_into().ok()),
+            })
+        })
+        .collect()
+}
 
-    let ip = object.get("internalipaddress").ok_or(DiscoveryError {
-        msg: "Expected internalipaddress".into(),
-    })?;
-    Ok(ip
-        .as_str()
+pub async fn discover_hue_bridge_n_upnp() -> Result<BridgeInfo, HueError> {
+    discover_hue_bridges_n_upnp()
+        .await?
+        .into_iter()
+        .next()
         .ok_or(DiscoveryError {
-            msg: "expect a string in internalipaddress".into(),
-        })?
-        .parse()?)
+            msg: "expected non-empty array".into(),
+        })
 }
 
 // Define the service name for hue bridge
 const SERVICE_NAME: &str = "_hue._tcp.local";
+const MULTICAST_ADDR_V6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+const MULTICAST_PORT: u16 = 5353;
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(2);
 
 // Define a function that discovers a hue bridge using mDNS
-pub async fn discover_hue_bridge_m_dns() -> Result<IpAddr, HueError> {
+pub async fn discover_hue_bridge_m_dns() -> Result<BridgeInfo, HueError> {
     // Iterate through responses from each hue bridge device, asking for new devices every 15s
     let stream_disc = mdns::discover::all(SERVICE_NAME, Duration::from_secs(1));
     let stream = match stream_disc {
@@ -79,17 +195,10 @@ pub async fn discover_hue_bridge_m_dns() -> Result<IpAddr, HueError> {
     pin_mut!(stream);
     let response = async_std::future::timeout(Duration::from_secs(5), stream.next()).await;
     match response {
-        Ok(Some(Ok(response))) => {
-            // Get the first IP address from the response
-            let ip = response
-                .records()
-                .filter_map(to_ip_addr)
-                .next()
-                .ok_or(DiscoveryError {
-                    msg: "No IP address found in response".into(),
-                })?;
-            Ok(ip)
-        }
+        // the id of the bridge comes from the TXT record, to validate its certificate
+        Ok(Some(Ok(response))) => validate_response(&response).ok_or(DiscoveryError {
+            msg: "No IP address found in response".into(),
+        }),
         Ok(Some(Err(e))) => Err(DiscoveryError { msg: e.to_string() }),
         Ok(None) => Err(DiscoveryError {
             msg: "No response from bridge".into(),
@@ -100,6 +209,106 @@ pub async fn discover_hue_bridge_m_dns() -> Result<IpAddr, HueError> {
     }
 }
 
+/// Returns all the bridges answering mDNS queries within `timeout`, over IPv4 and IPv6.
+pub async fn discover_hue_bridges_m_dns(timeout: Duration) -> Result<Vec<BridgeInfo>, HueError> {
+    let (v4, v6) = futures::join!(m_dns_v4_responses(timeout), m_dns_v6_responses(timeout));
+    let v6 = v6.unwrap_or_else(|e| {
+        log::debug!("Error in IPv6 mDNS discovery: {e}");
+        vec![]
+    });
+    let mut bridges = vec![];
+    for bridge in v4?.iter().chain(&v6).filter_map(validate_response) {
+        push_unique(&mut bridges, bridge);
+    }
+    Ok(bridges)
+}
+
+async fn m_dns_v4_responses(timeout: Duration) -> Result<Vec<Response>, HueError> {
+    let stream = mdns::discover::all(SERVICE_NAME, Duration::from_secs(1))
+        .map_err(|e| DiscoveryError { msg: e.to_string() })?
+        .listen();
+    pin_mut!(stream);
+    let mut responses = vec![];
+    let collect = async {
+        while let Some(response) = stream.next().await {
+            match response {
+                Ok(response) => responses.push(response),
+                Err(e) => log::debug!("Error in mDNS response: {e}"),
+            }
+        }
+    };
+    // the stream never ends, the timeout is the normal way out
+    let _ = async_std::future::timeout(timeout, collect).await;
+    Ok(responses)
+}
+
+/// Sends a one-shot query to the IPv6 mDNS group from an ephemeral port, which the bridges answer
+/// directly, see section 5.1 of RFC 6762.
+async fn m_dns_v6_responses(timeout: Duration) -> Result<Vec<Response>, HueError> {
+    let socket = async_std::net::UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0)).await?;
+    let mut query = dns_parser::Builder::new_query(0, false);
+    query.add_question(SERVICE_NAME, false, QueryType::PTR, QueryClass::IN);
+    let query = query.build().map_err(|_| DiscoveryError {
+        msg: "mDNS query truncated".into(),
+    })?;
+    socket
+        .send_to(&query, (MULTICAST_ADDR_V6, MULTICAST_PORT))
+        .await?;
+    let mut responses = vec![];
+    let mut buffer = vec![0; 4096];
+    let collect = async {
+        loop {
+            match socket.recv_from(&mut buffer).await {
+                Ok((len, _)) => match dns_parser::Packet::parse(&buffer[..len]) {
+                    Ok(packet) => responses.push(Response::from_packet(&packet)),
+                    Err(e) => log::debug!("Error in mDNS response: {e}"),
+                },
+                Err(e) => return log::debug!("Error in IPv6 mDNS discovery: {e}"),
+            }
+        }
+    };
+    let _ = async_std::future::timeout(timeout, collect).await;
+    Ok(responses)
+}
+
+// link-local IPv6 addresses need a zone index, which URLs cannot carry
+fn is_ipv6_link_local(ip: &IpAddr) -> bool {
+    matches!(ip, IpAddr::V6(ip) if ip.segments()[0] & 0xffc0 == 0xfe80)
+}
+
+/// Returns the bridge that sent an mDNS response, or `None` if it is not a response from a
+/// bridge. IPv4 addresses are preferred over IPv6 ones.
+fn validate_response(response: &Response) -> Option<BridgeInfo> {
+    let is_bridge = response
+        .records()
+        .any(|record| matches!(record.kind, RecordKind::PTR(_)) && record.name == SERVICE_NAME);
+    if !is_bridge {
+        return None;
+    }
+    let ip = response
+        .records()
+        .filter_map(to_ip_addr)
+        .filter(|ip| !is_ipv6_link_local(ip))
+        .min_by_key(IpAddr::is_ipv6)?;
+    let txt = |key: &str| {
+        response.txt_records().find_map(|entry| {
+            let (entry_key, value) = entry.split_once('=')?;
+            (entry_key == key).then(|| value.to_string())
+        })
+    };
+    let srv = response.records().find_map(|record| match &record.kind {
+        RecordKind::SRV { port, target, .. } => Some((*port, target.clone())),
+        _ => None,
+    });
+    Some(BridgeInfo {
+        ip,
+        bridge_id: txt("bridgeid").map(|id| id.to_lowercase()),
+        model_id: txt("modelid"),
+        host_name: srv.as_ref().map(|(_, target)| target.clone()),
+        port: srv.map(|(port, _)| port),
+    })
+}
+
 // Define a helper function that converts a record to an IP address
 fn to_ip_addr(record: &Record) -> Option<IpAddr> {
     match record.kind {
@@ -113,12 +322,92 @@ fn to_ip_addr(record: &Record) -> Option<IpAddr> {
 mod tests {
     use super::*;
 
+    fn response(id: &str, ips: &[&str]) -> Response {
+        let record = |name: &str, kind| Record {
+            name: name.into(),
+            class: dns_parser::Class::IN,
+            ttl: 120,
+            kind,
+        };
+        let instance = format!("Hue Bridge - {}._hue._tcp.local", &id[10..]);
+        let mut answers = vec![
+            record(SERVICE_NAME, RecordKind::PTR(instance.clone())),
+            record(
+                &instance,
+                RecordKind::TXT(vec![format!("bridgeid={id}"), "modelid=BSB002".into()]),
+            ),
+            record(
+                &instance,
+                RecordKind::SRV {
+                    priority: 0,
+                    weight: 0,
+                    port: 443,
+                    target: format!("{id}.local"),
+                },
+            ),
+        ];
+        for ip in ips {
+            let kind = match ip.parse().unwrap() {
+                IpAddr::V4(ip) => RecordKind::A(ip),
+                IpAddr::V6(ip) => RecordKind::AAAA(ip),
+            };
+            answers.push(record(&format!("{id}.local"), kind));
+        }
+        Response {
+            answers,
+            nameservers: vec![],
+            additional: vec![],
+        }
+    }
+
+    #[test]
+    fn responses_are_parsed() {
+        let bridge = validate_response(&response("ecb5fafffe1a2b3c", &["192.168.1.2"])).unwrap();
+        assert_eq!(bridge.ip.to_string(), "192.168.1.2");
+        assert_eq!(bridge.bridge_id.as_deref(), Some("ecb5fafffe1a2b3c"));
+        assert_eq!(bridge.model_id.as_deref(), Some("BSB002"));
+        assert_eq!(bridge.host_name.as_deref(), Some("ecb5fafffe1a2b3c.local"));
+        assert_eq!(bridge.port, Some(443));
+        // link-local IPv6 addresses are skipped
+        let bridge = validate_response(&response("ecb5fafffe4d5e6f", &["fe80::3", "fd00::3"]));
+        assert_eq!(bridge.unwrap().ip.to_string(), "fd00::3");
+    }
+
+    #[test]
+    fn same_bridge_is_listed_once() {
+        let mut bridges = vec![];
+        for response in [
+            response("ecb5fafffe1a2b3c", &["192.168.1.2"]),
+            response("ecb5fafffe1a2b3c", &["fd00::2"]),
+            response("ecb5fafffe4d5e6f", &["fd00::3"]),
+        ] {
+            push_unique(&mut bridges, validate_response(&response).unwrap());
+        }
+        let n_upnp: Vec<Map<String, Value>> = serde_json::from_value(serde_json::json!([
+            { "id": "ECB5FAFFFE1A2B3C", "internalipaddress": "192.168.1.2", "port": 443 },
+            { "id": "ecb5fafffe4d5e6f", "internalipaddress": "192.168.1.3", "port": 443 },
+            { "id": "001788fffe7a8b9c", "internalipaddress": "192.168.1.4", "port": 443 },
+            { "internalipaddress": "192.168.1.2" }
+        ]))
+        .unwrap();
+        for bridge in parse_n_upnp(&n_upnp).unwrap() {
+            push_unique(&mut bridges, bridge);
+        }
+        let ips: Vec<_> = bridges.iter().map(|b| b.ip.to_string()).collect();
+        assert_eq!(ips, ["192.168.1.2", "fd00::3", "192.168.1.4"]);
+        // the mDNS entries, which know the host name, are kept
+        assert_eq!(
+            bridges[0].host_name.as_deref(),
+            Some("ecb5fafffe1a2b3c.local")
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_discover_hue_bridge() {
-        let ip = discover_hue_bridge().await;
-        assert!(ip.is_ok());
-        let ip = ip.unwrap();
-        assert_eq!(ip.to_string(), "192.168.1.149");
+        let bridge = discover_hue_bridge().await;
+        assert!(bridge.is_ok());
+        let bridge = bridge.unwrap();
+        assert_eq!(bridge.ip.to_string(), "192.168.1.149");
     }
 }
@@ -0,0 +1,231 @@
+//! Annotating events with the names of the resources they are about and of their room.
+use crate::{Bridge, EventData, HueEvent, Resource, ResourceIdentifier};
+#[cfg(feature = "events")]
+use futures::{stream, Stream, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A change on a resource, along with the names of the resource and of its room.
+#[derive(Debug, Clone)]
+pub struct EnrichedEvent {
+    pub data: EventData,
+    /// The name of the resource, or of its device for services such as a motion sensor.
+    pub name: Option<String>,
+    /// The room of the resource or of its device. A room is its own room.
+    pub room_id: Option<String>,
+    pub room_name: Option<String>,
+}
+
+/// The names of the devices, rooms and zones, and the room of each device, used to enrich
+/// events. Kept up to date with the additions, renames and deletions of the event stream.
+#[derive(Debug, Clone, Default)]
+pub struct EventNames {
+    names: HashMap<String, String>,
+    // service id to the device, room or zone it belongs to
+    owners: HashMap<String, String>,
+    // device or room id to room id
+    rooms: HashMap<String, String>,
+}
+
+impl EventNames {
+    /// Fetches the devices, rooms and zones.
+    pub async fn fetch(bridge: &Bridge) -> crate::Result<EventNames> {
+        let mut names = EventNames::default();
+        for device in bridge.get_all_devices().await? {
+            names.add(Resource::Device(device));
+        }
+        for room in bridge.get_all_rooms().await? {
+            names.add(Resource::Room(room));
+        }
+        for zone in bridge.get_all_zones().await? {
+            names.add(Resource::Zone(zone));
+        }
+        Ok(names)
+    }
+
+    fn add(&mut self, resource: Resource) {
+        let (id, name, services) = match resource {
+            Resource::Device(device) => (device.id, device.metadata.name, device.services),
+            Resource::Room(room) => {
+                self.set_room_children(&room.id, &room.children);
+                self.rooms.insert(room.id.clone(), room.id.clone());
+                (room.id, room.metadata.name, room.services)
+            }
+            Resource::Zone(zone) => (zone.id, zone.metadata.name, zone.services),
+            _ => return,
+        };
+        for service in services {
+            self.owners.insert(service.rid, id.clone());
+        }
+        self.names.insert(id, name);
+    }
+
+    fn set_room_children(&mut self, room: &str, children: &[ResourceIdentifier]) {
+        self.rooms.retain(|id, owner| owner != room || id == room);
+        for child in children {
+            self.rooms.insert(child.rid.clone(), room.to_string());
+        }
+    }
+
+    fn remove(&mut self, id: &str) {
+        self.names.remove(id);
+        self.owners.remove(id);
+        // the services of a deleted device or group, and the devices of a deleted room
+        self.owners.retain(|_, owner| owner != id);
+        self.rooms.remove(id);
+        self.rooms.retain(|_, room| room != id);
+    }
+
+    /// Records the additions, renames and deletions of devices, rooms and zones.
+    pub fn record_event(&mut self, event: &HueEvent) {
+        match event {
+            HueEvent::Add(resources) => {
+                for resource in resources {
+                    self.add(resource.clone());
+                }
+            }
+            HueEvent::Delete(identifiers) => {
+                for identifier in identifiers {
+                    self.remove(&identifier.rid);
+                }
+            }
+            HueEvent::Update(data) => {
+                for data in data {
+                    match data {
+                        EventData::Room(change) | EventData::Zone(change) => {
+                            let name = change.metadata.as_ref().and_then(|m| m.name.as_ref());
+                            if let Some(name) = name {
+                                self.names.insert(change.id.clone(), name.clone());
+                            }
+                            if let (EventData::Room(_), Some(children)) = (data, &change.children) {
+                                self.set_room_children(&change.id, children);
+                            }
+                        }
+                        // devices are not modeled in events, but their renames are still useful
+                        EventData::Unknown { rtype, raw } if rtype == "device" => {
+                            let id = raw.get("id").and_then(Value::as_str);
+                            let name = raw.pointer("/metadata/name").and_then(Value::as_str);
+                            if let (Some(id), Some(name)) = (id, name) {
+                                self.names.insert(id.to_string(), name.to_string());
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            HueEvent::Error(_) => (),
+        }
+    }
+
+    /// Annotates a change with the names of its resource and room.
+    pub fn enrich(&self, data: EventData) -> EnrichedEvent {
+        let id = data.id().unwrap_or_default();
+        let owner = self.owners.get(id).map(String::as_str).unwrap_or(id);
+        let room_id = self.rooms.get(owner).cloned();
+        EnrichedEvent {
+            name: self.names.get(owner).cloned(),
+            room_name: room_id
+                .as_ref()
+                .and_then(|room| self.names.get(room))
+                .cloned(),
+            room_id,
+            data,
+        }
+    }
+}
+
+#[cfg(feature = "events")]
+impl Bridge {
+    /// Same as [`Bridge::events`], but each change carries the name of its resource and of its
+    /// room. Additions and deletions only update the names, and errors are skipped.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use futures::StreamExt;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let mut events = Box::pin(bridge.enriched_events().await.unwrap());
+    /// while let Some(event) = events.next().await {
+    ///     if let hueclient::EventData::Motion(_) = event.data {
+    ///         println!("motion on {:?} in {:?}", event.name, event.room_name);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn enriched_events(&self) -> crate::Result<impl Stream<Item = EnrichedEvent>> {
+        let events = self.events()?;
+        let mut names = EventNames::fetch(self).await?;
+        Ok(events.flat_map(move |event| {
+            names.record_event(&event);
+            let enriched: Vec<EnrichedEvent> = match event {
+                HueEvent::Update(data) => data.into_iter().map(|data| names.enrich(data)).collect(),
+                _ => vec![],
+            };
+            stream::iter(enriched)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn update(data: Value) -> HueEvent {
+        HueEvent::Update(vec![serde_json::from_value(data).unwrap()])
+    }
+
+    #[test]
+    fn names_follow_renames() {
+        let mut names = EventNames::default();
+        names.add(
+            serde_json::from_value(json!({
+                "type": "device", "id": "d1",
+                "metadata": { "name": "Hallway sensor", "archetype": "unknown_archetype" },
+                "services": [{ "rid": "m1", "rtype": "motion" }]
+            }))
+            .unwrap(),
+        );
+        names.add(
+            serde_json::from_value(json!({
+                "type": "room", "id": "r1", "metadata": { "name": "Hallway", "archetype": "hallway" },
+                "children": [{ "rid": "d1", "rtype": "device" }],
+                "services": [{ "rid": "g1", "rtype": "grouped_light" }]
+            }))
+            .unwrap(),
+        );
+        let motion = json!({ "type": "motion", "id": "m1", "motion": {
+            "motion": true, "motion_valid": true
+        }});
+
+        let enriched = names.enrich(serde_json::from_value(motion.clone()).unwrap());
+        assert_eq!(enriched.name.as_deref(), Some("Hallway sensor"));
+        assert_eq!(enriched.room_name.as_deref(), Some("Hallway"));
+
+        names.record_event(&update(json!({
+            "type": "room", "id": "r1", "metadata": { "name": "Entrance" }
+        })));
+        names.record_event(&update(json!({
+            "type": "device", "id": "d1", "metadata": { "name": "Entrance sensor" }
+        })));
+        let enriched = names.enrich(serde_json::from_value(motion.clone()).unwrap());
+        assert_eq!(enriched.name.as_deref(), Some("Entrance sensor"));
+        assert_eq!(enriched.room_id.as_deref(), Some("r1"));
+        assert_eq!(enriched.room_name.as_deref(), Some("Entrance"));
+
+        let deleted = |rid: &str, rtype: &str| {
+            HueEvent::Delete(vec![ResourceIdentifier {
+                rid: rid.into(),
+                rtype: rtype.into(),
+            }])
+        };
+        names.record_event(&deleted("r1", "room"));
+        let enriched = names.enrich(serde_json::from_value(motion.clone()).unwrap());
+        assert_eq!(enriched.name.as_deref(), Some("Entrance sensor"));
+        assert_eq!(enriched.room_id, None);
+        names.record_event(&deleted("d1", "device"));
+        assert!(names.owners.is_empty());
+        let enriched = names.enrich(serde_json::from_value(motion).unwrap());
+        assert_eq!(enriched.name, None);
+    }
+}
@@ -0,0 +1,685 @@
+//! Entertainment configurations, and keeping normal commands away from streamed lights.
+use crate::{
+    Bridge, BridgeResponseV2, CommandGroupedLight, CommandLight, Entertainment, EventData,
+    HueEvent, Resource, ResourceIdentifier,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntertainmentConfigurationMetadata {
+    pub name: String,
+}
+
+string_enum! {
+    /// What an entertainment area is laid out for.
+    pub enum EntertainmentConfigurationType {
+        /// Lights around a TV.
+        Screen => "screen",
+        /// Lights around a computer monitor.
+        Monitor => "monitor",
+        Music => "music",
+        /// Lights placed anywhere in a room.
+        Space3d => "3dspace",
+    }
+}
+
+/// An entertainment area, whose lights can be streamed to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntertainmentConfiguration {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub metadata: EntertainmentConfigurationMetadata,
+    pub configuration_type: Option<EntertainmentConfigurationType>,
+    /// `active` while a client is streaming to the area, `inactive` otherwise.
+    pub status: String,
+    /// The application streaming to the area, while it is active.
+    pub active_streamer: Option<ResourceIdentifier>,
+    /// The lights of the area.
+    #[serde(default)]
+    pub light_services: Vec<ResourceIdentifier>,
+    /// The channels colors are streamed to.
+    #[serde(default)]
+    pub channels: Vec<EntertainmentChannel>,
+}
+
+/// A position in an entertainment area, each coordinate going from -1 to 1: `x` from left to
+/// right, `y` from the back to the front, and `z` from the floor to the ceiling.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// The positions of an entertainment service in an entertainment area. Gradient lights have a
+/// position per segment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceLocation {
+    /// The entertainment service, see [`Bridge::get_all_entertainment_services`].
+    pub service: ResourceIdentifier,
+    pub positions: Vec<Position>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EntertainmentLocations {
+    pub service_locations: Vec<ServiceLocation>,
+}
+
+/// An entertainment area to create with [`Bridge::create_entertainment_configuration`], or to
+/// replace an existing one with [`Bridge::update_entertainment_configuration`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewEntertainmentConfiguration {
+    pub metadata: EntertainmentConfigurationMetadata,
+    pub configuration_type: EntertainmentConfigurationType,
+    pub locations: EntertainmentLocations,
+}
+
+impl NewEntertainmentConfiguration {
+    /// A new entertainment area, without any light.
+    pub fn new(
+        name: impl Into<String>,
+        configuration_type: EntertainmentConfigurationType,
+    ) -> Self {
+        NewEntertainmentConfiguration {
+            metadata: EntertainmentConfigurationMetadata { name: name.into() },
+            configuration_type,
+            locations: EntertainmentLocations::default(),
+        }
+    }
+
+    /// Adds the light rendering the entertainment service with the id `service` at `position`.
+    pub fn with_service(mut self, service: impl Into<String>, position: Position) -> Self {
+        self.locations.service_locations.push(ServiceLocation {
+            service: ResourceIdentifier {
+                rid: service.into(),
+                rtype: "entertainment".into(),
+            },
+            positions: vec![position],
+        });
+        self
+    }
+}
+
+/// An entertainment service rendering a channel, and the index of its segment doing so.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntertainmentChannelMember {
+    pub service: ResourceIdentifier,
+    pub index: u32,
+}
+
+/// A channel of an entertainment area, that is one color of a stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntertainmentChannel {
+    pub channel_id: u8,
+    pub position: Position,
+    #[serde(default)]
+    pub members: Vec<EntertainmentChannelMember>,
+}
+
+/// A light, or a segment of a gradient light, rendering a channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelLight {
+    /// The id of the light.
+    pub light: String,
+    /// The index of the segment of the light, 0 for lights that have a single one.
+    pub segment: u32,
+}
+
+/// A channel of an entertainment area, with its position and the lights rendering it, as
+/// returned by [`Bridge::get_entertainment_channels`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMapping {
+    pub channel_id: u8,
+    pub position: Position,
+    pub lights: Vec<ChannelLight>,
+}
+
+impl EntertainmentConfiguration {
+    pub fn is_streaming(&self) -> bool {
+        self.status == "active"
+    }
+
+    /// Returns the channel with the id `channel_id`, if the area has it.
+    pub fn channel(&self, channel_id: u8) -> Option<&EntertainmentChannel> {
+        self.channels
+            .iter()
+            .find(|channel| channel.channel_id == channel_id)
+    }
+
+    /// Maps the channels of this area to the lights rendering them, given the entertainment
+    /// services of the bridge. Members whose service is unknown, or does not render a light, are
+    /// left out.
+    pub fn map_channels(&self, services: &[Entertainment]) -> Vec<ChannelMapping> {
+        let lights: HashMap<&str, &str> = services
+            .iter()
+            .filter_map(|service| Some((service.id.as_str(), service.get_light()?)))
+            .collect();
+        self.channels
+            .iter()
+            .map(|channel| ChannelMapping {
+                channel_id: channel.channel_id,
+                position: channel.position,
+                lights: channel
+                    .members
+                    .iter()
+                    .filter_map(|member| {
+                        Some(ChannelLight {
+                            light: lights.get(member.service.rid.as_str())?.to_string(),
+                            segment: member.index,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+impl Bridge {
+    /// Returns a vector of all entertainment configurations that are registered at this
+    /// `Bridge`, sorted by their id's.
+    pub async fn get_all_entertainment_configurations(
+        &self,
+    ) -> crate::Result<Vec<EntertainmentConfiguration>> {
+        self.get_resources().await
+    }
+
+    pub async fn get_entertainment_configuration(
+        &self,
+        id: &str,
+    ) -> crate::Result<EntertainmentConfiguration> {
+        self.get_resource(id).await
+    }
+
+    /// Creates an entertainment area, for instance a temporary one to stream to from a test rig.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{EntertainmentConfigurationType, NewEntertainmentConfiguration, Position};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let mut area = NewEntertainmentConfiguration::new(
+    ///     "Test rig",
+    ///     EntertainmentConfigurationType::Space3d,
+    /// );
+    /// for (i, service) in bridge
+    ///     .get_all_entertainment_services()
+    ///     .await
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .filter(|service| service.renderer)
+    ///     .enumerate()
+    /// {
+    ///     let position = Position { x: i as f32 / 10. - 0.5, y: 0., z: 0. };
+    ///     area = area.with_service(service.id, position);
+    /// }
+    /// let created = bridge.create_entertainment_configuration(&area).await.unwrap();
+    /// bridge.start_entertainment_configuration(&created.rid).await.unwrap();
+    /// // stream...
+    /// bridge.stop_entertainment_configuration(&created.rid).await.unwrap();
+    /// bridge.delete_entertainment_configuration(&created.rid).await.unwrap();
+    /// # })
+    /// ```
+    pub async fn create_entertainment_configuration(
+        &self,
+        configuration: &NewEntertainmentConfiguration,
+    ) -> crate::Result<ResourceIdentifier> {
+        let url = format!(
+            "https://{}/clip/v2/resource/entertainment_configuration",
+            self.host()
+        );
+        let resp: BridgeResponseV2<ResourceIdentifier> = self
+            .send(self.client.post(&url).json(configuration))
+            .await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a created resource"))
+    }
+
+    /// Replaces the name, type and lights of the entertainment area with the id `id`.
+    pub async fn update_entertainment_configuration(
+        &self,
+        id: &str,
+        configuration: &NewEntertainmentConfiguration,
+    ) -> crate::Result<()> {
+        let url = format!(
+            "https://{}/clip/v2/resource/entertainment_configuration/{}",
+            self.host(),
+            id
+        );
+        let resp: BridgeResponseV2<serde_json::Value> =
+            self.send(self.client.put(&url).json(configuration)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    pub async fn delete_entertainment_configuration(&self, id: &str) -> crate::Result<()> {
+        self.delete_resource("entertainment_configuration", id)
+            .await
+    }
+
+    /// Starts the entertainment area with the id `id`: its lights stop answering normal commands
+    /// and wait for a stream, see [`Bridge::start_streaming`] with the `entertainment` feature.
+    pub async fn start_entertainment_configuration(&self, id: &str) -> crate::Result<()> {
+        self.put_entertainment_action(id, "start").await
+    }
+
+    /// Stops the entertainment area with the id `id`, giving its lights back to the other
+    /// applications.
+    pub async fn stop_entertainment_configuration(&self, id: &str) -> crate::Result<()> {
+        self.put_entertainment_action(id, "stop").await
+    }
+
+    pub(crate) async fn put_entertainment_action(
+        &self,
+        configuration: &str,
+        action: &str,
+    ) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct Action<'a> {
+            action: &'a str,
+        }
+        let url = format!(
+            "https://{}/clip/v2/resource/entertainment_configuration/{}",
+            self.host(),
+            configuration
+        );
+        let resp: BridgeResponseV2<serde_json::Value> = self
+            .send(self.client.put(&url).json(&Action { action }))
+            .await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Returns the channels of the entertainment configuration with the id `configuration`, with
+    /// their positions and the lights rendering them.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let channels = bridge
+    ///     .get_entertainment_channels("1a8d99cc-967b-44f2-9202-43f976c0fa6b")
+    ///     .await
+    ///     .unwrap();
+    /// for channel in channels.iter().filter(|channel| channel.position.x < 0.) {
+    ///     println!("channel {} is on the left", channel.channel_id);
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_entertainment_channels(
+        &self,
+        configuration: &str,
+    ) -> crate::Result<Vec<ChannelMapping>> {
+        let (configuration, services) = futures::try_join!(
+            self.get_resource::<EntertainmentConfiguration>(configuration),
+            self.get_all_entertainment_services(),
+        )?;
+        Ok(configuration.map_channels(&services))
+    }
+
+    /// Returns the ids of the lights of the entertainment areas being streamed to. The bridge
+    /// ignores normal commands to these lights, or fights with the stream over them.
+    pub async fn get_streamed_lights(&self) -> crate::Result<HashSet<String>> {
+        Ok(self
+            .get_all_entertainment_configurations()
+            .await?
+            .into_iter()
+            .filter(EntertainmentConfiguration::is_streaming)
+            .flat_map(|configuration| configuration.light_services)
+            .map(|light| light.rid)
+            .collect())
+    }
+
+    /// Same as [`Bridge::set_light_state`], but returns `HueError::LightStreamed` instead of
+    /// sending the command if the light is part of an active entertainment stream, according to
+    /// `streamed`.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandLight, HueError, StreamedLights};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let streamed = StreamedLights::fetch(&bridge).await.unwrap();
+    /// let command = CommandLight::default().on().with_brightness(80.);
+    /// match bridge
+    ///     .set_light_state_unless_streamed("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f", &command, &streamed)
+    ///     .await
+    /// {
+    ///     Err(HueError::LightStreamed { light }) => println!("{light} is busy with a stream"),
+    ///     result => result.unwrap(),
+    /// }
+    /// # })
+    /// ```
+    pub async fn set_light_state_unless_streamed(
+        &self,
+        light: &str,
+        command: &CommandLight,
+        streamed: &StreamedLights,
+    ) -> crate::Result<()> {
+        if streamed.contains(light) {
+            log::warn!("not sending a command to light {light}, it is being streamed to");
+            return Err(crate::HueError::LightStreamed {
+                light: light.to_string(),
+            });
+        }
+        self.set_light_state(light, command).await
+    }
+
+    /// Same as [`Bridge::set_group_state`], but returns `HueError::LightStreamed` instead of
+    /// sending the command if any light of the room or zone is part of an active entertainment
+    /// stream, according to `streamed`.
+    pub async fn set_group_state_unless_streamed(
+        &self,
+        group: &str,
+        command: &CommandGroupedLight,
+        streamed: &StreamedLights,
+    ) -> crate::Result<()> {
+        if let Some(light) = streamed.streamed_in_group(group) {
+            log::warn!(
+                "not sending a command to group {group}, its light {light} is being streamed to"
+            );
+            return Err(crate::HueError::LightStreamed {
+                light: light.to_string(),
+            });
+        }
+        self.set_group_state(group, command).await
+    }
+}
+
+/// The lights of the entertainment areas being streamed to, kept up to date with events, so that
+/// commands can be checked against it without a request each.
+#[derive(Debug, Clone, Default)]
+pub struct StreamedLights {
+    // entertainment configuration id to its lights
+    configurations: HashMap<String, Vec<String>>,
+    // ids of the entertainment configurations being streamed to
+    streaming: HashSet<String>,
+    // grouped light id to the lights of its room or zone
+    groups: HashMap<String, Vec<String>>,
+}
+
+impl StreamedLights {
+    /// Fetches the entertainment configurations, and the lights of the rooms and zones. Changes
+    /// of rooms and zones are not tracked afterwards.
+    pub async fn fetch(bridge: &Bridge) -> crate::Result<StreamedLights> {
+        let mut streamed = StreamedLights::default();
+        for configuration in bridge.get_all_entertainment_configurations().await? {
+            streamed.add_configuration(&configuration);
+        }
+        let devices = bridge.index_all_devices().await?;
+        for room in bridge.get_all_rooms().await? {
+            let lights: Vec<String> = room
+                .children
+                .iter()
+                .filter_map(|child| devices.get(&child.rid))
+                .flat_map(|device| device.get_lights())
+                .map(|light| light.to_string())
+                .collect();
+            streamed.add_group(&room.services, lights);
+        }
+        for zone in bridge.get_all_zones().await? {
+            let lights = zone
+                .children
+                .iter()
+                .filter(|child| child.rtype == "light")
+                .map(|child| child.rid.clone())
+                .collect();
+            streamed.add_group(&zone.services, lights);
+        }
+        Ok(streamed)
+    }
+
+    fn add_configuration(&mut self, configuration: &EntertainmentConfiguration) {
+        let lights = configuration
+            .light_services
+            .iter()
+            .map(|light| light.rid.clone())
+            .collect();
+        self.configurations.insert(configuration.id.clone(), lights);
+        if configuration.is_streaming() {
+            self.streaming.insert(configuration.id.clone());
+        } else {
+            self.streaming.remove(&configuration.id);
+        }
+    }
+
+    fn add_group(&mut self, services: &[ResourceIdentifier], lights: Vec<String>) {
+        if let Some(grouped_light) = services.iter().find(|s| s.rtype == "grouped_light") {
+            self.groups.insert(grouped_light.rid.clone(), lights);
+        }
+    }
+
+    /// Returns true if the light is part of an active entertainment stream.
+    pub fn contains(&self, light: &str) -> bool {
+        self.streaming
+            .iter()
+            .filter_map(|id| self.configurations.get(id))
+            .any(|lights| lights.iter().any(|l| l == light))
+    }
+
+    /// Returns a light of the grouped light `group` that is part of an active entertainment
+    /// stream, if any.
+    pub fn streamed_in_group(&self, group: &str) -> Option<&str> {
+        self.groups
+            .get(group)?
+            .iter()
+            .find(|light| self.contains(light))
+            .map(|light| light.as_str())
+    }
+
+    /// Updates the streamed lights with an event: entertainment areas starting or stopping to be
+    /// streamed to, or being created, changed or deleted.
+    pub fn record_event(&mut self, event: &HueEvent) {
+        match event {
+            HueEvent::Add(resources) => {
+                for resource in resources {
+                    if let Resource::EntertainmentConfiguration(configuration) = resource {
+                        self.add_configuration(configuration);
+                    }
+                }
+            }
+            HueEvent::Update(data) => {
+                for data in data {
+                    let EventData::Unknown { rtype, raw } = data else {
+                        continue;
+                    };
+                    if rtype != "entertainment_configuration" {
+                        continue;
+                    }
+                    let Some(id) = raw.get("id").and_then(|id| id.as_str()) else {
+                        continue;
+                    };
+                    match raw.get("status").and_then(|status| status.as_str()) {
+                        Some("active") => {
+                            self.streaming.insert(id.to_string());
+                        }
+                        Some(_) => {
+                            self.streaming.remove(id);
+                        }
+                        None => (),
+                    }
+                    if let Some(Ok(lights)) = raw
+                        .get("light_services")
+                        .map(Vec::<ResourceIdentifier>::deserialize)
+                    {
+                        let lights = lights.into_iter().map(|light| light.rid).collect();
+                        self.configurations.insert(id.to_string(), lights);
+                    }
+                }
+            }
+            HueEvent::Delete(identifiers) => {
+                for identifier in identifiers {
+                    if identifier.rtype == "entertainment_configuration" {
+                        self.configurations.remove(&identifier.rid);
+                        self.streaming.remove(&identifier.rid);
+                    }
+                }
+            }
+            HueEvent::Error(_) => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn channels_to_lights() {
+        let configuration: EntertainmentConfiguration = serde_json::from_value(json!({
+            "id": "c1",
+            "metadata": { "name": "TV" },
+            "status": "inactive",
+            "channels": [
+                {
+                    "channel_id": 0,
+                    "position": { "x": -0.5, "y": 0.8, "z": 0 },
+                    "members": [{ "service": { "rid": "e1", "rtype": "entertainment" }, "index": 0 }]
+                },
+                {
+                    "channel_id": 1,
+                    "position": { "x": 0.5, "y": 0.8, "z": 0 },
+                    "members": [
+                        { "service": { "rid": "e2", "rtype": "entertainment" }, "index": 2 },
+                        { "service": { "rid": "bridge", "rtype": "entertainment" }, "index": 0 }
+                    ]
+                }
+            ]
+        }))
+        .unwrap();
+        let services: Vec<Entertainment> = serde_json::from_value(json!([
+            {
+                "id": "e1",
+                "owner": { "rid": "d1", "rtype": "device" },
+                "renderer": true,
+                "renderer_reference": { "rid": "l1", "rtype": "light" },
+                "proxy": false
+            },
+            {
+                "id": "e2",
+                "owner": { "rid": "d2", "rtype": "device" },
+                "renderer": true,
+                "renderer_reference": { "rid": "l2", "rtype": "light" },
+                "proxy": false
+            },
+            {
+                "id": "bridge",
+                "owner": { "rid": "d3", "rtype": "device" },
+                "renderer": false,
+                "proxy": true
+            }
+        ]))
+        .unwrap();
+        assert_eq!(configuration.channel(1).unwrap().position.x, 0.5);
+        assert!(configuration.channel(2).is_none());
+        let channels = configuration.map_channels(&services);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(
+            channels[0].lights,
+            vec![ChannelLight {
+                light: "l1".into(),
+                segment: 0
+            }]
+        );
+        assert_eq!(configuration.configuration_type, None);
+        assert_eq!(
+            channels[1].lights,
+            vec![ChannelLight {
+                light: "l2".into(),
+                segment: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn new_configurations() {
+        let configuration =
+            NewEntertainmentConfiguration::new("Test rig", EntertainmentConfigurationType::Space3d)
+                .with_service(
+                    "e1",
+                    Position {
+                        x: -0.5,
+                        y: 0.,
+                        z: 1.,
+                    },
+                );
+        assert_eq!(
+            serde_json::to_value(&configuration).unwrap(),
+            json!({
+                "metadata": { "name": "Test rig" },
+                "configuration_type": "3dspace",
+                "locations": {
+                    "service_locations": [{
+                        "service": { "rid": "e1", "rtype": "entertainment" },
+                        "positions": [{ "x": -0.5, "y": 0.0, "z": 1.0 }]
+                    }]
+                }
+            })
+        );
+        let types: Vec<EntertainmentConfigurationType> =
+            serde_json::from_value(json!(["screen", "3dspace", "hologram"])).unwrap();
+        assert_eq!(
+            types,
+            vec![
+                EntertainmentConfigurationType::Screen,
+                EntertainmentConfigurationType::Space3d,
+                EntertainmentConfigurationType::Other("hologram".into()),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&types).unwrap(),
+            json!(["screen", "3dspace", "hologram"])
+        );
+    }
+
+    #[test]
+    fn streamed_lights_follow_events() {
+        let configuration: EntertainmentConfiguration = serde_json::from_value(json!({
+            "id": "c1",
+            "metadata": { "name": "TV" },
+            "status": "inactive",
+            "light_services": [
+                { "rid": "l1", "rtype": "light" },
+                { "rid": "l2", "rtype": "light" }
+            ]
+        }))
+        .unwrap();
+        let mut streamed = StreamedLights::default();
+        streamed.record_event(&HueEvent::Add(vec![Resource::EntertainmentConfiguration(
+            configuration,
+        )]));
+        streamed.add_group(
+            &[ResourceIdentifier {
+                rid: "g1".into(),
+                rtype: "grouped_light".into(),
+            }],
+            vec!["l3".into(), "l2".into()],
+        );
+        assert!(!streamed.contains("l1"));
+
+        let update = |data| HueEvent::Update(vec![serde_json::from_value(data).unwrap()]);
+        streamed.record_event(&update(json!({
+            "type": "entertainment_configuration",
+            "id": "c1",
+            "status": "active"
+        })));
+        assert!(streamed.contains("l1"));
+        assert_eq!(streamed.streamed_in_group("g1"), Some("l2"));
+
+        streamed.record_event(&update(json!({
+            "type": "entertainment_configuration",
+            "id": "c1",
+            "light_services": [{ "rid": "l1", "rtype": "light" }]
+        })));
+        assert!(streamed.contains("l1"));
+        assert_eq!(streamed.streamed_in_group("g1"), None);
+
+        streamed.record_event(&HueEvent::Delete(vec![ResourceIdentifier {
+            rid: "c1".into(),
+            rtype: "entertainment_configuration".into(),
+        }]));
+        assert!(!streamed.contains("l1"));
+    }
+}
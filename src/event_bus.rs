@@ -0,0 +1,99 @@
+//! Sharing one event stream between several consumers.
+#[cfg(feature = "events")]
+use crate::Bridge;
+use crate::{HueEvent, TaskHandle};
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast;
+
+/// Reads an event stream in a background task and broadcasts its events to all subscribers, so
+/// that several tasks can share a single connection to the bridge.
+///
+/// Each subscriber buffers up to `capacity` events. A subscriber falling further behind misses
+/// the oldest ones, and its next `recv` returns [`broadcast::error::RecvError::Lagged`].
+#[derive(Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<HueEvent>,
+    task: TaskHandle,
+}
+
+impl EventBus {
+    /// Broadcasts the events of `events`, such as a [`crate::FakeEventGenerator::stream`]. See
+    /// [`Bridge::event_bus`] for the events of a bridge.
+    pub fn new(events: impl Stream<Item = HueEvent> + Send + 'static, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        let broadcaster = sender.clone();
+        let task = TaskHandle::spawn(move |stopped| async move {
+            let mut events = Box::pin(events);
+            loop {
+                let event = tokio::select! {
+                    _ = stopped.cancelled() => break,
+                    event = events.next() => event,
+                };
+                match event {
+                    // having no subscriber at the moment is not an error
+                    Some(event) => drop(broadcaster.send(event)),
+                    None => break,
+                }
+            }
+        });
+        EventBus { sender, task }
+    }
+
+    /// Returns a receiver of the events received from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<HueEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Closes the stream. Subscribers still get the events they did not receive yet.
+    pub async fn stop(self) {
+        self.task.stop().await
+    }
+}
+
+#[cfg(feature = "events")]
+impl Bridge {
+    /// Connects to the event stream of the bridge, and broadcasts its events to all the
+    /// subscribers of the returned bus.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let bus = bridge.event_bus(256).unwrap();
+    /// let mut log = bus.subscribe();
+    /// tokio::spawn(async move {
+    ///     while let Ok(event) = log.recv().await {
+    ///         println!("{event:?}");
+    ///     }
+    /// });
+    /// let mut automation = bus.subscribe();
+    /// while let Ok(event) = automation.recv().await {
+    ///     // react to the event
+    /// }
+    /// # })
+    /// ```
+    pub fn event_bus(&self, capacity: usize) -> crate::Result<EventBus> {
+        Ok(EventBus::new(self.events()?, capacity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FakeEventGenerator;
+
+    #[tokio::test]
+    async fn all_subscribers_get_all_events() {
+        let events = FakeEventGenerator::new(7).motion_burst(5);
+        let count = events.len();
+        let bus = EventBus::new(FakeEventGenerator::stream(events), 64);
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        for _ in 0..count {
+            assert!(matches!(first.recv().await, Ok(HueEvent::Update(_))));
+            assert!(matches!(second.recv().await, Ok(HueEvent::Update(_))));
+        }
+        assert!(first.is_empty() && second.is_empty());
+        bus.stop().await;
+    }
+}
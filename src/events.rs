@@ -0,0 +1,808 @@
+//! The bridge event stream, pushing resource changes as server-sent events.
+use crate::resource::{parse_tagged, split_tagged};
+#[cfg(feature = "events")]
+use crate::Bridge;
+use crate::{
+    CommandLightColor, CommandLightDimming, ConnectivityStatus, On, PowerState, Resource,
+    ResourceIdentifier, SceneMetadata, TemperatureReport,
+};
+#[cfg(feature = "events")]
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventColorTemperature {
+    pub mirek: Option<u16>,
+    pub mirek_valid: bool,
+}
+
+/// A change on a light or a grouped light. Only the fields that changed are set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLight {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub on: Option<On>,
+    pub dimming: Option<CommandLightDimming>,
+    pub color_temperature: Option<EventColorTemperature>,
+    pub color: Option<CommandLightColor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventMetadata {
+    pub name: Option<String>,
+    pub archetype: Option<String>,
+}
+
+/// A change on a room or a zone. Only the fields that changed are set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventGroup {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub metadata: Option<EventMetadata>,
+    pub children: Option<Vec<ResourceIdentifier>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSceneStatus {
+    /// One of `inactive`, `static` or `dynamic_palette`.
+    pub active: String,
+}
+
+/// A change on a scene. Only the fields that changed are set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventScene {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub metadata: Option<SceneMetadata>,
+    pub status: Option<EventSceneStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotionReport {
+    pub motion: bool,
+    pub motion_valid: bool,
+}
+
+/// A change on a motion sensor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventMotion {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: Option<ResourceIdentifier>,
+    pub motion: Option<MotionReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightLevelReport {
+    /// The light level, as 10000 * log10(lux) + 1.
+    pub light_level: u32,
+    pub light_level_valid: bool,
+}
+
+/// A change on a light level sensor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLightLevel {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: Option<ResourceIdentifier>,
+    pub light: Option<LightLevelReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactReport {
+    pub changed: String,
+    /// `contact` when closed, `no_contact` when open.
+    pub state: String,
+}
+
+/// A change on a contact sensor, such as a door or window sensor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventContact {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: Option<ResourceIdentifier>,
+    pub contact_report: Option<ContactReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TamperReport {
+    pub changed: String,
+    /// What was tampered with, such as `battery_door`.
+    pub source: String,
+    /// `tampered` or `not_tampered`.
+    pub state: String,
+}
+
+/// A change on the tamper detection of a device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventTamper {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device that may have been tampered with.
+    pub owner: Option<ResourceIdentifier>,
+    pub tamper_reports: Option<Vec<TamperReport>>,
+}
+
+string_enum! {
+    /// What happened to a button.
+    pub enum ButtonEvent {
+        InitialPress => "initial_press",
+        /// Sent repeatedly while the button is held down.
+        Repeat => "repeat",
+        ShortRelease => "short_release",
+        LongPress => "long_press",
+        LongRelease => "long_release",
+        DoubleShortRelease => "double_short_release",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ButtonReport {
+    pub updated: String,
+    pub event: ButtonEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ButtonState {
+    pub button_report: Option<ButtonReport>,
+    pub last_event: Option<ButtonEvent>,
+}
+
+/// A button of a switch or a dimmer being used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventButton {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the button.
+    pub owner: Option<ResourceIdentifier>,
+    pub button: Option<ButtonState>,
+}
+
+impl EventButton {
+    /// What happened to the button.
+    pub fn event(&self) -> Option<ButtonEvent> {
+        let button = self.button.as_ref()?;
+        button
+            .button_report
+            .as_ref()
+            .map(|report| report.event.clone())
+            .or_else(|| button.last_event.clone())
+    }
+}
+
+/// A change on a temperature sensor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventTemperature {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: Option<ResourceIdentifier>,
+    pub temperature: Option<TemperatureReport>,
+}
+
+/// A change on the battery of a device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventDevicePower {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub owner: Option<ResourceIdentifier>,
+    pub power_state: Option<PowerState>,
+}
+
+/// A change on the Zigbee connectivity of a device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventZigbeeConnectivity {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub owner: Option<ResourceIdentifier>,
+    pub status: Option<ConnectivityStatus>,
+}
+
+/// The resource an event is about, tagged by its type.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventData {
+    Light(EventLight),
+    GroupedLight(EventLight),
+    Room(EventGroup),
+    Zone(EventGroup),
+    Scene(EventScene),
+    Motion(EventMotion),
+    LightLevel(EventLightLevel),
+    Contact(EventContact),
+    Tamper(EventTamper),
+    /// The motion detection of a camera.
+    CameraMotion(EventMotion),
+    Button(EventButton),
+    Temperature(EventTemperature),
+    DevicePower(EventDevicePower),
+    ZigbeeConnectivity(EventZigbeeConnectivity),
+    /// A resource type this crate does not model yet, with its payload as sent by the bridge.
+    #[serde(untagged)]
+    Unknown {
+        #[serde(skip_serializing)]
+        rtype: String,
+        #[serde(flatten)]
+        raw: Value,
+    },
+}
+
+impl<'de> Deserialize<'de> for EventData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (rtype, raw) = split_tagged(deserializer)?;
+        Ok(match rtype.as_str() {
+            "light" => EventData::Light(parse_tagged(raw)?),
+            "grouped_light" => EventData::GroupedLight(parse_tagged(raw)?),
+            "room" => EventData::Room(parse_tagged(raw)?),
+            "zone" => EventData::Zone(parse_tagged(raw)?),
+            "scene" => EventData::Scene(parse_tagged(raw)?),
+            "motion" => EventData::Motion(parse_tagged(raw)?),
+            "light_level" => EventData::LightLevel(parse_tagged(raw)?),
+            "contact" => EventData::Contact(parse_tagged(raw)?),
+            "tamper" => EventData::Tamper(parse_tagged(raw)?),
+            "camera_motion" => EventData::CameraMotion(parse_tagged(raw)?),
+            "button" => EventData::Button(parse_tagged(raw)?),
+            "temperature" => EventData::Temperature(parse_tagged(raw)?),
+            "device_power" => EventData::DevicePower(parse_tagged(raw)?),
+            "zigbee_connectivity" => EventData::ZigbeeConnectivity(parse_tagged(raw)?),
+            _ => EventData::Unknown { rtype, raw },
+        })
+    }
+}
+
+impl EventData {
+    /// Returns the id of the resource this event is about, if known.
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            EventData::Light(light) | EventData::GroupedLight(light) => Some(&light.id),
+            EventData::Room(group) | EventData::Zone(group) => Some(&group.id),
+            EventData::Scene(scene) => Some(&scene.id),
+            EventData::Motion(motion) | EventData::CameraMotion(motion) => Some(&motion.id),
+            EventData::LightLevel(light_level) => Some(&light_level.id),
+            EventData::Contact(contact) => Some(&contact.id),
+            EventData::Tamper(tamper) => Some(&tamper.id),
+            EventData::Button(button) => Some(&button.id),
+            EventData::Temperature(temperature) => Some(&temperature.id),
+            EventData::DevicePower(power) => Some(&power.id),
+            EventData::ZigbeeConnectivity(connectivity) => Some(&connectivity.id),
+            EventData::Unknown { .. } => None,
+        }
+    }
+}
+
+/// The resource types of a message that deserialized to [`EventData::Unknown`] or
+/// [`Resource::Unknown`], with the number of resources of each type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnknownEvents {
+    pub counts: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct EventEnvelope {
+    /// `add`, `update`, `delete` or `error`.
+    #[serde(rename = "type")]
+    kind: String,
+    data: Vec<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub enum HueEvent {
+    /// Resources were created, with all their properties.
+    Add(Vec<Resource>),
+    /// Resources were changed, only the properties that changed are set.
+    Update(Vec<EventData>),
+    /// Resources were deleted.
+    Delete(Vec<ResourceIdentifier>),
+    Error(String),
+}
+
+impl HueEvent {
+    /// Returns true if the event is about no resource.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            HueEvent::Add(resources) => resources.is_empty(),
+            HueEvent::Update(data) => data.is_empty(),
+            HueEvent::Delete(identifiers) => identifiers.is_empty(),
+            HueEvent::Error(_) => false,
+        }
+    }
+}
+
+/// The id of the last message received from an event stream, to resume it where it stopped
+/// with [`Bridge::events_resuming`]. Clones share the same id.
+#[derive(Debug, Clone, Default)]
+pub struct LastEventId(Arc<Mutex<String>>);
+
+impl LastEventId {
+    /// Resumes after `id`, typically saved by a previous run of the application.
+    pub fn new(id: impl Into<String>) -> Self {
+        LastEventId(Arc::new(Mutex::new(id.into())))
+    }
+
+    /// The id of the last message, empty until a message was received.
+    pub fn get(&self) -> String {
+        self.0.lock().unwrap().clone()
+    }
+
+    #[cfg(feature = "events")]
+    fn set(&self, id: &str) {
+        if !id.is_empty() {
+            *self.0.lock().unwrap() = id.to_string();
+        }
+    }
+}
+
+/// Selects the resources [`Bridge::events_filtered`] reports changes of. Resources are only
+/// deserialized when they match.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    rtypes: HashSet<String>,
+    ids: HashSet<String>,
+}
+
+impl EventFilter {
+    /// Only reports resources of type `rtype`, such as `light` or `motion`. Several types can be
+    /// selected.
+    pub fn with_type(mut self, rtype: impl Into<String>) -> Self {
+        self.rtypes.insert(rtype.into());
+        self
+    }
+
+    /// Only reports the resource `id`. Several ids can be selected.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.ids.insert(id.into());
+        self
+    }
+
+    pub fn with_ids(mut self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    fn matches(&self, raw: &Value) -> bool {
+        let field = |name: &str| raw.get(name).and_then(Value::as_str).unwrap_or_default();
+        (self.rtypes.is_empty() || self.rtypes.contains(field("type")))
+            && (self.ids.is_empty() || self.ids.contains(field("id")))
+    }
+}
+
+type UnknownHandler = Arc<dyn Fn(&UnknownEvents) + Send + Sync>;
+
+#[cfg(feature = "events")]
+#[derive(Default)]
+struct StreamOptions {
+    on_unknown: Option<UnknownHandler>,
+    last_event_id: Option<LastEventId>,
+    filter: Option<EventFilter>,
+}
+
+fn identifier(raw: &Value) -> Option<ResourceIdentifier> {
+    Some(ResourceIdentifier {
+        rid: raw.get("id")?.as_str()?.to_string(),
+        rtype: raw.get("type")?.as_str()?.to_string(),
+    })
+}
+
+/// Parses a message of the event stream, made of one event per kind of change.
+pub(crate) fn parse_message(
+    data: &str,
+    on_unknown: Option<&UnknownHandler>,
+    filter: Option<&EventFilter>,
+) -> Vec<HueEvent> {
+    let envelopes = match serde_json::from_str::<Vec<EventEnvelope>>(data) {
+        Ok(envelopes) => envelopes,
+        Err(e) => return vec![HueEvent::Error(format!("{:?}", e))],
+    };
+    let mut unknown = UnknownEvents::default();
+    let mut count_unknown = |raw: &Value| {
+        let rtype = raw.get("type").and_then(Value::as_str).unwrap_or_default();
+        *unknown.counts.entry(rtype.to_string()).or_default() += 1;
+    };
+    let mut events = vec![];
+    let mut errors = vec![];
+    for envelope in envelopes {
+        let resources = envelope
+            .data
+            .into_iter()
+            .filter(|raw| filter.is_none_or(|filter| filter.matches(raw)));
+        let event = match envelope.kind.as_str() {
+            // a resource that does not parse is reported on its own, the others are kept
+            "add" => HueEvent::Add(
+                resources
+                    .filter_map(|raw| match Resource::deserialize(&raw) {
+                        Ok(resource) => {
+                            if let Resource::Unknown { .. } = resource {
+                                count_unknown(&raw);
+                            }
+                            Some(resource)
+                        }
+                        Err(e) => {
+                            errors.push(HueEvent::Error(format!("{:?}", e)));
+                            None
+                        }
+                    })
+                    .collect(),
+            ),
+            "update" => HueEvent::Update(
+                resources
+                    .filter_map(|raw| match EventData::deserialize(&raw) {
+                        Ok(data) => {
+                            if let EventData::Unknown { .. } = data {
+                                count_unknown(&raw);
+                            }
+                            Some(data)
+                        }
+                        Err(e) => {
+                            errors.push(HueEvent::Error(format!("{:?}", e)));
+                            None
+                        }
+                    })
+                    .collect(),
+            ),
+            "delete" => HueEvent::Delete(resources.filter_map(|raw| identifier(&raw)).collect()),
+            "error" => HueEvent::Error(Value::from_iter(resources).to_string()),
+            kind => {
+                log::debug!("unknown event kind {kind}");
+                continue;
+            }
+        };
+        if !event.is_empty() || (filter.is_none() && errors.is_empty()) {
+            events.push(event);
+        }
+        events.append(&mut errors);
+    }
+    if !unknown.counts.is_empty() {
+        log::debug!("unknown event types {:?}", unknown.counts);
+        if let Some(on_unknown) = on_unknown {
+            on_unknown(&unknown);
+        }
+    }
+    events
+}
+
+// how long to wait before reconnecting the event stream, doubled after each failure up to
+// RECONNECT_MAX_DELAY, like reqwest-eventsource does
+#[cfg(feature = "events")]
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+#[cfg(feature = "events")]
+const RECONNECT_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[cfg(feature = "events")]
+fn connect_event_source(
+    client: &reqwest::Client,
+    url: &str,
+    last_event_id: &str,
+) -> crate::Result<reqwest_eventsource::EventSource> {
+    let mut request_builder = client.get(url);
+    if !last_event_id.is_empty() {
+        request_builder = request_builder.header("last-event-id", last_event_id);
+    }
+    let mut source = reqwest_eventsource::EventSource::new(request_builder)?;
+    source.set_retry_policy(Box::new(reqwest_eventsource::retry::Never));
+    Ok(source)
+}
+
+/// An event stream reconnecting after errors. Reconnections are not left to reqwest-eventsource,
+/// which would send its own `last-event-id` header after the one of the first request, so that
+/// each connection sends a single, up to date, id.
+#[cfg(feature = "events")]
+struct Reconnecting {
+    client: reqwest::Client,
+    url: String,
+    source: Option<reqwest_eventsource::EventSource>,
+    last_event_id: String,
+    failures: u32,
+    #[cfg(feature = "fault-injection")]
+    faults: crate::FaultInjection,
+}
+
+#[cfg(feature = "events")]
+type SourceEvent = Result<reqwest_eventsource::Event, reqwest_eventsource::Error>;
+
+#[cfg(feature = "events")]
+impl Reconnecting {
+    async fn next(mut self) -> Option<(SourceEvent, Self)> {
+        loop {
+            if let Some(source) = self.source.as_mut() {
+                match source.next().await {
+                    Some(event) => {
+                        #[cfg(feature = "fault-injection")]
+                        if matches!(event, Ok(reqwest_eventsource::Event::Message(_)))
+                            && self.faults.disconnects()
+                        {
+                            log::debug!("injecting an event stream disconnection");
+                            // the message is lost, and sent again after reconnecting
+                            source.close();
+                            self.source = None;
+                            return Some((Err(reqwest_eventsource::Error::StreamEnded), self));
+                        }
+                        match &event {
+                            Ok(reqwest_eventsource::Event::Open) => self.failures = 0,
+                            Ok(reqwest_eventsource::Event::Message(msg)) if !msg.id.is_empty() => {
+                                self.last_event_id = msg.id.clone()
+                            }
+                            _ => (),
+                        }
+                        return Some((event, self));
+                    }
+                    // without retry policy, the source ends after an error
+                    None => self.source = None,
+                }
+            }
+            let delay = RECONNECT_DELAY * 2u32.pow(self.failures.min(5));
+            self.failures += 1;
+            tokio::time::sleep(delay.min(RECONNECT_MAX_DELAY)).await;
+            match connect_event_source(&self.client, &self.url, &self.last_event_id) {
+                Ok(source) => self.source = Some(source),
+                Err(e) => log::warn!("could not reconnect the event stream: {e}"),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "events")]
+impl Bridge {
+    pub fn events(&self) -> crate::Result<impl Stream<Item = HueEvent>> {
+        self.event_stream(StreamOptions::default())
+    }
+
+    /// Same as [`Bridge::events`], but resumes after the message `last_event_id` refers to, if
+    /// any, and keeps it up to date, so it can be saved to resume after a restart. Events the
+    /// bridge no longer has are lost. Whichever method opened the stream, reconnections after
+    /// network blips resume after the last message received, or after `last_event_id` if none
+    /// was received yet.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use futures::StreamExt;
+    /// use hueclient::LastEventId;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let saved = std::fs::read_to_string("last_event_id").unwrap_or_default();
+    /// let last_event_id = LastEventId::new(saved);
+    /// let mut events = Box::pin(bridge.events_resuming(&last_event_id).unwrap());
+    /// while let Some(event) = events.next().await {
+    ///     println!("{event:?}");
+    ///     std::fs::write("last_event_id", last_event_id.get()).unwrap();
+    /// }
+    /// # })
+    /// ```
+    pub fn events_resuming(
+        &self,
+        last_event_id: &LastEventId,
+    ) -> crate::Result<impl Stream<Item = HueEvent>> {
+        self.event_stream(StreamOptions {
+            last_event_id: Some(last_event_id.clone()),
+            ..Default::default()
+        })
+    }
+
+    /// Same as [`Bridge::events`], but calls `on_unknown` whenever a message contains resource
+    /// types this crate does not model, which helps finding out what is worth supporting.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let events = bridge
+    ///     .events_with_unknown_handler(|unknown| eprintln!("unmodeled: {:?}", unknown.counts))
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn events_with_unknown_handler(
+        &self,
+        on_unknown: impl Fn(&UnknownEvents) + Send + Sync + 'static,
+    ) -> crate::Result<impl Stream<Item = HueEvent>> {
+        self.event_stream(StreamOptions {
+            on_unknown: Some(Arc::new(on_unknown)),
+            ..Default::default()
+        })
+    }
+
+    /// Same as [`Bridge::events`], but only reports the resources selected by `filter`.
+    /// Messages without any of them are skipped.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use futures::StreamExt;
+    /// use hueclient::EventFilter;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let room = bridge
+    ///     .resolve_all_rooms()
+    ///     .await
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .find(|room| room.metadata.name == "Kitchen")
+    ///     .unwrap();
+    /// let filter = EventFilter::default()
+    ///     .with_type("light")
+    ///     .with_ids(room.children.iter().map(|light| light.id.clone()));
+    /// let mut events = Box::pin(bridge.events_filtered(filter).unwrap());
+    /// while let Some(event) = events.next().await {
+    ///     println!("{event:?}");
+    /// }
+    /// # })
+    /// ```
+    pub fn events_filtered(
+        &self,
+        filter: EventFilter,
+    ) -> crate::Result<impl Stream<Item = HueEvent>> {
+        self.event_stream(StreamOptions {
+            filter: Some(filter),
+            ..Default::default()
+        })
+    }
+
+    fn event_stream(&self, options: StreamOptions) -> crate::Result<impl Stream<Item = HueEvent>> {
+        let StreamOptions {
+            on_unknown,
+            last_event_id,
+            filter,
+        } = options;
+        let filter = filter.map(Arc::new);
+        self.require(crate::Feature::EventStream)?;
+        let client = self.client.clone();
+        let url = format!("https://{}/eventstream/clip/v2", self.host());
+        let resume_from = last_event_id
+            .as_ref()
+            .map(LastEventId::get)
+            .unwrap_or_default();
+        let source = connect_event_source(&client, &url, &resume_from)?;
+        let reconnecting = Reconnecting {
+            client,
+            url,
+            source: Some(source),
+            last_event_id: resume_from,
+            failures: 0,
+            #[cfg(feature = "fault-injection")]
+            faults: self.faults.clone(),
+        };
+        Ok(stream::unfold(reconnecting, Reconnecting::next)
+            .then(move |event| {
+                let on_unknown = on_unknown.clone();
+                let last_event_id = last_event_id.clone();
+                let filter = filter.clone();
+                async move {
+                    log::debug!("event {:?}", event);
+                    match event {
+                        Ok(reqwest_eventsource::Event::Message(msg)) => {
+                            log::debug!("message {} {:?}", msg.id, msg.data);
+                            if let Some(last_event_id) = last_event_id {
+                                last_event_id.set(&msg.id);
+                            }
+                            parse_message(&msg.data, on_unknown.as_ref(), filter.as_deref())
+                        }
+                        Ok(reqwest_eventsource::Event::Open) => vec![],
+                        Err(e) => vec![HueEvent::Error(format!("{:?}", e))],
+                    }
+                }
+            })
+            .flat_map(stream::iter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_types_are_reported() {
+        let reported = Arc::new(Mutex::new(UnknownEvents::default()));
+        let handler: UnknownHandler = {
+            let reported = reported.clone();
+            Arc::new(move |unknown: &UnknownEvents| *reported.lock().unwrap() = unknown.clone())
+        };
+        let message = r#"[{"type":"update","data":[
+            {"type":"light","id":"l1","on":{"on":true}},
+            {"type":"bell_button","id":"b1"},
+            {"type":"bell_button","id":"b2"}
+        ]}]"#;
+        let [HueEvent::Update(data)] = &parse_message(message, Some(&handler), None)[..] else {
+            panic!("expected an event");
+        };
+        assert_eq!(data.len(), 3);
+        assert!(matches!(data[0], EventData::Light(_)));
+        let EventData::Unknown { rtype, raw } = &data[1] else {
+            panic!("expected an unknown resource");
+        };
+        assert_eq!(rtype, "bell_button");
+        assert_eq!(raw["id"], "b1");
+        assert_eq!(serde_json::to_value(&data[1]).unwrap(), *raw);
+        assert_eq!(
+            reported.lock().unwrap().counts,
+            [("bell_button".to_string(), 2)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn additions_and_deletions() {
+        let message = r#"[
+            {"type":"add","data":[{"type":"zone","id":"z1","metadata":{"name":"Upstairs",
+                "archetype":"upstairs"},"children":[],"services":[]}]},
+            {"type":"delete","data":[{"type":"scene","id":"s1","id_v1":"/scenes/abc"}]}
+        ]"#;
+        let [HueEvent::Add(added), HueEvent::Delete(deleted)] =
+            &parse_message(message, None, None)[..]
+        else {
+            panic!("expected an addition and a deletion");
+        };
+        assert!(matches!(&added[0], Resource::Zone(zone) if zone.metadata.name == "Upstairs"));
+        assert_eq!(
+            (deleted[0].rid.as_str(), deleted[0].rtype.as_str()),
+            ("s1", "scene")
+        );
+    }
+
+    #[test]
+    fn malformed_additions_are_skipped() {
+        let message = r#"[{"type":"add","data":[
+            {"type":"zone","id":"z1","metadata":{"name":"Upstairs","archetype":"upstairs"},
+                "children":[],"services":[]},
+            {"type":"room","id":"r1","metadata":"not a room"},
+            {"type":"bell_button","id":"b1","sound":"ding"}
+        ]}]"#;
+        let [HueEvent::Add(added), HueEvent::Error(_)] = &parse_message(message, None, None)[..]
+        else {
+            panic!("expected the valid additions and an error");
+        };
+        assert_eq!(added.len(), 2);
+        assert!(matches!(&added[0], Resource::Zone(_)));
+        let Resource::Unknown { rtype, raw } = &added[1] else {
+            panic!("expected an unknown resource");
+        };
+        assert_eq!(rtype, "bell_button");
+        assert_eq!(raw["sound"], "ding");
+    }
+
+    #[test]
+    fn filtered_events() {
+        let message = r#"[{"type":"update","data":[
+            {"type":"light","id":"l1","on":{"on":true}},
+            {"type":"light","id":"l2","on":{"on":true}},
+            {"type":"grouped_light","id":"l1","on":{"on":true}},
+            {"type":"button","id":"b1","button":"not a button"}
+        ]}]"#;
+        let filter = EventFilter::default().with_type("light").with_id("l1");
+        let [HueEvent::Update(data)] = &parse_message(message, None, Some(&filter))[..] else {
+            panic!("the malformed button should not have been deserialized");
+        };
+        assert_eq!(data.len(), 1);
+        assert!(matches!(&data[0], EventData::Light(light) if light.id == "l1"));
+    }
+
+    #[test]
+    fn sensor_events() {
+        let message = r#"[{"type":"update","data":[
+            {"type":"button","id":"b1","button":{
+                "button_report":{"updated":"2024-01-01T00:00:00.000Z","event":"short_release"},
+                "last_event":"short_release"
+            }},
+            {"type":"temperature","id":"t1","temperature":{
+                "temperature":21.5,"temperature_valid":true
+            }},
+            {"type":"device_power","id":"p1","power_state":{
+                "battery_state":"low","battery_level":12
+            }},
+            {"type":"zigbee_connectivity","id":"z1","status":"connectivity_issue"}
+        ]}]"#;
+        let [HueEvent::Update(data)] = &parse_message(message, None, None)[..] else {
+            panic!("expected an event");
+        };
+        assert!(
+            matches!(&data[0], EventData::Button(b) if b.event() == Some(ButtonEvent::ShortRelease))
+        );
+        assert!(matches!(&data[1], EventData::Temperature(t)
+            if t.temperature.as_ref().unwrap().temperature == 21.5));
+        assert!(matches!(&data[2], EventData::DevicePower(p)
+            if p.power_state.as_ref().unwrap().battery_level == Some(12)));
+        assert!(matches!(&data[3], EventData::ZigbeeConnectivity(z)
+            if z.status == Some(ConnectivityStatus::ConnectivityIssue)));
+    }
+}
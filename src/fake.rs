@@ -0,0 +1,317 @@
+//! Deterministic synthetic event sequences, for load testing consumers of [`Bridge::events`]
+//! without a bridge.
+//!
+//! Events are generated in the wire format of the bridge event stream and parsed the same way,
+//! so they go through the exact code path of real events.
+//!
+//! [`Bridge::events`]: crate::Bridge::events
+use crate::{events::parse_message, HueEvent};
+use futures::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+// keeps the delays of paced streams within what a Duration holds
+const MIN_SPEEDUP: f64 = 0.001;
+
+fn light_data(rtype: &str, id: &str, on: bool, brightness: Option<f32>) -> Value {
+    let mut light = json!({ "type": rtype, "id": id, "on": { "on": on } });
+    if let Some(brightness) = brightness {
+        light["dimming"] = json!({ "brightness": brightness });
+    }
+    light
+}
+
+// good enough and does not need a dependency
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// A generated event, along with when it happens relative to the start of the sequence.
+#[derive(Debug, Clone)]
+pub struct FakeEvent {
+    pub at: Duration,
+    /// The message as it would be sent by the bridge.
+    pub message: String,
+}
+
+impl FakeEvent {
+    pub fn event(&self) -> HueEvent {
+        let mut events = parse_message(&self.message, None, None);
+        // generated messages hold a single update
+        events.swap_remove(0)
+    }
+}
+
+/// Generates realistic event sequences from a seed. The same seed and the same calls always
+/// produce the same events.
+/// ### Example
+/// ```
+/// use futures::StreamExt;
+/// # tokio_test::block_on(async {
+/// let mut generator = hueclient::FakeEventGenerator::new(42);
+/// let mut events = generator.morning_routine();
+/// events.extend(generator.motion_burst(20));
+/// let mut stream = hueclient::FakeEventGenerator::stream(events);
+/// while let Some(event) = stream.next().await {
+///     println!("{:?}", event);
+/// }
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct FakeEventGenerator {
+    state: u64,
+    now: Duration,
+    lights: Vec<String>,
+    grouped_lights: Vec<String>,
+    motion_sensors: Vec<String>,
+    buttons: Vec<String>,
+}
+
+impl FakeEventGenerator {
+    /// Creates a generator for a home with 8 lights in 2 rooms, 2 motion sensors and 4 buttons.
+    pub fn new(seed: u64) -> Self {
+        let mut generator = Self {
+            state: seed,
+            now: Duration::ZERO,
+            lights: vec![],
+            grouped_lights: vec![],
+            motion_sensors: vec![],
+            buttons: vec![],
+        };
+        generator.lights = (0..8).map(|_| generator.next_id()).collect();
+        generator.grouped_lights = (0..2).map(|_| generator.next_id()).collect();
+        generator.motion_sensors = (0..2).map(|_| generator.next_id()).collect();
+        generator.buttons = (0..4).map(|_| generator.next_id()).collect();
+        generator
+    }
+
+    /// Uses the given light ids instead of generated ones, for instance those of a real bridge.
+    pub fn with_lights(self, lights: Vec<String>) -> Self {
+        Self { lights, ..self }
+    }
+
+    pub fn with_grouped_lights(self, grouped_lights: Vec<String>) -> Self {
+        Self {
+            grouped_lights,
+            ..self
+        }
+    }
+
+    pub fn with_motion_sensors(self, motion_sensors: Vec<String>) -> Self {
+        Self {
+            motion_sensors,
+            ..self
+        }
+    }
+
+    pub fn with_buttons(self, buttons: Vec<String>) -> Self {
+        Self { buttons, ..self }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        splitmix64(&mut self.state)
+    }
+
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next_u64() % (high - low + 1)
+    }
+
+    fn pick<'a>(&mut self, ids: &'a [String]) -> Option<&'a str> {
+        if ids.is_empty() {
+            return None;
+        }
+        let ix = self.next_u64() as usize % ids.len();
+        Some(&ids[ix])
+    }
+
+    fn next_id(&mut self) -> String {
+        let a = self.next_u64();
+        let b = self.next_u64();
+        format!(
+            "{:08x}-{:04x}-4{:03x}-8{:03x}-{:012x}",
+            a >> 32,
+            (a >> 16) & 0xffff,
+            a & 0xfff,
+            b >> 52,
+            b & 0xffff_ffff_ffff
+        )
+    }
+
+    fn advance(&mut self, low_ms: u64, high_ms: u64) {
+        let elapsed = self.range(low_ms, high_ms);
+        self.now += Duration::from_millis(elapsed);
+    }
+
+    fn emit(&mut self, data: Vec<Value>) -> FakeEvent {
+        let message = json!([{
+            "id": self.next_id(),
+            "type": "update",
+            "data": data,
+        }]);
+        FakeEvent {
+            at: self.now,
+            message: message.to_string(),
+        }
+    }
+
+    /// Lights fading in one after the other, brightness ramps, and finally everything off when
+    /// leaving home.
+    pub fn morning_routine(&mut self) -> Vec<FakeEvent> {
+        let mut events = vec![];
+        let lights = self.lights.clone();
+        let groups = self.grouped_lights.clone();
+        for light in &lights {
+            self.advance(200, 2_000);
+            let data = light_data("light", light, true, Some(1.0));
+            events.push(self.emit(vec![data]));
+        }
+        for step in 1..=10 {
+            self.advance(20_000, 40_000);
+            let brightness = step as f32 * 10.;
+            let data = lights
+                .iter()
+                .map(|light| light_data("light", light, true, Some(brightness)))
+                .chain(
+                    groups
+                        .iter()
+                        .map(|group| light_data("grouped_light", group, true, Some(brightness))),
+                )
+                .collect();
+            events.push(self.emit(data));
+        }
+        self.advance(600_000, 1_800_000);
+        let data = lights
+            .iter()
+            .map(|light| light_data("light", light, false, None))
+            .chain(
+                groups
+                    .iter()
+                    .map(|group| light_data("grouped_light", group, false, None)),
+            )
+            .collect();
+        events.push(self.emit(data));
+        events
+    }
+
+    /// `count` motion reports in quick succession, each detection followed by a clear.
+    pub fn motion_burst(&mut self, count: usize) -> Vec<FakeEvent> {
+        let sensors = self.motion_sensors.clone();
+        let mut events = vec![];
+        for _ in 0..count {
+            let Some(sensor) = self.pick(&sensors) else {
+                break;
+            };
+            for motion in [true, false] {
+                self.advance(50, if motion { 3_000 } else { 10_000 });
+                let data = json!({
+                    "type": "motion",
+                    "id": sensor,
+                    "motion": { "motion": motion, "motion_valid": true },
+                });
+                events.push(self.emit(vec![data]));
+            }
+        }
+        events
+    }
+
+    /// `count` button interactions: short presses, and sometimes long presses with repeats.
+    pub fn button_presses(&mut self, count: usize) -> Vec<FakeEvent> {
+        let buttons = self.buttons.clone();
+        let mut events = vec![];
+        for _ in 0..count {
+            let Some(button) = self.pick(&buttons) else {
+                break;
+            };
+            self.advance(500, 30_000);
+            let mut sequence = vec!["initial_press"];
+            if self.range(0, 3) == 0 {
+                sequence.push("long_press");
+                sequence.extend((0..self.range(1, 5)).map(|_| "repeat"));
+                sequence.push("long_release");
+            } else {
+                sequence.push("short_release");
+            }
+            for last_event in sequence {
+                self.advance(50, 800);
+                let data = json!({
+                    "type": "button",
+                    "id": button,
+                    "button": { "last_event": last_event },
+                });
+                events.push(self.emit(vec![data]));
+            }
+        }
+        events
+    }
+
+    /// Turns generated events into a stream of [`HueEvent`]s, as fast as they are consumed.
+    pub fn stream(events: Vec<FakeEvent>) -> impl Stream<Item = HueEvent> {
+        futures::stream::iter(events.into_iter().map(|event| event.event()))
+    }
+
+    /// Same as [`FakeEventGenerator::stream`], but waits between events according to their
+    /// timing, with time sped up by `speedup`. Speedups below 0.001, including zero, negative
+    /// speedups and NaN, are raised to 0.001.
+    pub fn paced_stream(events: Vec<FakeEvent>, speedup: f64) -> impl Stream<Item = HueEvent> {
+        // f64::max ignores NaN
+        let speedup = speedup.max(MIN_SPEEDUP);
+        let start = tokio::time::Instant::now();
+        futures::stream::iter(events).then(move |event| async move {
+            tokio::time::sleep_until(start + event.at.div_f64(speedup)).await;
+            event.event()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventData;
+
+    #[test]
+    fn same_seed_same_events() {
+        let mut a = FakeEventGenerator::new(7);
+        let mut b = FakeEventGenerator::new(7);
+        let a: Vec<String> = a
+            .button_presses(10)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        let b: Vec<String> = b
+            .button_presses(10)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn morning_routine_parses_as_light_events() {
+        let events = FakeEventGenerator::new(1).morning_routine();
+        assert!(events.windows(2).all(|w| w[0].at <= w[1].at));
+        for event in events {
+            let HueEvent::Update(data) = event.event() else {
+                panic!("expected an event");
+            };
+            assert!(data
+                .iter()
+                .all(|d| matches!(d, EventData::Light(_) | EventData::GroupedLight(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_speedups_do_not_panic() {
+        let mut events = FakeEventGenerator::new(3).button_presses(1);
+        events.truncate(2);
+        events[0].at = Duration::ZERO;
+        events[1].at = Duration::from_micros(1);
+        for speedup in [0., -2., f64::NAN, f64::INFINITY] {
+            let paced = FakeEventGenerator::paced_stream(events.clone(), speedup);
+            assert_eq!(paced.collect::<Vec<_>>().await.len(), 2);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Opt-in fault injection, to test how applications cope with a slow, failing or rate limiting
+//! bridge, and with event stream disconnections, without having to break a real bridge. Only
+//! built with the `fault-injection` feature, so that release builds cannot enable it.
+use crate::{fake::splitmix64, Bridge, HueError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The faults injected in the requests and event streams of a `Bridge`, see
+/// [`Bridge::with_fault_injection`]. Faults are drawn from a seeded generator, so a test run
+/// sees the same faults every time. Clones share the same generator.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjection {
+    latency: Duration,
+    jitter: Duration,
+    error_rate: f64,
+    rate_limit_rate: f64,
+    #[cfg(feature = "events")]
+    disconnect_rate: f64,
+    state: Arc<Mutex<u64>>,
+}
+
+impl FaultInjection {
+    /// No fault at all, until some are added with the methods below.
+    pub fn new(seed: u64) -> Self {
+        FaultInjection {
+            state: Arc::new(Mutex::new(seed)),
+            ..Default::default()
+        }
+    }
+
+    /// Delays every request by `latency`, plus up to `jitter`.
+    pub fn with_latency(self, latency: Duration, jitter: Duration) -> Self {
+        Self {
+            latency,
+            jitter,
+            ..self
+        }
+    }
+
+    /// Fails this proportion of the requests, between 0 and 1, as if the connection was reset.
+    /// They return a `HueError::Io` without reaching the bridge.
+    pub fn with_error_rate(self, error_rate: f64) -> Self {
+        Self { error_rate, ..self }
+    }
+
+    /// Answers this proportion of the requests, between 0 and 1, as a bridge receiving too many
+    /// requests does. They return a `HueError::RateLimited` without reaching the bridge.
+    pub fn with_rate_limit_rate(self, rate_limit_rate: f64) -> Self {
+        Self {
+            rate_limit_rate,
+            ..self
+        }
+    }
+
+    /// Disconnects the event stream instead of delivering this proportion of the messages,
+    /// between 0 and 1. The stream reports a `HueEvent::Error` and reconnects, as it does after
+    /// a network blip.
+    #[cfg(feature = "events")]
+    pub fn with_disconnect_rate(self, disconnect_rate: f64) -> Self {
+        Self {
+            disconnect_rate,
+            ..self
+        }
+    }
+
+    /// A number between 0 and 1.
+    fn draw(&self) -> f64 {
+        let mut state = self.state.lock().unwrap();
+        (splitmix64(&mut state) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn is_active(&self) -> bool {
+        !self.latency.is_zero()
+            || !self.jitter.is_zero()
+            || self.error_rate > 0.
+            || self.rate_limit_rate > 0.
+    }
+
+    /// Waits for the injected latency, then fails the request if a fault is drawn.
+    pub(crate) async fn before_request(&self) -> crate::Result<()> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        let delay = self.latency + self.jitter.mul_f64(self.draw());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        if self.draw() < self.error_rate {
+            log::debug!("injecting a request failure");
+            return Err(HueError::Io(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "injected fault",
+            )));
+        }
+        if self.draw() < self.rate_limit_rate {
+            log::debug!("injecting a rate limited response");
+            return Err(HueError::RateLimited);
+        }
+        Ok(())
+    }
+
+    /// Whether the event stream is to be disconnected instead of delivering a message.
+    #[cfg(feature = "events")]
+    pub(crate) fn disconnects(&self) -> bool {
+        self.disconnect_rate > 0. && self.draw() < self.disconnect_rate
+    }
+}
+
+impl Bridge {
+    /// Injects latency, failures, rate limiting and event stream disconnections, to test the
+    /// retry and reconnection logic of an application against a well behaved bridge.
+    /// ### Example
+    /// ```no_run
+    /// # #[cfg(feature = "events")]
+    /// # {
+    /// use hueclient::FaultInjection;
+    /// use std::time::Duration;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj")
+    ///    .with_fault_injection(
+    ///        FaultInjection::new(42)
+    ///            .with_latency(Duration::from_millis(200), Duration::from_millis(300))
+    ///            .with_rate_limit_rate(0.1)
+    ///            .with_disconnect_rate(0.01),
+    ///    );
+    /// # }
+    /// ```
+    pub fn with_fault_injection(self, faults: FaultInjection) -> Self {
+        Self { faults, ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn faults_are_injected() {
+        assert!(FaultInjection::default().before_request().await.is_ok());
+        let faults = FaultInjection::new(7).with_error_rate(1.);
+        assert!(matches!(
+            faults.before_request().await,
+            Err(HueError::Io(e)) if e.kind() == std::io::ErrorKind::ConnectionReset
+        ));
+        let faults = FaultInjection::new(7).with_rate_limit_rate(1.);
+        assert!(matches!(
+            faults.before_request().await,
+            Err(HueError::RateLimited)
+        ));
+
+        let latency = Duration::from_millis(20);
+        let faults = FaultInjection::new(7).with_latency(latency, Duration::ZERO);
+        let start = std::time::Instant::now();
+        assert!(faults.before_request().await.is_ok());
+        assert!(start.elapsed() >= latency);
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn disconnects_are_drawn_from_the_seed() {
+        let faults = FaultInjection::new(7).with_disconnect_rate(0.5);
+        let disconnects = (0..1000).filter(|_| faults.disconnects()).count();
+        assert!((400..600).contains(&disconnects), "{disconnects}");
+        // the same seed gives the same faults
+        let draws = |seed| {
+            let faults = FaultInjection::new(seed).with_disconnect_rate(0.5);
+            (0..20).map(|_| faults.disconnects()).collect::<Vec<_>>()
+        };
+        assert_eq!(draws(3), draws(3));
+    }
+}
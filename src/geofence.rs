@@ -0,0 +1,152 @@
+//! Presence and location resources used by the native automations of the bridge.
+use crate::{Bridge, BridgeResponseV2, ResourceIdentifier};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A client reporting whether its user is at home, such as a phone running the Hue app.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeofenceClient {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NewGeofenceClient<'a> {
+    r#type: &'static str,
+    name: &'a str,
+    is_at_home: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommandGeofenceClient {
+    is_at_home: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SunToday {
+    pub sunset_time: Option<String>,
+    pub day_type: Option<String>,
+}
+
+/// The location of the bridge, needed by the sunrise and sunset based automations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Geolocation {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// Whether the coordinates of the bridge have been set, and sunrise and sunset are known.
+    pub is_configured: bool,
+    pub sun_today: Option<SunToday>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommandGeolocation {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl Bridge {
+    /// Returns the geolocation resource of this `Bridge`.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let geolocation = bridge.get_geolocation().await.unwrap();
+    /// if !geolocation.is_configured {
+    ///     bridge.set_geolocation(&geolocation.id, 48.85, 2.35).await.unwrap();
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_geolocation(&self) -> crate::Result<Geolocation> {
+        let url = format!("https://{}/clip/v2/resource/geolocation", self.host());
+        let resp: BridgeResponseV2<Geolocation> = self.send(self.client.get(&url)).await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a geolocation resource"))
+    }
+
+    /// Sets the coordinates of the bridge. The bridge only uses them to compute sunrise and
+    /// sunset times, they are never returned.
+    pub async fn set_geolocation(
+        &self,
+        id: &str,
+        latitude: f64,
+        longitude: f64,
+    ) -> crate::Result<()> {
+        let url = format!(
+            "https://{}/clip/v2/resource/geolocation/{}",
+            self.host(),
+            id
+        );
+        let resp: BridgeResponseV2<Value> = self
+            .send(self.client.put(&url).json(&CommandGeolocation {
+                latitude,
+                longitude,
+            }))
+            .await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Returns a vector of all geofence clients that are registered at this `Bridge`, sorted by
+    /// their id's.
+    pub async fn get_all_geofence_clients(&self) -> crate::Result<Vec<GeofenceClient>> {
+        self.get_resources().await
+    }
+
+    /// Registers a geofence client and returns its identifier.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let client = bridge.create_geofence_client("presence-service", true).await.unwrap();
+    /// // later on, when leaving home
+    /// bridge.set_geofence_client_at_home(&client.rid, false).await.unwrap();
+    /// # })
+    /// ```
+    pub async fn create_geofence_client(
+        &self,
+        name: &str,
+        is_at_home: bool,
+    ) -> crate::Result<ResourceIdentifier> {
+        let url = format!("https://{}/clip/v2/resource/geofence_client", self.host());
+        let resp: BridgeResponseV2<ResourceIdentifier> = self
+            .send(self.client.post(&url).json(&NewGeofenceClient {
+                r#type: "geofence_client",
+                name,
+                is_at_home,
+            }))
+            .await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a created resource"))
+    }
+
+    /// Reports whether the user of a geofence client is at home.
+    pub async fn set_geofence_client_at_home(
+        &self,
+        id: &str,
+        is_at_home: bool,
+    ) -> crate::Result<()> {
+        let url = format!(
+            "https://{}/clip/v2/resource/geofence_client/{}",
+            self.host(),
+            id
+        );
+        let resp: BridgeResponseV2<Value> = self
+            .send(
+                self.client
+                    .put(&url)
+                    .json(&CommandGeofenceClient { is_at_home }),
+            )
+            .await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    pub async fn delete_geofence_client(&self, id: &str) -> crate::Result<()> {
+        self.delete_resource("geofence_client", id).await
+    }
+}
@@ -0,0 +1,305 @@
+//! Creating, updating and deleting rooms and zones.
+use crate::{
+    Bridge, BridgeResponseV2, GroupMetadata, ResolvedRoom, ResourceIdentifier, RoomArchetype,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+fn identifiers(
+    rtype: &str,
+    ids: impl IntoIterator<Item = impl Into<String>>,
+) -> Vec<ResourceIdentifier> {
+    ids.into_iter()
+        .map(|id| ResourceIdentifier {
+            rid: id.into(),
+            rtype: rtype.into(),
+        })
+        .collect()
+}
+
+/// A room to create with [`Bridge::create_room`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewRoom {
+    pub metadata: GroupMetadata,
+    /// The devices in the room. A device can only be in one room.
+    pub children: Vec<ResourceIdentifier>,
+}
+
+impl NewRoom {
+    /// A new empty room. `archetype` is one of the room types of the Hue app, such as
+    /// [`RoomArchetype::LivingRoom`] or `"bedroom"`.
+    pub fn new(name: impl Into<String>, archetype: impl Into<RoomArchetype>) -> Self {
+        NewRoom {
+            metadata: GroupMetadata {
+                name: name.into(),
+                archetype: archetype.into(),
+            },
+            children: vec![],
+        }
+    }
+
+    pub fn with_devices(self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            children: identifiers("device", ids),
+            ..self
+        }
+    }
+}
+
+/// The metadata of a room or a zone to change, only the fields that are set are sent.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommandGroupMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archetype: Option<RoomArchetype>,
+}
+
+/// A partial update of a room, only the fields that are set are sent.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommandRoom {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CommandGroupMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<ResourceIdentifier>>,
+}
+
+impl CommandRoom {
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            metadata: Some(CommandGroupMetadata {
+                name: Some(name.into()),
+                ..self.metadata.unwrap_or_default()
+            }),
+            ..self
+        }
+    }
+
+    pub fn with_archetype(self, archetype: impl Into<RoomArchetype>) -> Self {
+        Self {
+            metadata: Some(CommandGroupMetadata {
+                archetype: Some(archetype.into()),
+                ..self.metadata.unwrap_or_default()
+            }),
+            ..self
+        }
+    }
+
+    /// Replaces all the devices of the room.
+    pub fn with_devices(self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            children: Some(identifiers("device", ids)),
+            ..self
+        }
+    }
+}
+
+/// A zone to create with [`Bridge::create_zone`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewZone {
+    pub metadata: GroupMetadata,
+    /// The lights in the zone. Unlike rooms, a light can be in several zones.
+    pub children: Vec<ResourceIdentifier>,
+}
+
+impl NewZone {
+    /// A new empty zone. `archetype` is one of the room types of the Hue app, such as
+    /// [`RoomArchetype::Downstairs`] or `"garden"`.
+    pub fn new(name: impl Into<String>, archetype: impl Into<RoomArchetype>) -> Self {
+        NewZone {
+            metadata: GroupMetadata {
+                name: name.into(),
+                archetype: archetype.into(),
+            },
+            children: vec![],
+        }
+    }
+
+    pub fn with_lights(self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            children: identifiers("light", ids),
+            ..self
+        }
+    }
+
+    /// Sets the lights of the zone to all the lights of `rooms`, as returned by
+    /// [`Bridge::resolve_all_rooms`].
+    pub fn with_lights_of_rooms<'a>(
+        self,
+        rooms: impl IntoIterator<Item = &'a ResolvedRoom>,
+    ) -> Self {
+        let ids = rooms
+            .into_iter()
+            .flat_map(|room| &room.children)
+            .map(|light| light.id.as_str());
+        self.with_lights(ids)
+    }
+}
+
+/// A partial update of a zone, only the fields that are set are sent.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommandZone {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CommandGroupMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<ResourceIdentifier>>,
+}
+
+impl CommandZone {
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            metadata: Some(CommandGroupMetadata {
+                name: Some(name.into()),
+                ..self.metadata.unwrap_or_default()
+            }),
+            ..self
+        }
+    }
+
+    pub fn with_archetype(self, archetype: impl Into<RoomArchetype>) -> Self {
+        Self {
+            metadata: Some(CommandGroupMetadata {
+                archetype: Some(archetype.into()),
+                ..self.metadata.unwrap_or_default()
+            }),
+            ..self
+        }
+    }
+
+    /// Replaces all the lights of the zone.
+    pub fn with_lights(self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            children: Some(identifiers("light", ids)),
+            ..self
+        }
+    }
+}
+
+impl Bridge {
+    /// Creates a room and returns its identifier.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::NewRoom;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let room = NewRoom::new("Office", "office")
+    ///     .with_devices(["0b6c2d3e-4f5a-4b6c-9d7e-8f9a0b1c2d3e"]);
+    /// let created = bridge.create_room(&room).await.unwrap();
+    /// println!("created {}", created.rid);
+    /// # })
+    /// ```
+    pub async fn create_room(&self, room: &NewRoom) -> crate::Result<ResourceIdentifier> {
+        let url = format!("https://{}/clip/v2/resource/room", self.host());
+        let resp: BridgeResponseV2<ResourceIdentifier> =
+            self.send(self.client.post(&url).json(room)).await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a created resource"))
+    }
+
+    /// Same as [`Bridge::create_room`], but if a room with the same name already exists, its
+    /// archetype and devices are updated instead of creating a duplicate.
+    pub async fn ensure_room(&self, room: &NewRoom) -> crate::Result<ResourceIdentifier> {
+        let existing = self
+            .get_all_rooms()
+            .await?
+            .into_iter()
+            .find(|r| crate::names_match(&r.metadata.name, &room.metadata.name));
+        match existing {
+            Some(existing) => {
+                let command = CommandRoom {
+                    metadata: Some(CommandGroupMetadata {
+                        name: None,
+                        archetype: Some(room.metadata.archetype.clone()),
+                    }),
+                    children: Some(room.children.clone()),
+                };
+                self.update_room(&existing.id, &command).await?;
+                Ok(ResourceIdentifier {
+                    rid: existing.id,
+                    rtype: "room".into(),
+                })
+            }
+            None => self.create_room(room).await,
+        }
+    }
+
+    pub async fn update_room(&self, id: &str, command: &CommandRoom) -> crate::Result<()> {
+        let url = format!("https://{}/clip/v2/resource/room/{}", self.host(), id);
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Deletes a room. Its devices are not deleted, they just do not belong to a room anymore.
+    pub async fn delete_room(&self, id: &str) -> crate::Result<()> {
+        self.delete_resource("room", id).await
+    }
+
+    /// Creates a zone and returns its identifier.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::NewZone;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let rooms = bridge.resolve_all_rooms().await.unwrap();
+    /// let downstairs = ["Kitchen", "Living room"];
+    /// let zone = NewZone::new("Downstairs", "downstairs").with_lights_of_rooms(
+    ///     rooms
+    ///         .iter()
+    ///         .filter(|room| downstairs.contains(&room.metadata.name.as_str())),
+    /// );
+    /// let created = bridge.create_zone(&zone).await.unwrap();
+    /// println!("created {}", created.rid);
+    /// # })
+    /// ```
+    pub async fn create_zone(&self, zone: &NewZone) -> crate::Result<ResourceIdentifier> {
+        let url = format!("https://{}/clip/v2/resource/zone", self.host());
+        let resp: BridgeResponseV2<ResourceIdentifier> =
+            self.send(self.client.post(&url).json(zone)).await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a created resource"))
+    }
+
+    /// Same as [`Bridge::create_zone`], but if a zone with the same name already exists, its
+    /// archetype and lights are updated instead of creating a duplicate.
+    pub async fn ensure_zone(&self, zone: &NewZone) -> crate::Result<ResourceIdentifier> {
+        let existing = self
+            .get_all_zones()
+            .await?
+            .into_iter()
+            .find(|z| crate::names_match(&z.metadata.name, &zone.metadata.name));
+        match existing {
+            Some(existing) => {
+                let command = CommandZone {
+                    metadata: Some(CommandGroupMetadata {
+                        name: None,
+                        archetype: Some(zone.metadata.archetype.clone()),
+                    }),
+                    children: Some(zone.children.clone()),
+                };
+                self.update_zone(&existing.id, &command).await?;
+                Ok(ResourceIdentifier {
+                    rid: existing.id,
+                    rtype: "zone".into(),
+                })
+            }
+            None => self.create_zone(zone).await,
+        }
+    }
+
+    pub async fn update_zone(&self, id: &str, command: &CommandZone) -> crate::Result<()> {
+        let url = format!("https://{}/clip/v2/resource/zone/{}", self.host(), id);
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Deletes a zone. Its lights are not deleted.
+    pub async fn delete_zone(&self, id: &str) -> crate::Result<()> {
+        self.delete_resource("zone", id).await
+    }
+}
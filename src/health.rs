@@ -0,0 +1,399 @@
+//! Device health, combining connectivity, battery and activity into a single status, and the
+//! Zigbee network of the bridge.
+use crate::{Bridge, BridgeResponseV2, Device, EventData, HueEvent, ResourceIdentifier};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectivityStatus {
+    Connected,
+    Disconnected,
+    ConnectivityIssue,
+    UnidirectionalIncoming,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZigbeeConnectivity {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub owner: ResourceIdentifier,
+    pub status: ConnectivityStatus,
+    pub mac_address: Option<String>,
+    /// The channel of the Zigbee network, only set on the bridge.
+    pub channel: Option<ZigbeeChannelState>,
+}
+
+/// The channels a Hue Zigbee network can use. They are chosen to overlap as little as possible
+/// with the common Wi-Fi channels 1, 6 and 11.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ZigbeeChannel {
+    #[serde(rename = "channel_11")]
+    Channel11,
+    #[serde(rename = "channel_15")]
+    Channel15,
+    #[serde(rename = "channel_20")]
+    Channel20,
+    #[serde(rename = "channel_25")]
+    Channel25,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZigbeeChannelState {
+    /// `set`, or `changing` while the devices are moving to a new channel.
+    pub status: Option<String>,
+    /// The current channel, such as `channel_15`, or `not_configured`.
+    pub value: Option<String>,
+}
+
+impl ZigbeeChannelState {
+    /// The current channel, if it is configured.
+    pub fn channel(&self) -> Option<ZigbeeChannel> {
+        let value = self.value.as_ref()?;
+        serde_json::from_value(Value::String(value.clone())).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatteryState {
+    Normal,
+    Low,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerState {
+    pub battery_state: Option<BatteryState>,
+    /// The battery level, in percent.
+    pub battery_level: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevicePower {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub owner: ResourceIdentifier,
+    pub power_state: PowerState,
+}
+
+string_enum! {
+    pub enum SoftwareUpdateState {
+        NoUpdate => "no_update",
+        UpdatePending => "update_pending",
+        ReadyToInstall => "ready_to_install",
+        Installing => "installing",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceSoftwareUpdate {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub owner: ResourceIdentifier,
+    pub state: SoftwareUpdateState,
+    /// Why the update cannot be installed, such as `battery_low`.
+    #[serde(default)]
+    pub problems: Vec<String>,
+}
+
+impl Bridge {
+    /// Returns a vector of all zigbee connectivity services that are registered at this `Bridge`,
+    /// sorted by their id's.
+    pub async fn get_all_zigbee_connectivity(&self) -> crate::Result<Vec<ZigbeeConnectivity>> {
+        self.get_resources().await
+    }
+
+    /// Returns the Zigbee connectivity of the bridge itself, which holds the channel of the
+    /// Zigbee network.
+    pub async fn get_bridge_zigbee_connectivity(&self) -> crate::Result<ZigbeeConnectivity> {
+        let bridge_device = self
+            .get_all_devices()
+            .await?
+            .into_iter()
+            .find(|device| device.services.iter().any(|s| s.rtype == "bridge"))
+            .ok_or_else(|| crate::HueError::protocol_err("the bridge device was not found"))?;
+        let connectivity = bridge_device
+            .services
+            .iter()
+            .find(|s| s.rtype == "zigbee_connectivity")
+            .ok_or_else(|| {
+                crate::HueError::protocol_err("the bridge has no zigbee connectivity")
+            })?;
+        self.get_resource(&connectivity.rid).await
+    }
+
+    /// Moves the Zigbee network to another channel, for instance to get away from a busy Wi-Fi
+    /// channel. All the devices follow the bridge, which takes a few minutes during which they may
+    /// not respond. Devices that are unreachable during the change may have to be reset.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::ZigbeeChannel;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let connectivity = bridge.get_bridge_zigbee_connectivity().await.unwrap();
+    /// let current = connectivity.channel.and_then(|channel| channel.channel());
+    /// if current == Some(ZigbeeChannel::Channel20) {
+    ///     bridge.set_zigbee_channel(ZigbeeChannel::Channel25).await.unwrap();
+    /// }
+    /// # })
+    /// ```
+    pub async fn set_zigbee_channel(&self, channel: ZigbeeChannel) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct Channel {
+            value: ZigbeeChannel,
+        }
+        #[derive(Serialize)]
+        struct PutZigbeeConnectivity {
+            channel: Channel,
+        }
+        let connectivity = self.get_bridge_zigbee_connectivity().await?;
+        let url = format!(
+            "https://{}/clip/v2/resource/zigbee_connectivity/{}",
+            self.host(),
+            connectivity.id
+        );
+        let command = PutZigbeeConnectivity {
+            channel: Channel { value: channel },
+        };
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(&command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Returns the firmware update status of every device, sorted by id.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::SoftwareUpdateState;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let devices = bridge.index_all_devices().await.unwrap();
+    /// for update in bridge.get_all_device_software_updates().await.unwrap() {
+    ///     if update.state != SoftwareUpdateState::NoUpdate {
+    ///         let name = devices.get(&update.owner.rid).map(|d| d.metadata.name.as_str());
+    ///         println!("{name:?}: {:?} {:?}", update.state, update.problems);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_device_software_updates(
+        &self,
+    ) -> crate::Result<Vec<DeviceSoftwareUpdate>> {
+        self.get_resources().await
+    }
+
+    /// Returns a vector of all device power services that are registered at this `Bridge`, sorted
+    /// by their id's. Only battery powered devices have one.
+    pub async fn get_all_device_power(&self) -> crate::Result<Vec<DevicePower>> {
+        self.get_resources().await
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Critical,
+}
+
+/// The health of a device, as computed by [`Device::health`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceHealth {
+    /// The worst status among all the checks below.
+    pub status: HealthStatus,
+    pub connectivity: Option<ConnectivityStatus>,
+    pub battery_state: Option<BatteryState>,
+    pub battery_level: Option<u8>,
+    /// The last time an event was received for the device or one of its services.
+    pub last_seen: Option<SystemTime>,
+    /// Human readable descriptions of the problems found.
+    pub problems: Vec<String>,
+}
+
+/// The connectivity and power of all devices, indexed by device id, along with the last time an
+/// event was seen for each of them.
+#[derive(Debug, Clone, Default)]
+pub struct HealthCache {
+    connectivity: HashMap<String, ZigbeeConnectivity>,
+    power: HashMap<String, DevicePower>,
+    owners: HashMap<String, String>,
+    last_seen: HashMap<String, SystemTime>,
+    stale_after: Option<Duration>,
+}
+
+impl HealthCache {
+    /// Fetches the devices, their connectivity and power services.
+    pub async fn fetch(bridge: &Bridge) -> crate::Result<HealthCache> {
+        Self::fetch_for(bridge, &bridge.get_all_devices().await?).await
+    }
+
+    /// Fetches the connectivity and power services of `devices`.
+    async fn fetch_for(bridge: &Bridge, devices: &[Device]) -> crate::Result<HealthCache> {
+        let mut cache = HealthCache::default();
+        for device in devices {
+            for service in &device.services {
+                cache.owners.insert(service.rid.clone(), device.id.clone());
+            }
+        }
+        for connectivity in bridge.get_all_zigbee_connectivity().await? {
+            cache
+                .connectivity
+                .insert(connectivity.owner.rid.clone(), connectivity);
+        }
+        for power in bridge.get_all_device_power().await? {
+            cache.power.insert(power.owner.rid.clone(), power);
+        }
+        Ok(cache)
+    }
+
+    /// Reports devices as stale when no event was received from them for `stale_after`. Mains
+    /// powered lights can stay silent for days, so this is off by default.
+    pub fn with_stale_after(self, stale_after: Duration) -> Self {
+        Self {
+            stale_after: Some(stale_after),
+            ..self
+        }
+    }
+
+    /// Records the activity of the devices an event is about, and their connectivity and battery
+    /// changes.
+    pub fn record_event(&mut self, event: &HueEvent) {
+        if let HueEvent::Update(data) = event {
+            let now = SystemTime::now();
+            for data in data {
+                match data {
+                    EventData::ZigbeeConnectivity(change) => {
+                        let cached = self.connectivity.values_mut().find(|c| c.id == change.id);
+                        if let (Some(cached), Some(status)) = (cached, change.status) {
+                            cached.status = status;
+                        }
+                    }
+                    EventData::DevicePower(change) => {
+                        let cached = self.power.values_mut().find(|p| p.id == change.id);
+                        if let (Some(cached), Some(power_state)) = (cached, &change.power_state) {
+                            cached.power_state = power_state.clone();
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            for id in data.iter().filter_map(EventData::id) {
+                let device = self.owners.get(id).map(String::as_str).unwrap_or(id);
+                // avoid allocating the key for devices already seen, by far the most common case
+                match self.last_seen.get_mut(device) {
+                    Some(last_seen) => *last_seen = now,
+                    None => {
+                        self.last_seen.insert(device.to_string(), now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Device {
+    /// Combines the zigbee connectivity, battery and last activity of this device into a single
+    /// health status.
+    pub fn health(&self, cache: &HealthCache) -> DeviceHealth {
+        let connectivity = cache.connectivity.get(&self.id).map(|c| c.status);
+        let power = cache.power.get(&self.id).map(|p| &p.power_state);
+        let last_seen = cache.last_seen.get(&self.id).copied();
+        let mut health = DeviceHealth {
+            status: HealthStatus::Ok,
+            connectivity,
+            battery_state: power.and_then(|p| p.battery_state),
+            battery_level: power.and_then(|p| p.battery_level),
+            last_seen,
+            problems: vec![],
+        };
+        let mut problem = |status: HealthStatus, description: String| {
+            health.status = health.status.max(status);
+            health.problems.push(description);
+        };
+        match connectivity {
+            Some(ConnectivityStatus::Connected) | None => (),
+            Some(ConnectivityStatus::Disconnected) => {
+                problem(HealthStatus::Critical, "disconnected".into())
+            }
+            Some(status) => problem(HealthStatus::Warning, format!("connectivity {status:?}")),
+        }
+        match power.and_then(|p| p.battery_state) {
+            Some(BatteryState::Critical) => {
+                problem(HealthStatus::Critical, "battery critical".into())
+            }
+            Some(BatteryState::Low) => problem(HealthStatus::Warning, "battery low".into()),
+            _ => (),
+        }
+        if let Some(stale_after) = cache.stale_after {
+            let silent_for = last_seen.and_then(|seen| seen.elapsed().ok());
+            if silent_for.is_none_or(|silent_for| silent_for > stale_after) {
+                problem(HealthStatus::Warning, "no recent activity".into());
+            }
+        }
+        health
+    }
+}
+
+impl Bridge {
+    /// Returns all the devices that have a problem, worst first.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for (device, health) in bridge.get_problem_devices().await.unwrap() {
+    ///     println!("{}: {:?}", device.metadata.name, health.problems);
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_problem_devices(&self) -> crate::Result<Vec<(Device, DeviceHealth)>> {
+        let devices = self.get_all_devices().await?;
+        let cache = HealthCache::fetch_for(self, &devices).await?;
+        let mut problems: Vec<_> = devices
+            .into_iter()
+            .map(|device| {
+                let health = device.health(&cache);
+                (device, health)
+            })
+            .filter(|(_, health)| health.status != HealthStatus::Ok)
+            .collect();
+        problems.sort_by_key(|(_, health)| std::cmp::Reverse(health.status));
+        Ok(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn zigbee_channel_names() {
+        assert_eq!(
+            serde_json::to_value(ZigbeeChannel::Channel11).unwrap(),
+            json!("channel_11")
+        );
+        for channel in [
+            ZigbeeChannel::Channel11,
+            ZigbeeChannel::Channel15,
+            ZigbeeChannel::Channel20,
+            ZigbeeChannel::Channel25,
+        ] {
+            let value = serde_json::to_value(channel).unwrap();
+            assert_eq!(
+                serde_json::from_value::<ZigbeeChannel>(value).unwrap(),
+                channel
+            );
+        }
+        let state: ZigbeeChannelState =
+            serde_json::from_value(json!({ "status": "set", "value": "channel_25" })).unwrap();
+        assert_eq!(state.channel(), Some(ZigbeeChannel::Channel25));
+        let state: ZigbeeChannelState =
+            serde_json::from_value(json!({ "status": "set", "value": "not_configured" })).unwrap();
+        assert_eq!(state.channel(), None);
+    }
+}
@@ -0,0 +1,79 @@
+//! Status of the third party ecosystems the bridge is integrated with.
+use crate::{Bridge, BridgeResponseV2};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HomekitStatus {
+    Paired,
+    Pairing,
+    Unpaired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Homekit {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub status: HomekitStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatterFabricStatus {
+    Pending,
+    Timedout,
+    Paired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatterFabricData {
+    /// The label the controller gave itself, such as the name of its ecosystem.
+    pub label: String,
+    pub vendor_id: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatterFabric {
+    pub id: String,
+    pub status: MatterFabricStatus,
+    /// Only set once the fabric is paired.
+    pub fabric_data: Option<MatterFabricData>,
+    /// When the fabric was paired, as an ISO 8601 timestamp.
+    pub creation_time: Option<String>,
+}
+
+impl Bridge {
+    /// Returns the HomeKit resource of this `Bridge`.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// println!("HomeKit: {:?}", bridge.get_homekit().await.unwrap().status);
+    /// # })
+    /// ```
+    pub async fn get_homekit(&self) -> crate::Result<Homekit> {
+        let url = format!("https://{}/clip/v2/resource/homekit", self.host());
+        let resp: BridgeResponseV2<Homekit> = self.send(self.client.get(&url)).await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err("expected a homekit resource"))
+    }
+
+    /// Returns a vector of all the Matter fabrics (controllers) this `Bridge` is paired with,
+    /// sorted by their id's.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for fabric in bridge.get_all_matter_fabrics().await.unwrap() {
+    ///     let label = fabric.fabric_data.map(|data| data.label);
+    ///     println!("{:?} {:?} since {:?}", label, fabric.status, fabric.creation_time);
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_matter_fabrics(&self) -> crate::Result<Vec<MatterFabric>> {
+        self.get_resources().await
+    }
+}
@@ -0,0 +1,170 @@
+//! Detecting the devices added, removed or renamed while an application was not running.
+use crate::{Bridge, Device, Storage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const INVENTORY: &str = "inventory";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryDevice {
+    pub name: String,
+    pub archetype: String,
+}
+
+/// The devices of a bridge at some point in time, by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventorySnapshot {
+    pub devices: BTreeMap<String, InventoryDevice>,
+}
+
+/// A difference between two [`InventorySnapshot`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryChange {
+    Added {
+        id: String,
+        name: String,
+    },
+    Removed {
+        id: String,
+        name: String,
+    },
+    Renamed {
+        id: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl InventorySnapshot {
+    pub fn from_devices<'a>(devices: impl IntoIterator<Item = &'a Device>) -> Self {
+        InventorySnapshot {
+            devices: devices
+                .into_iter()
+                .map(|device| {
+                    let device_data = InventoryDevice {
+                        name: device.metadata.name.clone(),
+                        archetype: device.metadata.archetype.clone(),
+                    };
+                    (device.id.clone(), device_data)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the changes from this snapshot to `newer`, sorted by device id.
+    pub fn diff(&self, newer: &InventorySnapshot) -> Vec<InventoryChange> {
+        let mut changes = vec![];
+        for (id, device) in &self.devices {
+            match newer.devices.get(id) {
+                None => changes.push(InventoryChange::Removed {
+                    id: id.clone(),
+                    name: device.name.clone(),
+                }),
+                Some(new) if new.name != device.name => changes.push(InventoryChange::Renamed {
+                    id: id.clone(),
+                    from: device.name.clone(),
+                    to: new.name.clone(),
+                }),
+                Some(_) => (),
+            }
+        }
+        for (id, device) in &newer.devices {
+            if !self.devices.contains_key(id) {
+                changes.push(InventoryChange::Added {
+                    id: id.clone(),
+                    name: device.name.clone(),
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.id().cmp(b.id()));
+        changes
+    }
+}
+
+impl InventoryChange {
+    pub fn id(&self) -> &str {
+        match self {
+            InventoryChange::Added { id, .. }
+            | InventoryChange::Removed { id, .. }
+            | InventoryChange::Renamed { id, .. } => id,
+        }
+    }
+}
+
+impl Bridge {
+    /// Compares the devices of this `Bridge` with the snapshot saved in `storage` by the previous
+    /// call, then saves the current devices as the new snapshot. The first call returns no
+    /// changes, as there is nothing to compare with.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{FileStorage, InventoryChange};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let storage = FileStorage::new("/var/lib/myapp/hue");
+    /// for change in bridge.diff_inventory(&storage).await.unwrap() {
+    ///     if let InventoryChange::Added { name, .. } = change {
+    ///         println!("new device while we were away: {name}");
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn diff_inventory(
+        &self,
+        storage: &dyn Storage,
+    ) -> crate::Result<Vec<InventoryChange>> {
+        let current = InventorySnapshot::from_devices(&self.get_all_devices().await?);
+        let changes = match storage.load(INVENTORY)? {
+            Some(previous) => {
+                serde_json::from_slice::<InventorySnapshot>(&previous)?.diff(&current)
+            }
+            None => vec![],
+        };
+        storage.save(INVENTORY, &serde_json::to_vec(&current)?)?;
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(devices: &[(&str, &str)]) -> InventorySnapshot {
+        InventorySnapshot {
+            devices: devices
+                .iter()
+                .map(|(id, name)| {
+                    let device = InventoryDevice {
+                        name: name.to_string(),
+                        archetype: "sultan_bulb".into(),
+                    };
+                    (id.to_string(), device)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn added_removed_renamed() {
+        let before = snapshot(&[("1", "Desk"), ("2", "Hall"), ("3", "Porch")]);
+        let after = snapshot(&[("1", "Desk"), ("2", "Hallway"), ("4", "Garden")]);
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                InventoryChange::Renamed {
+                    id: "2".into(),
+                    from: "Hall".into(),
+                    to: "Hallway".into()
+                },
+                InventoryChange::Removed {
+                    id: "3".into(),
+                    name: "Porch".into()
+                },
+                InventoryChange::Added {
+                    id: "4".into(),
+                    name: "Garden".into()
+                },
+            ]
+        );
+    }
+}
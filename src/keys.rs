@@ -0,0 +1,203 @@
+//! Managing the application keys registered on the bridge.
+use crate::{Bridge, BridgeResponse, BridgeResponseV2};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The result of [`Bridge::rotate_application_key`].
+#[derive(Debug, Clone)]
+pub struct KeyRotation {
+    /// The bridge, using the new application key.
+    pub bridge: Bridge,
+    /// Whether the old key was removed from the bridge. Recent firmwares do not allow removing
+    /// keys through the API anymore, they have to be removed from the Hue account instead.
+    pub old_key_deleted: bool,
+}
+
+/// An application registered on the bridge, as listed by [`Bridge::get_all_applications`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhitelistedApplication {
+    /// The application key of the application.
+    #[serde(skip_deserializing)]
+    pub key: String,
+    /// The name the application registered with, such as `hue_app#iphone`.
+    pub name: String,
+    /// When the application was registered, in local time, such as `2023-01-31T18:04:52`.
+    #[serde(rename = "create date")]
+    pub create_date: String,
+    /// When the application last used its key, in local time.
+    #[serde(rename = "last use date")]
+    pub last_use_date: String,
+}
+
+#[derive(Deserialize)]
+struct Whitelist {
+    whitelist: HashMap<String, WhitelistedApplication>,
+}
+
+impl Whitelist {
+    /// The applications, least recently used first.
+    fn into_applications(self) -> Vec<WhitelistedApplication> {
+        let mut applications: Vec<_> = self
+            .whitelist
+            .into_iter()
+            .map(|(key, application)| WhitelistedApplication { key, ..application })
+            .collect();
+        applications.sort_by(|a, b| (&a.last_use_date, &a.key).cmp(&(&b.last_use_date, &b.key)));
+        applications
+    }
+}
+
+impl Bridge {
+    /// Checks that the application key of this `Bridge` is accepted by the bridge.
+    pub async fn verify_application_key(&self) -> crate::Result<()> {
+        let url = format!("https://{}/clip/v2/resource/bridge", self.host());
+        let resp: BridgeResponseV2<Value> = self.send(self.client.get(&url)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    pub(crate) async fn delete_whitelist_entry(&self, key: &str) -> crate::Result<()> {
+        let url = format!(
+            "https://{}/api/{}/config/whitelist/{}",
+            self.host(),
+            self.application_key,
+            key
+        );
+        let resp: BridgeResponse<Value> = self.send(self.client.delete(&url)).await?;
+        resp.get_all()?;
+        Ok(())
+    }
+
+    /// Removes the application key of this `Bridge` from the bridge, for instance when the
+    /// application is uninstalled. The key cannot be used anymore afterwards. Recent firmwares do
+    /// not allow removing keys through the API, and return a `HueError::BridgeError`.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// bridge.unregister_application().await.unwrap();
+    /// # })
+    /// ```
+    pub async fn unregister_application(self) -> crate::Result<()> {
+        self.delete_whitelist_entry(&self.application_key).await
+    }
+
+    /// Returns all the applications registered on this `Bridge`, least recently used first, so
+    /// that old keys can be found and removed with [`Bridge::delete_application`].
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for application in bridge.get_all_applications().await.unwrap() {
+    ///     println!("{} was last used {}", application.name, application.last_use_date);
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_applications(&self) -> crate::Result<Vec<WhitelistedApplication>> {
+        let url = format!(
+            "https://{}/api/{}/config",
+            self.host(),
+            self.application_key
+        );
+        let resp: BridgeResponse<Whitelist> = self.send(self.client.get(&url)).await?;
+        Ok(resp.get()?.into_applications())
+    }
+
+    /// Removes the application key `key` from this `Bridge`, see
+    /// [`Bridge::unregister_application`].
+    pub async fn delete_application(&self, key: &str) -> crate::Result<()> {
+        self.delete_whitelist_entry(key).await
+    }
+
+    /// Replaces the application key of this `Bridge` by a new one, without downtime:
+    /// 1. a new key is registered under `name`, which requires the link button to have been
+    ///    pressed shortly before,
+    /// 2. the new key is checked to work,
+    /// 3. `store` is called to persist the new key, the old one keeps working until it succeeds,
+    /// 4. the old key is removed from the bridge, when the firmware allows it.
+    ///
+    /// If anything fails before `store` succeeds, the old key is left untouched.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let storage = hueclient::FileStorage::new("/var/lib/myapp/hue");
+    /// let rotation = bridge
+    ///     .rotate_application_key("mylaptop", |new| {
+    ///         let saved = new.save_application_key(&storage);
+    ///         async move { saved }
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// let bridge = rotation.bridge;
+    /// # })
+    /// ```
+    pub async fn rotate_application_key<F, Fut>(
+        &self,
+        name: &str,
+        store: F,
+    ) -> crate::Result<KeyRotation>
+    where
+        F: FnOnce(&Bridge) -> Fut,
+        Fut: std::future::Future<Output = crate::Result<()>>,
+    {
+        let new = self
+            .unauth()
+            .register_application(name)
+            .await?
+            .with_body_logging(self.body_logging.clone());
+        #[cfg(feature = "fault-injection")]
+        let new = new.with_fault_injection(self.faults.clone());
+        new.verify_application_key().await?;
+        if let Err(e) = store(&new).await {
+            // best effort, to avoid leaving an unused key behind
+            let _ = new.delete_whitelist_entry(&new.application_key).await;
+            return Err(e);
+        }
+        let old_key_deleted = match new.delete_whitelist_entry(&self.application_key).await {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("could not delete the old application key: {e}");
+                false
+            }
+        };
+        Ok(KeyRotation {
+            bridge: new,
+            old_key_deleted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applications_by_last_use() {
+        let config = serde_json::json!({
+            "name": "Philips hue",
+            "whitelist": {
+                "recent": {
+                    "last use date": "2024-05-02T08:00:00",
+                    "create date": "2023-01-31T18:04:52",
+                    "name": "hue_app#iphone"
+                },
+                "stale": {
+                    "last use date": "2019-11-20T21:12:01",
+                    "create date": "2019-11-20T21:11:40",
+                    "name": "ci#runner"
+                }
+            }
+        });
+        let whitelist: Whitelist = serde_json::from_value(config).unwrap();
+        let applications = whitelist.into_applications();
+        assert_eq!(applications[0].key, "stale");
+        assert_eq!(applications[0].name, "ci#runner");
+        assert_eq!(applications[1].key, "recent");
+        assert_eq!(applications[1].create_date, "2023-01-31T18:04:52");
+    }
+}
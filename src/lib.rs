@@ -4,8 +4,10 @@
 //! A short overview of the most common use cases of this library.
 //! ### Initial Setup
 //! ```no_run
+//! # #[cfg(feature = "discovery")]
 //! # tokio_test::block_on(async {
 //! let bridge = hueclient::Bridge::discover_required()
+//!     .await
 //!     .register_application("mycomputer") // Press the bridge before running this
 //!     .await
 //!     .unwrap();
@@ -14,15 +16,21 @@
 //! ```
 //! ### Second run
 //! ```no_run
+//! # #[cfg(feature = "discovery")]
+//! # tokio_test::block_on(async {
 //! const USERNAME: &str = "the username that was generated in the previous example";
 //! let bridge = hueclient::Bridge::discover_required()
+//!    .await
 //!    .with_user(USERNAME);
+//! # })
 //! ```
 //! ### Good night
 //! ```no_run
+//! # #[cfg(feature = "discovery")]
 //! # tokio_test::block_on(async {
 //! # const USERNAME: &str = "the username that was generated in the previous example";
 //! # let bridge = hueclient::Bridge::discover_required()
+//! #   .await
 //! #   .with_user(USERNAME);
 //! let cmd = hueclient::CommandLight::default().off();
 //! for light in &bridge.get_all_lights().await.unwrap() {
@@ -37,12 +45,16 @@ pub enum HueError {
     /// Returned when a network error occurs.
     #[error("An error occurred while performing an HTTP request")]
     Reqwest(#[from] reqwest::Error),
+    #[cfg(feature = "events")]
     #[error("An error occurred while creating an event source")]
     ReqwestEventSource(#[from] reqwest_eventsource::CannotCloneRequestError),
     /// Returned on a JSON failure, which will usually be a problem with deserializing the bridge
     /// response.
     #[error("An error occurred while manipulating JSON")]
     SerdeJson(#[from] serde_json::Error),
+    /// Returned when reading or writing a [`FileStorage`] fails.
+    #[error("An I/O error occurred")]
+    Io(#[from] std::io::Error),
     /// Returned when discovery.meethue.com returns an invalid IP-address.
     #[error("An error occurred while parsing an address")]
     AddrParse(#[from] std::net::AddrParseError),
@@ -65,6 +77,41 @@ pub enum HueError {
         /// An error message describing the failure.
         description: String,
     },
+    /// Returned when an operation did not complete within its deadline.
+    #[error("A timeout occurred: {}", msg)]
+    Timeout {
+        /// An error message describing what timed out.
+        msg: String,
+    },
+    /// Returned when the bridge firmware is too old for an operation.
+    #[error("An unsupported feature was used: {}", msg)]
+    UnsupportedFeature {
+        /// An error message describing the missing feature.
+        msg: String,
+    },
+    /// Returned when a command is not supported by the light it is sent to.
+    #[error("An invalid command was sent")]
+    InvalidCommand(#[from] CommandError),
+    /// Returned when a command targets a light, or a room or zone with a light, that is part of
+    /// an active entertainment stream.
+    #[error("Light {} is being streamed to", light)]
+    LightStreamed {
+        /// The id of the light.
+        light: String,
+    },
+    /// Returned when the bridge refuses a request because it received too many of them, the
+    /// request can be sent again later.
+    #[error("The bridge is receiving too many requests")]
+    RateLimited,
+    /// Returned when an operation was aborted through its `CancellationToken`.
+    #[error("The operation was cancelled")]
+    Cancelled,
+    /// Returned when the DTLS connection of an entertainment stream fails.
+    #[error("A streaming error occurred: {}", msg)]
+    StreamingError {
+        /// An error message describing the failure.
+        msg: String,
+    },
     /// Returned when discovering a bridge in the local network fails.
     #[error("A discovery error occurred: {}", msg)]
     DiscoveryError {
@@ -84,9 +131,176 @@ impl HueError {
 /// A type alias used for convenience and consiceness throughout the library.
 pub type Result<T> = std::result::Result<T, HueError>;
 
+pub use tokio_util::sync::CancellationToken;
+
+/// Runs `operation` until it completes, or until `token` is cancelled, in which case `operation`
+/// is dropped and `HueError::Cancelled` is returned. All the operations of this crate, including
+/// discovery, registration and bulk requests, can safely be dropped at any point.
+/// ### Example
+/// ```no_run
+/// # #[cfg(feature = "discovery")]
+/// # tokio_test::block_on(async {
+/// let shutdown = hueclient::CancellationToken::new();
+/// let ip = hueclient::cancellable(&shutdown, async {
+///     hueclient::Bridge::discover()
+///         .await
+///         .map(|bridge| bridge.ip)
+///         .ok_or(hueclient::HueError::DiscoveryError {
+///             msg: "no bridge found".into(),
+///         })
+/// })
+/// .await;
+/// # })
+/// ```
+pub async fn cancellable<T>(
+    token: &CancellationToken,
+    operation: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        result = operation => result,
+        _ = token.cancelled() => Err(HueError::Cancelled),
+    }
+}
+
+/// Declares an enum of the string values of a field of the API. Values this library does not know
+/// about are kept in an `Other` variant, so that they are written back to the bridge unchanged.
+macro_rules! string_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_attr:meta])* $variant:ident => $value:literal),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $($(#[$variant_attr])* $variant,)*
+            /// A value this library does not know about, as sent by the bridge.
+            Other(String),
+        }
+
+        impl $name {
+            /// The name of the value in the API.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Other(value.to_string()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                $name::from(value.as_str())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.as_str().to_string()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+mod animation;
+mod behavior;
 mod bridge;
+mod coalesce;
+mod color;
+pub mod colors;
 mod command_parser;
+mod danger;
+mod diagnostics;
+#[cfg(feature = "discovery")]
 mod disco;
+mod enriched;
+mod entertainment;
+mod event_bus;
+mod events;
+mod fake;
+#[cfg(feature = "fault-injection")]
+mod faults;
+mod geofence;
+mod groups;
+mod health;
+mod integrations;
+mod inventory;
+mod keys;
+mod lock;
+mod logging;
+mod names;
+#[cfg(feature = "events")]
+mod occupancy;
+mod reconcile;
+mod resource;
+mod scenes;
+mod security;
+mod sensors;
+mod setup;
+mod smart_scenes;
+mod state;
+mod storage;
+#[cfg(feature = "entertainment")]
+mod streaming;
+mod tls;
+mod validation;
+mod version;
 
+pub use animation::*;
+pub use behavior::*;
 pub use bridge::*;
+pub use coalesce::*;
+pub use color::*;
 pub use command_parser::*;
+pub use danger::*;
+pub use diagnostics::*;
+#[cfg(feature = "discovery")]
+pub use disco::BridgeInfo;
+pub use enriched::*;
+pub use entertainment::*;
+pub use event_bus::*;
+pub use events::*;
+pub use fake::*;
+#[cfg(feature = "fault-injection")]
+pub use faults::*;
+pub use geofence::*;
+pub use groups::*;
+pub use health::*;
+pub use integrations::*;
+pub use inventory::*;
+pub use keys::*;
+pub use lock::*;
+pub use logging::*;
+pub use names::*;
+#[cfg(feature = "events")]
+pub use occupancy::*;
+pub use reconcile::*;
+pub use resource::*;
+pub use scenes::*;
+pub use security::*;
+pub use sensors::*;
+pub use setup::*;
+pub use smart_scenes::*;
+pub use state::*;
+pub use storage::*;
+#[cfg(feature = "entertainment")]
+pub use streaming::*;
+pub use validation::*;
+pub use version::*;
@@ -0,0 +1,271 @@
+//! An advisory lock shared by all the applications talking to the same bridge.
+//!
+//! The claim is stored on the bridge itself, as a v1 `resourcelink` whose description holds the
+//! owner and the expiry time of the claim. Nothing prevents an application ignoring the lock from
+//! controlling the lights: it only helps cooperating applications (say, several instances of the
+//! same adaptive lighting controller) agree on which one is in charge.
+use crate::{Bridge, BridgeResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const LOCK_PREFIX: &str = "hueclient-lock:";
+// arbitrary, but constant, class id for the resourcelinks used as locks
+const LOCK_CLASS_ID: u32 = 7407;
+// resourcelink descriptions are limited to 64 characters
+const MAX_DESCRIPTION_LEN: usize = 64;
+// the owner is followed by a space and the expiry, up to 20 digits
+const MAX_OWNER_LEN: usize = MAX_DESCRIPTION_LEN - 21;
+// resourcelink names are limited to 32 characters, the prefix included
+const MAX_NAME_LEN: usize = 32;
+
+/// The current claim on a lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockClaim {
+    pub owner: String,
+    pub expires_at: SystemTime,
+}
+
+impl LockClaim {
+    fn parse(description: &str) -> Option<LockClaim> {
+        let (owner, expiry) = description.rsplit_once(' ')?;
+        Some(LockClaim {
+            owner: owner.to_string(),
+            expires_at: UNIX_EPOCH + Duration::from_secs(expiry.parse().ok()?),
+        })
+    }
+
+    fn to_description(&self) -> String {
+        let expiry = self
+            .expires_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        format!("{} {}", self.owner, expiry)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= SystemTime::now()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Created {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceLink {
+    name: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PostResourceLink<'a> {
+    name: &'a str,
+    description: &'a str,
+    classid: u32,
+    recycle: bool,
+    links: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct PutResourceLink<'a> {
+    description: &'a str,
+}
+
+/// An advisory lock named `name`, claimed on behalf of `owner`. See the [module
+/// documentation](self) for the caveats.
+#[derive(Debug, Clone)]
+pub struct BridgeLock {
+    bridge: Bridge,
+    name: String,
+    owner: String,
+    ttl: Duration,
+}
+
+impl Bridge {
+    /// Returns a handle on the advisory lock `name`, to be claimed by `owner`. Owners should be
+    /// unique per application instance and at most 43 bytes long, longer ones are truncated.
+    /// Names are limited to 17 bytes, the operations on a lock with a longer name fail.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let lock = bridge.lock("adaptive-lighting", "kitchen-pi");
+    /// if lock.try_acquire().await.unwrap() {
+    ///     // we are in charge until the claim expires, renew it with try_acquire
+    /// }
+    /// # })
+    /// ```
+    pub fn lock(&self, name: impl Into<String>, owner: impl Into<String>) -> BridgeLock {
+        let mut owner: String = owner.into();
+        if owner.len() > MAX_OWNER_LEN {
+            let mut end = MAX_OWNER_LEN;
+            while !owner.is_char_boundary(end) {
+                end -= 1;
+            }
+            owner.truncate(end);
+        }
+        BridgeLock {
+            bridge: self.clone(),
+            name: name.into(),
+            owner,
+            ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+impl BridgeLock {
+    /// Sets for how long a claim is valid. The owner is expected to renew it before it expires.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        Self { ttl, ..self }
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "https://{}/api/{}/resourcelinks",
+            self.bridge.host(),
+            self.bridge.application_key
+        )
+    }
+
+    fn link_name(&self) -> crate::Result<String> {
+        let name = format!("{LOCK_PREFIX}{}", self.name);
+        if name.len() > MAX_NAME_LEN {
+            return Err(crate::HueError::protocol_err(format!(
+                "the lock name {:?} is longer than {} bytes",
+                self.name,
+                MAX_NAME_LEN - LOCK_PREFIX.len()
+            )));
+        }
+        Ok(name)
+    }
+
+    async fn find(&self) -> crate::Result<Option<(String, Option<LockClaim>)>> {
+        let name = self.link_name()?;
+        let links: HashMap<String, ResourceLink> =
+            self.bridge.send(self.bridge.client.get(self.url())).await?;
+        // concurrent claims may have created several links, the oldest one is the lock
+        Ok(links
+            .into_iter()
+            .filter(|(_, link)| link.name == name)
+            .min_by(|(a, _), (b, _)| (a.len(), a).cmp(&(b.len(), b)))
+            .map(|(id, link)| (id, LockClaim::parse(&link.description))))
+    }
+
+    /// Returns the current claim on the lock, if any, expired or not.
+    pub async fn holder(&self) -> crate::Result<Option<LockClaim>> {
+        Ok(self.find().await?.and_then(|(_, claim)| claim))
+    }
+
+    /// Claims or renews the lock. Returns false if it is currently held by another owner.
+    pub async fn try_acquire(&self) -> crate::Result<bool> {
+        let existing = self.find().await?;
+        if let Some((_, Some(claim))) = &existing {
+            if claim.owner != self.owner && !claim.is_expired() {
+                return Ok(false);
+            }
+        }
+        let claim = LockClaim {
+            owner: self.owner.clone(),
+            expires_at: SystemTime::now() + self.ttl,
+        };
+        let created = self
+            .write(existing.map(|(id, _)| id), &claim.to_description())
+            .await?;
+        if let Some(created) = created {
+            // another owner may have created a link at the same time, only the oldest one counts
+            if self.find().await?.is_none_or(|(id, _)| id != created) {
+                if let Err(e) = self.delete(&created).await {
+                    log::warn!("could not delete the lost lock link {created}: {e}");
+                }
+                return Ok(false);
+            }
+        }
+        // two owners may have written concurrently, the last write wins
+        Ok(self.holder().await?.is_some_and(|c| c.owner == self.owner))
+    }
+
+    /// Releases the lock if it is held by this owner.
+    pub async fn release(&self) -> crate::Result<()> {
+        if let Some((id, Some(claim))) = self.find().await? {
+            if claim.owner == self.owner {
+                let expired = LockClaim {
+                    owner: self.owner.clone(),
+                    expires_at: UNIX_EPOCH,
+                };
+                self.write(Some(id), &expired.to_description()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the link `id`, or creates a new one and returns its id.
+    async fn write(&self, id: Option<String>, description: &str) -> crate::Result<Option<String>> {
+        match id {
+            Some(id) => {
+                let request = self
+                    .bridge
+                    .client
+                    .put(format!("{}/{}", self.url(), id))
+                    .json(&PutResourceLink { description });
+                let resp: BridgeResponse<Value> = self.bridge.send(request).await?;
+                resp.get_all()?;
+                Ok(None)
+            }
+            None => {
+                let request = self.bridge.client.post(self.url()).json(&PostResourceLink {
+                    name: &self.link_name()?,
+                    description,
+                    classid: LOCK_CLASS_ID,
+                    recycle: false,
+                    links: vec!["/groups/0"],
+                });
+                let resp: BridgeResponse<Created> = self.bridge.send(request).await?;
+                Ok(Some(resp.get()?.id))
+            }
+        }
+    }
+
+    async fn delete(&self, id: &str) -> crate::Result<()> {
+        let request = self.bridge.client.delete(format!("{}/{}", self.url(), id));
+        let resp: BridgeResponse<Value> = self.bridge.send(request).await?;
+        resp.get_all()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claim_description_round_trip() {
+        let claim = LockClaim {
+            owner: "kitchen pi".into(),
+            expires_at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        };
+        let description = claim.to_description();
+        assert_eq!(description, "kitchen pi 1700000000");
+        assert_eq!(LockClaim::parse(&description), Some(claim));
+    }
+
+    #[test]
+    fn owner_and_name_limits() {
+        let bridge = Bridge::for_ip([192u8, 168, 0, 4]).with_user("key");
+        let lock = bridge.lock("adaptive-lighting", "o".repeat(100));
+        let claim = LockClaim {
+            owner: lock.owner.clone(),
+            expires_at: UNIX_EPOCH + Duration::from_secs(u64::MAX / 2),
+        };
+        assert_eq!(claim.to_description().len(), MAX_DESCRIPTION_LEN - 1);
+        assert!(lock.link_name().is_ok());
+        assert!(bridge
+            .lock("adaptive-lighting-2", "pi")
+            .link_name()
+            .is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! Opt-in logging of the bodies exchanged with the bridge, to investigate firmware quirks.
+use crate::Bridge;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Which requests have their bodies logged, see [`Bridge::with_body_logging`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BodyLogging {
+    /// No body is logged, the default.
+    #[default]
+    Off,
+    /// The bodies of all requests are logged.
+    All,
+    /// Only the bodies of requests to these endpoints are logged. Endpoints are resource types
+    /// such as `light` or `scene` for the v2 API, and top level paths such as `config` or
+    /// `resourcelinks` for the v1 API.
+    Endpoints(Vec<String>),
+}
+
+/// The endpoint of a request path: the resource type for v2 paths, the segment following the
+/// application key for v1 paths.
+fn endpoint(path: &str) -> Option<&str> {
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    match (segments.next(), segments.next()) {
+        (Some("clip"), Some("v2")) => segments.nth(1),
+        (Some("api"), Some(_key)) => segments.next(),
+        _ => None,
+    }
+}
+
+impl BodyLogging {
+    fn applies_to(&self, path: &str) -> bool {
+        match self {
+            BodyLogging::Off => false,
+            BodyLogging::All => true,
+            BodyLogging::Endpoints(endpoints) => {
+                endpoint(path).is_some_and(|endpoint| endpoints.iter().any(|e| e == endpoint))
+            }
+        }
+    }
+}
+
+static SECRET_FIELDS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#""(username|clientkey|application_key)"\s*:\s*"[^"]*""#).unwrap()
+});
+
+/// Replaces the keys of the `whitelist` objects, which are the application keys of every
+/// application registered on the bridge.
+fn redact_whitelists(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            if let Some(serde_json::Value::Object(whitelist)) = object.get_mut("whitelist") {
+                *whitelist = std::mem::take(whitelist)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (_, application))| (format!("<redacted {i}>"), application))
+                    .collect();
+            }
+            object.values_mut().for_each(redact_whitelists);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_whitelists),
+        _ => (),
+    }
+}
+
+/// Removes the application key and the other credentials from a logged url or body.
+fn redact(text: &str, application_key: &str) -> String {
+    let mut text = if application_key.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(application_key, "<redacted>")
+    };
+    if let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&text) {
+        redact_whitelists(&mut value);
+        text = value.to_string();
+    }
+    SECRET_FIELDS
+        .replace_all(&text, r#""$1":"<redacted>""#)
+        .into_owned()
+}
+
+// the body of these responses is not JSON
+fn check_rate_limit(response: reqwest::Response) -> crate::Result<reqwest::Response> {
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(crate::HueError::RateLimited);
+    }
+    Ok(response)
+}
+
+impl Bridge {
+    /// Logs the bodies of the requests and responses selected by `body_logging`, with credentials
+    /// redacted. They are logged at the debug level, with the `hueclient::body` target.
+    /// ### Example
+    /// ```no_run
+    /// use hueclient::BodyLogging;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj")
+    ///    .with_body_logging(BodyLogging::Endpoints(vec!["light".into(), "scene".into()]));
+    /// ```
+    pub fn with_body_logging(self, body_logging: BodyLogging) -> Self {
+        Self {
+            body_logging,
+            ..self
+        }
+    }
+
+    /// Sends a request and deserializes the JSON response, logging both bodies if requested.
+    pub(crate) async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> crate::Result<T> {
+        self.send_reading(
+            request,
+            |response| async move { Ok(response.bytes().await?) },
+        )
+        .await
+    }
+
+    /// Same as [`Bridge::send`], but the body of the response is read by `read`, for instance to
+    /// report the progress of a big download.
+    pub(crate) async fn send_reading<T, F, Fut, B>(
+        &self,
+        request: reqwest::RequestBuilder,
+        read: F,
+    ) -> crate::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        F: FnOnce(reqwest::Response) -> Fut,
+        Fut: std::future::Future<Output = crate::Result<B>>,
+        B: AsRef<[u8]>,
+    {
+        let request = request.build()?;
+        #[cfg(feature = "fault-injection")]
+        self.faults.before_request().await?;
+        if !self.body_logging.applies_to(request.url().path()) {
+            let response = check_rate_limit(self.client.execute(request).await?)?;
+            return Ok(serde_json::from_slice(read(response).await?.as_ref())?);
+        }
+        let url = redact(request.url().as_str(), &self.application_key);
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+        let body = redact(&body, &self.application_key);
+        log::debug!(target: "hueclient::body", "{} {url} {body}", request.method());
+        let response = check_rate_limit(self.client.execute(request).await?)?;
+        let status = response.status();
+        let body = read(response).await?;
+        let body = body.as_ref();
+        let logged = redact(&String::from_utf8_lossy(body), &self.application_key);
+        log::debug!(target: "hueclient::body", "{status} {url} {logged}");
+        Ok(serde_json::from_slice(body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_and_redaction() {
+        assert_eq!(endpoint("/clip/v2/resource/light/abc"), Some("light"));
+        assert_eq!(
+            endpoint("/api/secret/resourcelinks/12"),
+            Some("resourcelinks")
+        );
+        assert_eq!(endpoint("/clip/v2/resource"), None);
+        let logging = BodyLogging::Endpoints(vec!["scene".into()]);
+        assert!(logging.applies_to("/clip/v2/resource/scene"));
+        assert!(!logging.applies_to("/clip/v2/resource/light"));
+        assert_eq!(
+            redact(
+                r#"/api/secret/config {"username": "other", "name": "secret"}"#,
+                "secret"
+            ),
+            r#"/api/<redacted>/config {"username":"<redacted>", "name": "<redacted>"}"#
+        );
+        let config = serde_json::json!({
+            "name": "Philips hue",
+            "whitelist": {
+                "other-application-key": { "name": "hue_app#iphone" },
+                "secret": { "name": "myapp" }
+            }
+        });
+        let redacted = redact(&config.to_string(), "secret");
+        assert!(!redacted.contains("other-application-key"));
+        assert!(redacted.contains(r#""<redacted 0>":{"name":"#));
+        assert!(redacted.contains(r#""<redacted 1>":{"name":"#));
+    }
+}
@@ -0,0 +1,214 @@
+//! Helpers for comparing names, and for finding lights by name when names are not unique.
+use crate::{Bridge, BridgeResponseV2};
+use caseless::Caseless;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use unicode_normalization::UnicodeNormalization;
+
+/// Returns the form of `name` used to compare names: surrounding whitespace is trimmed, then
+/// Unicode compatibility normalization and case folding are applied. Unlike `to_lowercase`,
+/// this matches `Straße` with `STRASSE`, and an accented letter with the same letter followed
+/// by a combining accent.
+pub fn normalize_name(name: &str) -> String {
+    name.trim()
+        .nfd()
+        .default_case_fold()
+        .nfkd()
+        .default_case_fold()
+        .nfkc()
+        .collect()
+}
+
+/// Whether two names are the same once normalized with [`normalize_name`].
+/// ### Example
+/// ```
+/// assert!(hueclient::names_match("Salle à manger", "SALLE A\u{300} MANGER"));
+/// assert!(hueclient::names_match("Straße 🌙", "strasse 🌙"));
+/// assert!(!hueclient::names_match("Salle à manger", "Salle a manger"));
+/// ```
+pub fn names_match(a: &str, b: &str) -> bool {
+    normalize_name(a) == normalize_name(b)
+}
+
+/// A light, annotated with the name of its room and a name that is unique across the bridge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightHandle {
+    pub id: String,
+    pub name: String,
+    pub room: Option<String>,
+    /// The light name if it is unique, otherwise the light name followed by its room name, and
+    /// a number if it is still ambiguous.
+    pub unique_name: String,
+}
+
+/// A name shared by several lights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateName {
+    pub name: String,
+    pub lights: Vec<LightHandle>,
+}
+
+fn disambiguate(lights: Vec<(String, String, Option<String>)>) -> Vec<LightHandle> {
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    for (_, name, _) in &lights {
+        *by_name.entry(normalize_name(name)).or_default() += 1;
+    }
+    let mut handles: Vec<LightHandle> = lights
+        .iter()
+        .map(|(id, name, room)| LightHandle {
+            id: id.clone(),
+            name: name.clone(),
+            room: room.clone(),
+            unique_name: match room {
+                Some(room) if by_name[&normalize_name(name)] > 1 => format!("{name} ({room})"),
+                _ => name.clone(),
+            },
+        })
+        .collect();
+    let mut by_unique_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (ix, handle) in handles.iter().enumerate() {
+        by_unique_name
+            .entry(normalize_name(&handle.unique_name))
+            .or_default()
+            .push(ix);
+    }
+    for ixs in by_unique_name.values().filter(|ixs| ixs.len() > 1) {
+        for (n, &ix) in ixs.iter().enumerate() {
+            handles[ix].unique_name = format!("{} #{}", handles[ix].unique_name, n + 1);
+        }
+    }
+    handles
+}
+
+#[derive(Serialize)]
+struct CommandLightName<'a> {
+    metadata: LightName<'a>,
+}
+
+#[derive(Serialize)]
+struct LightName<'a> {
+    name: &'a str,
+}
+
+impl Bridge {
+    /// Returns a handle on every light, sorted by id, with a unique name built from its room name
+    /// when several lights share the same name.
+    pub async fn get_light_handles(&self) -> crate::Result<Vec<LightHandle>> {
+        let devices = self.index_all_devices().await?;
+        let mut rooms_by_light: HashMap<&str, String> = HashMap::new();
+        let rooms = self.get_all_rooms().await?;
+        for room in &rooms {
+            for child in &room.children {
+                if let Some(device) = devices.get(&child.rid) {
+                    for light in device.get_lights() {
+                        rooms_by_light.insert(light, room.metadata.name.clone());
+                    }
+                }
+            }
+        }
+        let lights = self
+            .get_all_lights()
+            .await?
+            .into_iter()
+            .map(|light| {
+                let room = rooms_by_light.get(light.id.as_str()).cloned();
+                (light.id, light.metadata.name, room)
+            })
+            .collect();
+        Ok(disambiguate(lights))
+    }
+
+    /// Returns the light names that are used more than once, names that only differ by case or
+    /// Unicode normalization being considered the same.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for duplicate in bridge.find_duplicate_light_names().await.unwrap() {
+    ///     for light in duplicate.lights {
+    ///         println!("{} is {}", light.id, light.unique_name);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn find_duplicate_light_names(&self) -> crate::Result<Vec<DuplicateName>> {
+        let mut by_name: HashMap<String, Vec<LightHandle>> = HashMap::new();
+        for handle in self.get_light_handles().await? {
+            by_name
+                .entry(normalize_name(&handle.name))
+                .or_default()
+                .push(handle);
+        }
+        let mut duplicates: Vec<DuplicateName> = by_name
+            .into_values()
+            .filter(|lights| lights.len() > 1)
+            .map(|lights| DuplicateName {
+                name: lights[0].name.clone(),
+                lights,
+            })
+            .collect();
+        duplicates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(duplicates)
+    }
+
+    pub async fn rename_light(&self, light: &str, name: &str) -> crate::Result<()> {
+        let url = format!("https://{}/clip/v2/resource/light/{}", self.host(), light);
+        let resp: BridgeResponseV2<Value> = self
+            .send(self.client.put(&url).json(&CommandLightName {
+                metadata: LightName { name },
+            }))
+            .await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Renames every light whose name is not unique to its unique name, and returns the renamed
+    /// lights. The bridge limits names to 32 characters, so long names may be rejected.
+    pub async fn rename_duplicate_lights(&self) -> crate::Result<Vec<LightHandle>> {
+        let mut renamed = vec![];
+        for duplicate in self.find_duplicate_light_names().await? {
+            for light in duplicate.lights {
+                self.rename_light(&light.id, &light.unique_name).await?;
+                renamed.push(light);
+            }
+        }
+        Ok(renamed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn light(id: &str, name: &str, room: Option<&str>) -> (String, String, Option<String>) {
+        (id.into(), name.into(), room.map(Into::into))
+    }
+
+    #[test]
+    fn disambiguates_with_room_then_number() {
+        let handles = disambiguate(vec![
+            light("1", "Ceiling", Some("Kitchen")),
+            light("2", "Ceiling", Some("Office")),
+            light("3", "Lamp", Some("Office")),
+            light("4", "Spot", Some("Office")),
+            light("5", "SPOT", Some("Office")),
+            light("6", "Café", Some("Kitchen")),
+            light("7", "Cafe\u{301}", Some("Office")),
+        ]);
+        let names: Vec<&str> = handles.iter().map(|h| h.unique_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Ceiling (Kitchen)",
+                "Ceiling (Office)",
+                "Lamp",
+                "Spot (Office) #1",
+                "SPOT (Office) #2",
+                "Café (Kitchen)",
+                "Cafe\u{301} (Office)"
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Room occupancy, combining motion sensors, light level sensors and the lights of each room.
+use crate::{Bridge, EventData, HueEvent};
+use futures::{Stream, StreamExt};
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Occupancy {
+    /// Motion was detected recently, or the lights were turned on.
+    Occupied,
+    /// No motion was detected for a while, the room is probably being left.
+    CoolingDown,
+    /// No motion was detected for long enough to consider the room empty.
+    Vacant,
+}
+
+/// A change of occupancy of a room.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OccupancyUpdate {
+    pub room_id: String,
+    pub room_name: String,
+    pub occupancy: Occupancy,
+    /// The last light level reported by a sensor of the room, as 10000 * log10(lux) + 1.
+    pub light_level: Option<u32>,
+    /// Whether the lights of the room are on.
+    pub lights_on: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct RoomState {
+    name: String,
+    occupancy: Occupancy,
+    motion: bool,
+    last_activity: Instant,
+    light_level: Option<u32>,
+    lights_on: Option<bool>,
+}
+
+impl RoomState {
+    fn new(name: String, now: Instant) -> Self {
+        RoomState {
+            name,
+            occupancy: Occupancy::Vacant,
+            motion: false,
+            last_activity: now,
+            light_level: None,
+            lights_on: None,
+        }
+    }
+
+    fn activity(&mut self, now: Instant) -> bool {
+        self.last_activity = now;
+        let changed = self.occupancy != Occupancy::Occupied;
+        self.occupancy = Occupancy::Occupied;
+        changed
+    }
+
+    fn on_motion(&mut self, motion: bool, now: Instant) -> bool {
+        self.motion = motion;
+        if motion {
+            self.activity(now)
+        } else {
+            // the timeouts start when the sensor stops reporting motion
+            self.last_activity = now;
+            false
+        }
+    }
+
+    /// The time at which the occupancy will change if nothing happens.
+    fn deadline(&self, config: &OccupancyTracker) -> Option<Instant> {
+        match self.occupancy {
+            _ if self.motion => None,
+            Occupancy::Occupied => Some(self.last_activity + config.cooling_down_after),
+            Occupancy::CoolingDown => {
+                Some(self.last_activity + config.cooling_down_after + config.vacant_after)
+            }
+            Occupancy::Vacant => None,
+        }
+    }
+
+    fn tick(&mut self, now: Instant, config: &OccupancyTracker) -> bool {
+        let Some(deadline) = self.deadline(config) else {
+            return false;
+        };
+        if now < deadline {
+            return false;
+        }
+        self.occupancy = match self.occupancy {
+            Occupancy::Occupied => Occupancy::CoolingDown,
+            _ => Occupancy::Vacant,
+        };
+        // a long pause may skip cooling down altogether
+        if self
+            .deadline(config)
+            .is_some_and(|deadline| now >= deadline)
+        {
+            self.occupancy = Occupancy::Vacant;
+        }
+        true
+    }
+}
+
+/// Tracks the occupancy of every room from the event stream, see
+/// [`OccupancyTracker::updates`].
+#[derive(Debug, Clone)]
+pub struct OccupancyTracker {
+    bridge: Bridge,
+    cooling_down_after: Duration,
+    vacant_after: Duration,
+}
+
+type Rooms = HashMap<String, RoomState>;
+
+struct Tracking {
+    config: OccupancyTracker,
+    events: Pin<Box<dyn Stream<Item = HueEvent> + Send>>,
+    rooms: Rooms,
+    // resource id (motion, light level or grouped light) to room id
+    room_of: HashMap<String, String>,
+    pending: VecDeque<OccupancyUpdate>,
+}
+
+impl Tracking {
+    fn update(&self, room_id: &str) -> Option<OccupancyUpdate> {
+        let room = self.rooms.get(room_id)?;
+        Some(OccupancyUpdate {
+            room_id: room_id.to_string(),
+            room_name: room.name.clone(),
+            occupancy: room.occupancy,
+            light_level: room.light_level,
+            lights_on: room.lights_on,
+        })
+    }
+
+    fn apply(&mut self, data: &EventData, now: Instant) {
+        let Some(room_id) = data.id().and_then(|id| self.room_of.get(id)).cloned() else {
+            return;
+        };
+        let Some(room) = self.rooms.get_mut(&room_id) else {
+            return;
+        };
+        let changed = match data {
+            EventData::Motion(motion) => match &motion.motion {
+                Some(report) if report.motion_valid => room.on_motion(report.motion, now),
+                _ => false,
+            },
+            EventData::LightLevel(light_level) => {
+                if let Some(report) = light_level.light.as_ref().filter(|r| r.light_level_valid) {
+                    room.light_level = Some(report.light_level);
+                }
+                false
+            }
+            EventData::GroupedLight(group) => match &group.on {
+                Some(on) => {
+                    room.lights_on = Some(on.on);
+                    // turning the lights on is a sure sign someone is there
+                    on.on && room.activity(now)
+                }
+                None => false,
+            },
+            _ => false,
+        };
+        if changed {
+            self.pending.extend(self.update(&room_id));
+        }
+    }
+
+    fn tick(&mut self, now: Instant) {
+        let mut changed: Vec<String> = self
+            .rooms
+            .iter_mut()
+            .filter_map(|(id, room)| room.tick(now, &self.config).then(|| id.clone()))
+            .collect();
+        changed.sort();
+        for id in changed {
+            self.pending.extend(self.update(&id));
+        }
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.rooms
+            .values()
+            .filter_map(|room| room.deadline(&self.config))
+            .min()
+    }
+}
+
+impl OccupancyTracker {
+    pub fn new(bridge: Bridge) -> Self {
+        OccupancyTracker {
+            bridge,
+            cooling_down_after: Duration::from_secs(120),
+            vacant_after: Duration::from_secs(180),
+        }
+    }
+
+    /// How long without motion before an occupied room is cooling down. Defaults to 2 minutes.
+    pub fn with_cooling_down_after(self, cooling_down_after: Duration) -> Self {
+        Self {
+            cooling_down_after,
+            ..self
+        }
+    }
+
+    /// How long a room stays cooling down, without motion, before it is vacant. Defaults to 3
+    /// minutes.
+    pub fn with_vacant_after(self, vacant_after: Duration) -> Self {
+        Self {
+            vacant_after,
+            ..self
+        }
+    }
+
+    /// Returns a stream of the occupancy changes of all rooms. Rooms start vacant, or occupied
+    /// when their lights are on.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use futures::StreamExt;
+    /// use hueclient::{Occupancy, OccupancyTracker};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let mut updates = Box::pin(OccupancyTracker::new(bridge).updates().await.unwrap());
+    /// while let Some(update) = updates.next().await {
+    ///     if update.occupancy == Occupancy::Vacant {
+    ///         println!("{} is empty", update.room_name);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn updates(&self) -> crate::Result<impl Stream<Item = OccupancyUpdate>> {
+        let now = Instant::now();
+        let devices = self.bridge.index_all_devices().await?;
+        let mut rooms = Rooms::new();
+        let mut room_of = HashMap::new();
+        for room in self.bridge.get_all_rooms().await? {
+            for service in room
+                .children
+                .iter()
+                .filter_map(|child| devices.get(&child.rid))
+                .flat_map(|device| &device.services)
+            {
+                room_of.insert(service.rid.clone(), room.id.clone());
+            }
+            rooms.insert(room.id.clone(), RoomState::new(room.metadata.name, now));
+        }
+        for group in self.bridge.get_all_grouped_lights().await? {
+            if let Some(room) = rooms.get_mut(&group.owner.rid) {
+                room_of.insert(group.id.clone(), group.owner.rid.clone());
+                room.lights_on = group.on.as_ref().map(|on| on.on);
+                if room.lights_on == Some(true) {
+                    room.activity(now);
+                }
+            }
+        }
+        let tracking = Tracking {
+            config: self.clone(),
+            events: Box::pin(self.bridge.events()?),
+            rooms,
+            room_of,
+            pending: VecDeque::new(),
+        };
+        Ok(futures::stream::unfold(
+            tracking,
+            |mut tracking| async move {
+                loop {
+                    if let Some(update) = tracking.pending.pop_front() {
+                        return Some((update, tracking));
+                    }
+                    let deadline = tracking.next_deadline();
+                    tokio::select! {
+                        event = tracking.events.next() => match event? {
+                            HueEvent::Update(data) => {
+                                let now = Instant::now();
+                                for data in &data {
+                                    tracking.apply(data, now);
+                                }
+                            }
+                            HueEvent::Add(_) | HueEvent::Delete(_) => (),
+                            HueEvent::Error(e) => log::warn!("event stream error: {e}"),
+                        },
+                        _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)),
+                            if deadline.is_some() => tracking.tick(Instant::now()),
+                    }
+                }
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occupied_cooling_down_vacant() {
+        let config = OccupancyTracker::new(crate::Bridge::for_ip([127u8, 0, 0, 1]).with_user("k"))
+            .with_cooling_down_after(Duration::from_secs(60))
+            .with_vacant_after(Duration::from_secs(60));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut room = RoomState::new("Office".into(), start);
+        assert!(room.on_motion(true, at(0)));
+        assert!(
+            !room.tick(at(500), &config),
+            "no timeout while motion is reported"
+        );
+        assert!(!room.on_motion(false, at(500)));
+        assert!(room.tick(at(560), &config));
+        assert_eq!(room.occupancy, Occupancy::CoolingDown);
+        assert!(room.on_motion(true, at(570)));
+        assert_eq!(room.occupancy, Occupancy::Occupied);
+        room.on_motion(false, at(580));
+        assert!(room.tick(at(1000), &config));
+        assert_eq!(room.occupancy, Occupancy::Vacant);
+    }
+}
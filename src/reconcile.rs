@@ -0,0 +1,376 @@
+//! A reconciliation loop continuously converging lights and groups toward a declared state.
+use crate::{Bridge, CommandLight, GroupedLight, Light, LightTarget, TaskHandle};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Converges the actual state of lights and groups toward a desired state, Kubernetes-style.
+///
+/// Every `interval`, the reconciler fetches the current state of the bridge and re-sends the
+/// desired command to every target that drifted away from it. When a target that had converged
+/// drifts, it is assumed someone changed it on purpose (from a switch or the app) and it is left
+/// alone for `override_hold` before being reverted. Desired values out of the range of a light
+/// are compared as the bridge clamps them, and a target still not converging after
+/// [`Reconciler::MAX_ATTEMPTS`] commands is left alone until its desired state changes.
+///
+/// Clones share the same desired state, so it can be changed while the reconciler is running.
+#[derive(Debug, Clone)]
+pub struct Reconciler {
+    bridge: Bridge,
+    desired: Arc<Mutex<HashMap<LightTarget, CommandLight>>>,
+    interval: Duration,
+    override_hold: Duration,
+    command_interval: Duration,
+}
+
+#[derive(Debug, Default)]
+struct TargetStatus {
+    converged: bool,
+    hold_until: Option<Instant>,
+    /// The commands sent since the target last converged.
+    attempts: u32,
+    /// The desired command the attempts were made for, serialized since commands are not
+    /// comparable.
+    command: Option<serde_json::Value>,
+}
+
+impl TargetStatus {
+    /// Counts an attempt at sending `command`, or returns false if it was already sent too many
+    /// times without converging. A new command starts over.
+    fn attempt(&mut self, command: &CommandLight) -> bool {
+        let command = serde_json::to_value(command).ok();
+        if self.command != command {
+            self.command = command;
+            self.attempts = 0;
+        }
+        if self.attempts >= Reconciler::MAX_ATTEMPTS {
+            return false;
+        }
+        self.attempts += 1;
+        true
+    }
+}
+
+impl Reconciler {
+    /// How many times a command is sent to a target that does not converge before giving up.
+    pub const MAX_ATTEMPTS: u32 = 5;
+
+    pub fn new(bridge: Bridge) -> Reconciler {
+        Reconciler {
+            bridge,
+            desired: Default::default(),
+            interval: Duration::from_secs(10),
+            override_hold: Duration::from_secs(30 * 60),
+            // the bridge handles about 10 light commands per second
+            command_interval: Duration::from_millis(100),
+        }
+    }
+
+    /// Sets how often the bridge state is checked.
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    /// Sets how long a manual change is respected before being reverted. A zero duration
+    /// reverts manual changes on the next check.
+    pub fn with_override_hold(self, override_hold: Duration) -> Self {
+        Self {
+            override_hold,
+            ..self
+        }
+    }
+
+    /// Sets the minimum delay between two commands sent to the bridge.
+    pub fn with_command_interval(self, command_interval: Duration) -> Self {
+        Self {
+            command_interval,
+            ..self
+        }
+    }
+
+    /// Declares the state `target` should be kept in.
+    pub fn set_desired(&self, target: LightTarget, command: CommandLight) {
+        self.desired.lock().unwrap().insert(target, command);
+    }
+
+    /// Stops managing `target`, leaving it in its current state.
+    pub fn remove_desired(&self, target: &LightTarget) -> Option<CommandLight> {
+        self.desired.lock().unwrap().remove(target)
+    }
+
+    /// Starts the reconciliation loop in the background.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{CommandLight, LightTarget, Reconciler};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let reconciler = Reconciler::new(bridge);
+    /// reconciler.set_desired(
+    ///     LightTarget::Light("a2ad7a3b-3a4b-4c5d-8e9f-0a1b2c3d4e5f".into()),
+    ///     CommandLight::default().on().with_brightness(80.0),
+    /// );
+    /// let handle = reconciler.start();
+    /// # handle.stop().await;
+    /// # })
+    /// ```
+    pub fn start(&self) -> TaskHandle {
+        let reconciler = self.clone();
+        TaskHandle::spawn(move |stopped| async move {
+            let mut statuses: HashMap<LightTarget, TargetStatus> = HashMap::new();
+            loop {
+                if let Err(e) = reconciler.reconcile(&mut statuses).await {
+                    log::warn!("reconciliation failed: {e}");
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(reconciler.interval) => (),
+                    _ = stopped.cancelled() => break,
+                }
+            }
+        })
+    }
+
+    async fn reconcile(
+        &self,
+        statuses: &mut HashMap<LightTarget, TargetStatus>,
+    ) -> crate::Result<()> {
+        let desired = self.desired.lock().unwrap().clone();
+        statuses.retain(|target, _| desired.contains_key(target));
+
+        // grouped lights do not report colors, the colors of their lights are compared instead
+        let group_colors = desired.iter().any(|(target, command)| {
+            matches!(target, LightTarget::Group(_))
+                && (command.color.is_some() || command.color_temperature.is_some())
+        });
+
+        let mut lights = HashMap::new();
+        if group_colors || desired.keys().any(|t| matches!(t, LightTarget::Light(_))) {
+            lights = self.bridge.index_all_lights().await?;
+        }
+        let mut groups = HashMap::new();
+        if desired.keys().any(|t| matches!(t, LightTarget::Group(_))) {
+            for group in self.bridge.get_all_grouped_lights().await? {
+                groups.insert(group.id.clone(), group);
+            }
+        }
+
+        let mut members: HashMap<String, Vec<Light>> = HashMap::new();
+        if group_colors {
+            let devices = self.bridge.index_all_devices().await?;
+            let rooms = self.bridge.get_all_rooms().await?;
+            let zones = self.bridge.get_all_zones().await?;
+            let groups = rooms
+                .into_iter()
+                .map(|room| room.resolve(&devices, &lights))
+                .map(|room| (room.services, room.children))
+                .chain(
+                    zones
+                        .into_iter()
+                        .map(|zone| zone.resolve(&lights))
+                        .map(|zone| (zone.services, zone.children)),
+                );
+            for (services, lights) in groups {
+                if let Some(service) = services.iter().find(|s| s.rtype == "grouped_light") {
+                    members.insert(service.rid.clone(), lights);
+                }
+            }
+        }
+
+        let now = Instant::now();
+        for (target, command) in &desired {
+            let matches = match target {
+                LightTarget::Light(id) => lights.get(id).map(|l| light_matches(command, l)),
+                LightTarget::Group(id) => groups.get(id).map(|g| {
+                    let lights = members.get(id).map(Vec::as_slice).unwrap_or_default();
+                    group_matches(command, g, lights)
+                }),
+            };
+            let Some(matches) = matches else {
+                log::debug!("reconciler target {target:?} not found on the bridge");
+                continue;
+            };
+            let status = statuses.entry(target.clone()).or_default();
+            if matches {
+                status.converged = true;
+                status.hold_until = None;
+                status.attempts = 0;
+                continue;
+            }
+            if status.converged {
+                log::info!(
+                    "{target:?} was changed manually, holding for {:?}",
+                    self.override_hold
+                );
+                status.converged = false;
+                status.hold_until = Some(now + self.override_hold);
+            }
+            if status.hold_until.is_some_and(|until| until > now) {
+                continue;
+            }
+            if !status.attempt(command) {
+                continue;
+            }
+            log::debug!("converging {target:?}");
+            if let Err(e) = self.bridge.set_target_state(target, command).await {
+                log::warn!("could not converge {target:?}: {e}");
+            }
+            if status.attempts == Self::MAX_ATTEMPTS {
+                log::warn!(
+                    "{target:?} did not converge after {} commands, leaving it alone until its \
+                     desired state changes",
+                    Self::MAX_ATTEMPTS
+                );
+            }
+            tokio::time::sleep(self.command_interval).await;
+        }
+        Ok(())
+    }
+}
+
+fn brightness_matches(desired: f32, actual: f32) -> bool {
+    (desired - actual).abs() <= 1.0
+}
+
+fn light_matches(command: &CommandLight, light: &Light) -> bool {
+    if let Some(on) = &command.on {
+        if on.on != light.on.on {
+            return false;
+        }
+        if !on.on {
+            return true;
+        }
+    }
+    // the bridge brings values out of the range of the light within it, so the clamped values
+    // are the ones the light ends up in
+    if let Some(dimming) = &command.dimming {
+        match &light.dimming {
+            Some(actual) => {
+                let desired = dimming.brightness.max(actual.min_dim_level.unwrap_or(0.));
+                if !brightness_matches(desired, actual.brightness) {
+                    return false;
+                }
+            }
+            None => return false,
+        }
+    }
+    if let Some(ct) = &command.color_temperature {
+        match &light.color_temperature {
+            Some(actual)
+                if actual.mirek_valid
+                    && actual.mirek == Some(actual.mirek_schema.clamp(ct.mirek)) => {}
+            _ => return false,
+        }
+    }
+    if let Some(color) = &command.color {
+        match &light.color {
+            Some(actual) => {
+                let desired = match actual.gamut() {
+                    Some(gamut) => gamut.clamp(&color.xy),
+                    None => color.xy.clone(),
+                };
+                if (actual.xy.x - desired.x).abs() > 0.01 || (actual.xy.y - desired.y).abs() > 0.01
+                {
+                    return false;
+                }
+            }
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Whether `group` is in the state of `command`, the colors being compared on the `members` of
+/// the group that support them.
+fn group_matches(command: &CommandLight, group: &GroupedLight, members: &[Light]) -> bool {
+    if let Some(on) = &command.on {
+        if group.on.as_ref().map(|o| o.on) != Some(on.on) {
+            return false;
+        }
+        if !on.on {
+            return true;
+        }
+    }
+    if let Some(dimming) = &command.dimming {
+        match &group.dimming {
+            Some(actual) if brightness_matches(dimming.brightness, actual.brightness) => (),
+            _ => return false,
+        }
+    }
+    members.iter().all(|light| {
+        let colors = CommandLight {
+            color_temperature: command
+                .color_temperature
+                .clone()
+                .filter(|_| light.color_temperature.is_some()),
+            color: command.color.clone().filter(|_| light.color.is_some()),
+            ..Default::default()
+        };
+        light_matches(&colors, light)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn light(mirek: u16) -> Light {
+        crate::bridge::test_light(json!({
+            "color_temperature": {
+                "mirek": mirek,
+                "mirek_valid": true,
+                "mirek_schema": { "mirek_minimum": 153, "mirek_maximum": 454 }
+            }
+        }))
+    }
+
+    #[test]
+    fn group_color_temperatures_are_compared_on_members() {
+        let group: GroupedLight = serde_json::from_value(json!({
+            "id": "g1",
+            "owner": { "rid": "r1", "rtype": "room" },
+            "on": { "on": true },
+            "dimming": { "brightness": 80.0 }
+        }))
+        .unwrap();
+        let command = CommandLight::default()
+            .on()
+            .with_brightness(80.)
+            .with_mirek(370);
+        assert!(group_matches(&command, &group, &[light(370), light(370)]));
+        assert!(!group_matches(&command, &group, &[light(370), light(250)]));
+        let command = CommandLight::default().on().with_xy(0.6, 0.3);
+        assert!(group_matches(&command, &group, &[light(250)]));
+    }
+
+    #[test]
+    fn values_out_of_range_are_compared_clamped() {
+        let light = light(454);
+        assert!(light_matches(
+            &CommandLight::default().with_mirek(500),
+            &light
+        ));
+        assert!(!light_matches(
+            &CommandLight::default().with_mirek(400),
+            &light
+        ));
+        let light = crate::bridge::test_light(json!({
+            "dimming": { "brightness": 2.0, "min_dim_level": 2.0 }
+        }));
+        assert!(light_matches(
+            &CommandLight::default().with_brightness(0.5),
+            &light
+        ));
+    }
+
+    #[test]
+    fn attempts_stop_until_the_command_changes() {
+        let mut status = TargetStatus::default();
+        let command = CommandLight::default().on();
+        for _ in 0..Reconciler::MAX_ATTEMPTS {
+            assert!(status.attempt(&command));
+        }
+        assert!(!status.attempt(&command));
+        assert!(status.attempt(&CommandLight::default().off()));
+    }
+}
@@ -0,0 +1,269 @@
+//! Fetching any type of resource of the CLIP v2 API.
+use crate::{Bridge, BridgeResponseV2};
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// A resource of the CLIP v2 API, listed at `/clip/v2/resource/{RTYPE}`. Implementing it for a
+/// type of your own makes it available to [`Bridge::get_resources`] and [`Bridge::get_resource`],
+/// for resource types this library does not know about yet.
+/// ### Example
+/// ```no_run
+/// # tokio_test::block_on(async {
+/// use hueclient::HueResource;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Temperature {
+///     id: String,
+///     enabled: bool,
+/// }
+///
+/// impl HueResource for Temperature {
+///     const RTYPE: &'static str = "temperature";
+///
+///     fn id(&self) -> &str {
+///         &self.id
+///     }
+/// }
+///
+/// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+/// for sensor in bridge.get_resources::<Temperature>().await.unwrap() {
+///     println!("{} enabled: {}", sensor.id, sensor.enabled);
+/// }
+/// # })
+/// ```
+pub trait HueResource: DeserializeOwned {
+    /// The type of the resource in the API, such as `light` or `room`.
+    const RTYPE: &'static str;
+
+    fn id(&self) -> &str;
+}
+
+/// Splits a resource tagged with its `type`, as sent by the bridge, into its type and its payload.
+pub(crate) fn split_tagged<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<(String, Value), D::Error> {
+    let raw = Value::deserialize(deserializer)?;
+    match raw.get("type") {
+        Some(Value::String(rtype)) => Ok((rtype.clone(), raw)),
+        _ => Err(de::Error::missing_field("type")),
+    }
+}
+
+pub(crate) fn parse_tagged<T: DeserializeOwned, E: de::Error>(raw: Value) -> Result<T, E> {
+    serde_json::from_value(raw).map_err(E::custom)
+}
+
+// implements HueResource for each type, and lists them in Resource
+macro_rules! resources {
+    ($($variant:ident => $rtype:literal),* $(,)?) => {
+        $(
+            impl HueResource for crate::$variant {
+                const RTYPE: &'static str = $rtype;
+
+                fn id(&self) -> &str {
+                    &self.id
+                }
+            }
+        )*
+
+        /// Any resource of the bridge, tagged by its type like [`crate::EventData`].
+        // lights dominate every home, boxing them would cost an allocation each for nothing
+        #[allow(clippy::large_enum_variant)]
+        #[derive(Debug, Clone, Serialize)]
+        #[serde(tag = "type")]
+        pub enum Resource {
+            $(
+                #[serde(rename = $rtype)]
+                $variant(crate::$variant),
+            )*
+            /// A resource type this crate does not model yet, with its payload as sent by the
+            /// bridge.
+            #[serde(untagged)]
+            Unknown {
+                #[serde(skip_serializing)]
+                rtype: String,
+                #[serde(flatten)]
+                raw: Value,
+            },
+        }
+
+        impl<'de> Deserialize<'de> for Resource {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let (rtype, raw) = split_tagged(deserializer)?;
+                Ok(match rtype.as_str() {
+                    $($rtype => Resource::$variant(parse_tagged(raw)?),)*
+                    _ => Resource::Unknown { rtype, raw },
+                })
+            }
+        }
+
+        impl Resource {
+            /// Returns the id of the resource, if known.
+            pub fn id(&self) -> Option<&str> {
+                match self {
+                    $(Resource::$variant(r) => Some(r.id()),)*
+                    Resource::Unknown { raw, .. } => raw.get("id").and_then(Value::as_str),
+                }
+            }
+        }
+    };
+}
+
+resources! {
+    BehaviorInstance => "behavior_instance",
+    BehaviorScript => "behavior_script",
+    CameraMotion => "camera_motion",
+    Contact => "contact",
+    Device => "device",
+    DevicePower => "device_power",
+    DeviceSoftwareUpdate => "device_software_update",
+    Entertainment => "entertainment",
+    EntertainmentConfiguration => "entertainment_configuration",
+    GeofenceClient => "geofence_client",
+    GroupedLight => "grouped_light",
+    GroupedLightLevel => "grouped_light_level",
+    GroupedMotion => "grouped_motion",
+    Homekit => "homekit",
+    Light => "light",
+    LightLevel => "light_level",
+    MatterFabric => "matter_fabric",
+    Motion => "motion",
+    Room => "room",
+    Scene => "scene",
+    SmartScene => "smart_scene",
+    Tamper => "tamper",
+    Temperature => "temperature",
+    ZigbeeConnectivity => "zigbee_connectivity",
+    Zone => "zone",
+}
+
+/// Parses the resources of a snapshot one by one, so that a resource the bridge sends in an
+/// unexpected shape does not hide all the others.
+fn parse_resources(values: Vec<Value>) -> Vec<Resource> {
+    values
+        .into_iter()
+        .filter_map(|value| match Resource::deserialize(&value) {
+            Ok(resource) => Some(resource),
+            Err(e) => {
+                log::warn!("skipping a resource that could not be parsed: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+impl Bridge {
+    /// Returns every resource of this `Bridge` in a single request, as a snapshot of the whole
+    /// home. Resource types this crate does not model are returned as [`Resource::Unknown`], and
+    /// resources that cannot be parsed are left out.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::Resource;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for resource in bridge.get_all_resources().await.unwrap() {
+    ///     if let Resource::Light(light) = resource {
+    ///         println!("{} is on: {}", light.metadata.name, light.on.on);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_resources(&self) -> crate::Result<Vec<Resource>> {
+        let url = format!("https://{}/clip/v2/resource", self.host());
+        let resp: BridgeResponseV2<Value> = self.send(self.client.get(&url)).await?;
+        Ok(parse_resources(resp.get()?))
+    }
+
+    /// Returns all the resources of type `T` that are registered at this `Bridge`, sorted by
+    /// their id's.
+    pub async fn get_resources<T: HueResource>(&self) -> crate::Result<Vec<T>> {
+        let url = format!("https://{}/clip/v2/resource/{}", self.host(), T::RTYPE);
+        let resp: BridgeResponseV2<T> = self.send(self.client.get(&url)).await?;
+        let mut resources = resp.get()?;
+        resources.sort_by(|a, b| a.id().cmp(b.id()));
+        Ok(resources)
+    }
+
+    /// Returns the resource of type `T` with the given id, without downloading all the others.
+    pub async fn get_resource<T: HueResource>(&self, id: &str) -> crate::Result<T> {
+        let url = format!(
+            "https://{}/clip/v2/resource/{}/{}",
+            self.host(),
+            T::RTYPE,
+            id
+        );
+        let resp: BridgeResponseV2<T> = self.send(self.client.get(&url)).await?;
+        resp.get()?
+            .pop()
+            .ok_or_else(|| crate::HueError::protocol_err(format!("unknown {} {}", T::RTYPE, id)))
+    }
+
+    /// Deletes any resource, given its type such as `light` or `entertainment_configuration`,
+    /// including the types this library does not model yet. Errors of the bridge, such as an
+    /// unknown id, are returned as [`crate::HueError::BridgeErrorV2`].
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// bridge
+    ///     .delete_resource("homekit", "8c5c0b5e-6b5a-4a3e-9d3e-0b1c2d3e4f5a")
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub async fn delete_resource(&self, rtype: &str, id: &str) -> crate::Result<()> {
+        let url = format!("https://{}/clip/v2/resource/{}/{}", self.host(), rtype, id);
+        let resp: BridgeResponseV2<serde_json::Value> = self.send(self.client.delete(&url)).await?;
+        resp.get()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resources_by_type() {
+        let resources: Vec<Resource> = serde_json::from_value(json!([
+            {
+                "type": "room",
+                "id": "r1",
+                "metadata": { "name": "Office", "archetype": "office" },
+                "children": [],
+                "services": []
+            },
+            { "type": "homekit", "id": "h1", "status": "unpaired" },
+            { "type": "bell_button", "id": "b1", "sound": "ding" }
+        ]))
+        .unwrap();
+        assert!(matches!(&resources[0], Resource::Room(room) if room.metadata.name == "Office"));
+        assert!(matches!(&resources[1], Resource::Homekit(homekit) if homekit.id == "h1"));
+        assert!(matches!(&resources[2], Resource::Unknown { rtype, .. } if rtype == "bell_button"));
+        assert_eq!(resources[2].id(), Some("b1"));
+        assert_eq!(
+            serde_json::to_value(&resources[2]).unwrap(),
+            json!({ "type": "bell_button", "id": "b1", "sound": "ding" })
+        );
+        assert_eq!(
+            serde_json::to_value(&resources[1]).unwrap(),
+            json!({ "type": "homekit", "id": "h1", "id_v1": null, "status": "unpaired" })
+        );
+    }
+
+    #[test]
+    fn malformed_resources_are_skipped() {
+        let resources = parse_resources(vec![
+            json!({ "type": "room", "id": "r1", "metadata": "not a room" }),
+            json!({ "type": "homekit", "id": "h1", "status": "paired" }),
+        ]);
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].id(), Some("h1"));
+    }
+}
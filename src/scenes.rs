@@ -0,0 +1,242 @@
+//! Tracking which scene is active in each room and zone.
+//!
+//! The bridge reports a scene as active until another one is recalled, even when the lights were
+//! changed by hand in the meantime. This tracks a scene as active only as long as the lights of
+//! its group still match it.
+use crate::{Bridge, CommandLight, EventData, EventLight, HueEvent, Resource, Scene};
+use std::collections::HashMap;
+
+const BRIGHTNESS_TOLERANCE: f32 = 2.;
+const XY_TOLERANCE: f32 = 0.01;
+
+/// The scenes, the lights of each room and zone, and the scene logically active in each of them.
+#[derive(Debug, Clone, Default)]
+pub struct ActiveScenes {
+    scenes: HashMap<String, Scene>,
+    // light id to the rooms and zones it belongs to
+    groups_of_light: HashMap<String, Vec<String>>,
+    // room or zone id to scene id
+    active: HashMap<String, String>,
+}
+
+/// Whether a light change contradicts the action of a scene on that light.
+fn contradicts(action: &CommandLight, change: &EventLight) -> bool {
+    if let (Some(expected), Some(observed)) = (&action.on, &change.on) {
+        if expected.on != observed.on {
+            return true;
+        }
+    }
+    if let (Some(expected), Some(observed)) = (&action.dimming, &change.dimming) {
+        if (expected.brightness - observed.brightness).abs() > BRIGHTNESS_TOLERANCE {
+            return true;
+        }
+    }
+    if let (Some(expected), Some(observed)) = (&action.color, &change.color) {
+        if (expected.xy.x - observed.xy.x).abs() > XY_TOLERANCE
+            || (expected.xy.y - observed.xy.y).abs() > XY_TOLERANCE
+        {
+            return true;
+        }
+    }
+    if let (Some(expected), Some(observed)) = (&action.color_temperature, &change.color_temperature)
+    {
+        if observed.mirek_valid && observed.mirek != Some(expected.mirek) {
+            return true;
+        }
+    }
+    false
+}
+
+impl ActiveScenes {
+    /// Fetches the scenes, rooms and zones. Scenes the bridge reports as active are assumed to
+    /// still match the lights.
+    pub async fn fetch(bridge: &Bridge) -> crate::Result<ActiveScenes> {
+        let mut tracker = ActiveScenes::default();
+        let devices = bridge.index_all_devices().await?;
+        for room in bridge.get_all_rooms().await? {
+            for light in room
+                .children
+                .iter()
+                .filter_map(|child| devices.get(&child.rid))
+                .flat_map(|device| device.get_lights())
+            {
+                tracker.add_light(light, &room.id);
+            }
+        }
+        for zone in bridge.get_all_zones().await? {
+            for child in zone.children.iter().filter(|child| child.rtype == "light") {
+                tracker.add_light(&child.rid, &zone.id);
+            }
+        }
+        for (scene, active) in bridge.get_all_scenes_with_status().await? {
+            if active {
+                tracker
+                    .active
+                    .insert(scene.group.rid.clone(), scene.id.clone());
+            }
+            tracker.scenes.insert(scene.id.clone(), scene);
+        }
+        Ok(tracker)
+    }
+
+    fn add_light(&mut self, light: &str, group: &str) {
+        self.groups_of_light
+            .entry(light.to_string())
+            .or_default()
+            .push(group.to_string());
+    }
+
+    /// Returns the scene logically active in a room or a zone.
+    pub fn active_scene(&self, group: &str) -> Option<&Scene> {
+        self.active.get(group).and_then(|id| self.scenes.get(id))
+    }
+
+    /// Updates the active scenes with an event: scene recalls activate a scene, light changes
+    /// that do not match the active scene of their room or zone deactivate it, and created or
+    /// deleted scenes are tracked.
+    pub fn record_event(&mut self, event: &HueEvent) {
+        let data = match event {
+            HueEvent::Update(data) => data,
+            HueEvent::Add(resources) => {
+                for resource in resources {
+                    if let Resource::Scene(scene) = resource {
+                        self.scenes.insert(scene.id.clone(), scene.clone());
+                    }
+                }
+                return;
+            }
+            HueEvent::Delete(identifiers) => {
+                for identifier in identifiers.iter().filter(|i| i.rtype == "scene") {
+                    self.scenes.remove(&identifier.rid);
+                    self.active.retain(|_, scene| *scene != identifier.rid);
+                }
+                return;
+            }
+            HueEvent::Error(_) => return,
+        };
+        for data in data {
+            match data {
+                EventData::Scene(change) => {
+                    let (Some(status), Some(scene)) = (&change.status, self.scenes.get(&change.id))
+                    else {
+                        continue;
+                    };
+                    let group = scene.group.rid.clone();
+                    if status.active != "inactive" {
+                        self.active.insert(group, change.id.clone());
+                    } else if self.active.get(&group) == Some(&change.id) {
+                        self.active.remove(&group);
+                    }
+                }
+                EventData::Light(change) => {
+                    let Some(groups) = self.groups_of_light.get(&change.id) else {
+                        continue;
+                    };
+                    for group in groups {
+                        let action = self
+                            .active_scene(group)
+                            .and_then(|scene| {
+                                scene.actions.iter().find(|a| a.target.rid == change.id)
+                            })
+                            .map(|action| &action.action);
+                        if action.is_some_and(|action| contradicts(action, change)) {
+                            self.active.remove(group);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+impl Bridge {
+    /// Returns the scenes the bridge reports as active, statically or with a dynamic palette.
+    pub async fn get_all_active_scenes(&self) -> crate::Result<Vec<Scene>> {
+        Ok(self
+            .get_all_scenes_with_status()
+            .await?
+            .into_iter()
+            .filter(|(_, active)| *active)
+            .map(|(scene, _)| scene)
+            .collect())
+    }
+
+    /// Returns all the scenes, with whether the bridge reports them as active.
+    async fn get_all_scenes_with_status(&self) -> crate::Result<Vec<(Scene, bool)>> {
+        let url = format!("https://{}/clip/v2/resource/scene", self.host());
+        let resp: crate::BridgeResponseV2<serde_json::Value> =
+            self.send(self.client.get(&url)).await?;
+        let mut scenes = vec![];
+        for raw in resp.get()? {
+            let status = raw.pointer("/status/active").and_then(|s| s.as_str());
+            let active = status.is_some_and(|status| status != "inactive");
+            scenes.push((serde_json::from_value(raw)?, active));
+        }
+        Ok(scenes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(data: serde_json::Value) -> HueEvent {
+        HueEvent::Update(vec![serde_json::from_value(data).unwrap()])
+    }
+
+    #[test]
+    fn manual_change_deactivates_scene() {
+        let scene: Scene = serde_json::from_value(json!({
+            "id": "s1",
+            "metadata": { "name": "Relax" },
+            "group": { "rid": "r1", "rtype": "room" },
+            "actions": [{
+                "target": { "rid": "l1", "rtype": "light" },
+                "action": { "on": { "on": true }, "dimming": { "brightness": 50.0 } }
+            }]
+        }))
+        .unwrap();
+        let mut tracker = ActiveScenes::default();
+        tracker.scenes.insert("s1".into(), scene);
+        tracker.add_light("l1", "r1");
+
+        tracker.record_event(&event(
+            json!({ "type": "scene", "id": "s1", "status": { "active": "static" } }),
+        ));
+        assert_eq!(tracker.active_scene("r1").unwrap().id, "s1");
+        // the bridge applying the scene
+        tracker.record_event(&event(
+            json!({ "type": "light", "id": "l1", "dimming": { "brightness": 50.4 } }),
+        ));
+        assert!(tracker.active_scene("r1").is_some());
+        // someone dimming the light by hand
+        tracker.record_event(&event(
+            json!({ "type": "light", "id": "l1", "dimming": { "brightness": 20.0 } }),
+        ));
+        assert!(tracker.active_scene("r1").is_none());
+    }
+
+    #[test]
+    fn created_scene_is_tracked() {
+        let scene: Scene = serde_json::from_value(json!({
+            "id": "s2",
+            "metadata": { "name": "Read" },
+            "group": { "rid": "z1", "rtype": "zone" },
+            "actions": []
+        }))
+        .unwrap();
+        let mut tracker = ActiveScenes::default();
+        tracker.record_event(&HueEvent::Add(vec![Resource::Scene(scene)]));
+        tracker.record_event(&event(
+            json!({ "type": "scene", "id": "s2", "status": { "active": "static" } }),
+        ));
+        assert_eq!(tracker.active_scene("z1").unwrap().id, "s2");
+        tracker.record_event(&HueEvent::Delete(vec![crate::ResourceIdentifier {
+            rid: "s2".into(),
+            rtype: "scene".into(),
+        }]));
+        assert!(tracker.active_scene("z1").is_none());
+    }
+}
@@ -0,0 +1,273 @@
+//! A security view of the Hue Secure sensors: contact sensors, tamper detection, and motion
+//! detected by sensors and cameras.
+//!
+//! The bridge API does not expose the arming state of the Hue app, so [`SecurityMonitor`] keeps
+//! its own, and flags the events that happen while it is armed.
+use crate::{Bridge, ContactReport, EventData, Motion, MotionReport, MotionSensitivity};
+use crate::{ResourceIdentifier, TamperReport};
+#[cfg(feature = "events")]
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: ResourceIdentifier,
+    pub enabled: bool,
+    pub contact_report: Option<ContactReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tamper {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device that may have been tampered with.
+    pub owner: ResourceIdentifier,
+    #[serde(default)]
+    pub tamper_reports: Vec<TamperReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraMotion {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The camera.
+    pub owner: ResourceIdentifier,
+    pub enabled: bool,
+    pub motion: MotionReport,
+    pub sensitivity: Option<MotionSensitivity>,
+}
+
+/// All the security sensors of a bridge.
+#[derive(Debug, Clone)]
+pub struct SecurityStatus {
+    pub contacts: Vec<Contact>,
+    pub tampers: Vec<Tamper>,
+    pub camera_motions: Vec<CameraMotion>,
+    pub motions: Vec<Motion>,
+}
+
+impl SecurityStatus {
+    /// Whether a contact sensor is open, a device is tampered with, or motion is detected.
+    pub fn is_triggered(&self) -> bool {
+        let open = self.contacts.iter().any(|contact| {
+            contact
+                .contact_report
+                .as_ref()
+                .is_some_and(|report| report.state == "no_contact")
+        });
+        let tampered = self.tampers.iter().any(|tamper| {
+            tamper
+                .tamper_reports
+                .iter()
+                .any(|report| report.state == "tampered")
+        });
+        let camera_motion = self
+            .camera_motions
+            .iter()
+            .any(|camera| camera.motion.motion_valid && camera.motion.motion);
+        let motion = self
+            .motions
+            .iter()
+            .any(|sensor| sensor.motion.motion_valid && sensor.motion.motion);
+        open || tampered || camera_motion || motion
+    }
+}
+
+/// A change on a security sensor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecurityEvent {
+    Contact {
+        id: String,
+        owner: Option<String>,
+        open: bool,
+    },
+    Tamper {
+        id: String,
+        owner: Option<String>,
+        tampered: bool,
+    },
+    Motion {
+        id: String,
+        owner: Option<String>,
+        /// Whether the motion was detected by a camera rather than a motion sensor.
+        camera: bool,
+        motion: bool,
+    },
+}
+
+impl SecurityEvent {
+    /// Returns the security event of an event, if it is about a security sensor.
+    pub fn from_event_data(data: &EventData) -> Option<SecurityEvent> {
+        let owner = |owner: &Option<ResourceIdentifier>| owner.as_ref().map(|o| o.rid.clone());
+        match data {
+            EventData::Contact(contact) => Some(SecurityEvent::Contact {
+                id: contact.id.clone(),
+                owner: owner(&contact.owner),
+                open: contact.contact_report.as_ref()?.state == "no_contact",
+            }),
+            EventData::Tamper(tamper) => Some(SecurityEvent::Tamper {
+                id: tamper.id.clone(),
+                owner: owner(&tamper.owner),
+                tampered: tamper
+                    .tamper_reports
+                    .as_ref()?
+                    .iter()
+                    .any(|report| report.state == "tampered"),
+            }),
+            EventData::Motion(motion) | EventData::CameraMotion(motion) => {
+                let report = motion.motion.as_ref().filter(|r| r.motion_valid)?;
+                Some(SecurityEvent::Motion {
+                    id: motion.id.clone(),
+                    owner: owner(&motion.owner),
+                    camera: matches!(data, EventData::CameraMotion(_)),
+                    motion: report.motion,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this event should raise an alarm while armed: a contact opening, a device being
+    /// tampered with, or motion.
+    pub fn is_trigger(&self) -> bool {
+        match self {
+            SecurityEvent::Contact { open, .. } => *open,
+            SecurityEvent::Tamper { tampered, .. } => *tampered,
+            SecurityEvent::Motion { motion, .. } => *motion,
+        }
+    }
+}
+
+/// A security event, with the arming state at the time it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityUpdate {
+    pub event: SecurityEvent,
+    pub armed: bool,
+    /// Whether the event is a trigger that happened while armed.
+    pub alarm: bool,
+}
+
+/// Tracks an arming state and turns the event stream into [`SecurityUpdate`]s. Clones share
+/// the same arming state.
+#[derive(Debug, Clone)]
+pub struct SecurityMonitor {
+    bridge: Bridge,
+    armed: Arc<AtomicBool>,
+}
+
+impl SecurityMonitor {
+    /// A disarmed monitor.
+    pub fn new(bridge: Bridge) -> Self {
+        SecurityMonitor {
+            bridge,
+            armed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn arm(&self) {
+        self.armed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn disarm(&self) {
+        self.armed.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::SeqCst)
+    }
+
+    /// Reads the current state of all the security sensors.
+    pub async fn status(&self) -> crate::Result<SecurityStatus> {
+        Ok(SecurityStatus {
+            contacts: self.bridge.get_resources().await?,
+            tampers: self.bridge.get_resources().await?,
+            camera_motions: self.bridge.get_resources().await?,
+            motions: self.bridge.get_all_motion_sensors().await?,
+        })
+    }
+
+    #[cfg(feature = "events")]
+    /// Returns a stream of the changes on the security sensors.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use futures::StreamExt;
+    /// use hueclient::SecurityMonitor;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let monitor = SecurityMonitor::new(bridge);
+    /// if monitor.status().await.unwrap().is_triggered() {
+    ///     println!("a door is open, arming anyway");
+    /// }
+    /// monitor.arm();
+    /// let mut updates = Box::pin(monitor.updates().unwrap());
+    /// while let Some(update) = updates.next().await {
+    ///     if update.alarm {
+    ///         println!("alarm: {:?}", update.event);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub fn updates(&self) -> crate::Result<impl Stream<Item = SecurityUpdate>> {
+        let armed = self.armed.clone();
+        Ok(self.bridge.events()?.flat_map(move |event| {
+            let armed = armed.load(Ordering::SeqCst);
+            let updates: Vec<SecurityUpdate> = match event {
+                crate::HueEvent::Update(data) => data
+                    .iter()
+                    .filter_map(SecurityEvent::from_event_data)
+                    .map(|event| SecurityUpdate {
+                        alarm: armed && event.is_trigger(),
+                        event,
+                        armed,
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+            futures::stream::iter(updates)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn security_events() {
+        let parse = |data| SecurityEvent::from_event_data(&serde_json::from_value(data).unwrap());
+        let contact = json!({
+            "type": "contact",
+            "id": "c1",
+            "owner": { "rid": "d1", "rtype": "device" },
+            "contact_report": { "changed": "2024-01-01T00:00:00.000Z", "state": "no_contact" }
+        });
+        let expected = SecurityEvent::Contact {
+            id: "c1".into(),
+            owner: Some("d1".into()),
+            open: true,
+        };
+        assert_eq!(parse(contact), Some(expected.clone()));
+        assert!(expected.is_trigger());
+        let camera = json!({
+            "type": "camera_motion",
+            "id": "m1",
+            "motion": { "motion": false, "motion_valid": true }
+        });
+        assert!(matches!(
+            parse(camera),
+            Some(SecurityEvent::Motion {
+                camera: true,
+                motion: false,
+                ..
+            })
+        ));
+        assert_eq!(parse(json!({ "type": "light", "id": "l1" })), None);
+    }
+}
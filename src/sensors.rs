@@ -0,0 +1,301 @@
+//! Motion, light level and temperature sensors, and their configuration.
+use crate::ResourceIdentifier;
+use crate::{Bridge, BridgeResponse, BridgeResponseV2, LightLevelReport, MotionReport};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotionSensitivity {
+    /// `set`, or `changing` while a new sensitivity is being applied to the sensor.
+    pub status: Option<String>,
+    pub sensitivity: u32,
+    /// The highest sensitivity the sensor supports.
+    pub sensitivity_max: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Motion {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: ResourceIdentifier,
+    pub enabled: bool,
+    pub motion: MotionReport,
+    pub sensitivity: Option<MotionSensitivity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightLevel {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: ResourceIdentifier,
+    pub enabled: bool,
+    pub light: LightLevelReport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureReport {
+    /// The temperature in degrees Celsius.
+    pub temperature: f32,
+    pub temperature_valid: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Temperature {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The device of the sensor.
+    pub owner: ResourceIdentifier,
+    pub enabled: bool,
+    pub temperature: TemperatureReport,
+}
+
+/// The last motion report of a [`GroupedMotion`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotionSample {
+    /// When the report changed, as an RFC 3339 timestamp.
+    pub changed: String,
+    pub motion: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedMotionState {
+    /// Missing until one of the sensors of the group has reported.
+    pub motion_report: Option<MotionSample>,
+}
+
+/// The motion of all the sensors of a room or a zone, combined by the bridge: there is motion
+/// as soon as one of them detects some.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedMotion {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The room or zone of the sensors.
+    pub owner: ResourceIdentifier,
+    pub enabled: bool,
+    pub motion: GroupedMotionState,
+}
+
+impl GroupedMotion {
+    /// Whether there is motion in the group, if any sensor has reported yet.
+    pub fn motion(&self) -> Option<bool> {
+        self.motion.motion_report.as_ref().map(|r| r.motion)
+    }
+}
+
+/// The last light level report of a [`GroupedLightLevel`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightLevelSample {
+    /// When the report changed, as an RFC 3339 timestamp.
+    pub changed: String,
+    /// The light level, as 10000 * log10(lux) + 1.
+    pub light_level: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedLightLevelState {
+    /// Missing until one of the sensors of the group has reported.
+    pub light_level_report: Option<LightLevelSample>,
+}
+
+/// The light level of all the sensors of a room or a zone, combined by the bridge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedLightLevel {
+    pub id: String,
+    pub id_v1: Option<String>,
+    /// The room or zone of the sensors.
+    pub owner: ResourceIdentifier,
+    pub enabled: bool,
+    pub light: GroupedLightLevelState,
+}
+
+impl GroupedLightLevel {
+    /// The light level of the group, as 10000 * log10(lux) + 1, if any sensor has reported yet.
+    pub fn light_level(&self) -> Option<u32> {
+        self.light
+            .light_level_report
+            .as_ref()
+            .map(|r| r.light_level)
+    }
+}
+
+/// The thresholds of a light level sensor, only available through the v1 API. Light levels
+/// are expressed as 10000 * log10(lux) + 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightLevelThresholds {
+    /// Below this level, the sensor reports that it is dark.
+    #[serde(rename = "tholddark")]
+    pub dark: u32,
+    /// Above `dark` plus this offset, the sensor reports that it is daylight.
+    #[serde(rename = "tholdoffset")]
+    pub offset: u32,
+}
+
+impl LightLevel {
+    fn v1_path(&self) -> crate::Result<&str> {
+        self.id_v1.as_deref().ok_or_else(|| {
+            crate::HueError::protocol_err(format!("light level {} has no v1 id", self.id))
+        })
+    }
+}
+
+impl Bridge {
+    async fn set_sensor_enabled(&self, rtype: &str, id: &str, enabled: bool) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct PutSensor {
+            enabled: bool,
+        }
+        let url = format!("https://{}/clip/v2/resource/{}/{}", self.host(), rtype, id);
+        let resp: BridgeResponseV2<Value> = self
+            .send(self.client.put(&url).json(&PutSensor { enabled }))
+            .await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Returns a vector of all motion sensors that are registered at this `Bridge`, sorted by
+    /// their id's.
+    pub async fn get_all_motion_sensors(&self) -> crate::Result<Vec<Motion>> {
+        self.get_resources().await
+    }
+
+    /// Changes the sensitivity of a motion sensor, between 0 and its
+    /// [`MotionSensitivity::sensitivity_max`].
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for sensor in bridge.get_all_motion_sensors().await.unwrap() {
+    ///     if let Some(max) = sensor.sensitivity.and_then(|s| s.sensitivity_max) {
+    ///         bridge.set_motion_sensitivity(&sensor.id, max).await.unwrap();
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn set_motion_sensitivity(&self, id: &str, sensitivity: u32) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct Sensitivity {
+            sensitivity: u32,
+        }
+        #[derive(Serialize)]
+        struct PutMotion {
+            sensitivity: Sensitivity,
+        }
+        let url = format!("https://{}/clip/v2/resource/motion/{}", self.host(), id);
+        let command = PutMotion {
+            sensitivity: Sensitivity { sensitivity },
+        };
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(&command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Returns the motion of every room and zone that has motion sensors, sorted by their id's.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for grouped in bridge.get_all_grouped_motions().await.unwrap() {
+    ///     println!("{} motion: {:?}", grouped.owner.rid, grouped.motion());
+    /// }
+    /// # })
+    /// ```
+    pub async fn get_all_grouped_motions(&self) -> crate::Result<Vec<GroupedMotion>> {
+        self.get_resources().await
+    }
+
+    /// Returns the light level of every room and zone that has light level sensors, sorted by
+    /// their id's.
+    pub async fn get_all_grouped_light_levels(&self) -> crate::Result<Vec<GroupedLightLevel>> {
+        self.get_resources().await
+    }
+
+    /// Returns a vector of all light level sensors that are registered at this `Bridge`, sorted
+    /// by their id's.
+    pub async fn get_all_light_level_sensors(&self) -> crate::Result<Vec<LightLevel>> {
+        self.get_resources().await
+    }
+
+    /// Enables or disables a light level sensor. A disabled sensor does not report light levels.
+    pub async fn set_light_level_enabled(&self, id: &str, enabled: bool) -> crate::Result<()> {
+        self.set_sensor_enabled("light_level", id, enabled).await
+    }
+
+    /// Reads the dark and daylight thresholds of a light level sensor.
+    pub async fn get_light_level_thresholds(
+        &self,
+        sensor: &LightLevel,
+    ) -> crate::Result<LightLevelThresholds> {
+        #[derive(Deserialize)]
+        struct Sensor {
+            config: LightLevelThresholds,
+        }
+        let url = format!(
+            "https://{}/api/{}{}",
+            self.host(),
+            self.application_key,
+            sensor.v1_path()?
+        );
+        let resp: BridgeResponse<Sensor> = self.send(self.client.get(&url)).await?;
+        Ok(resp.get()?.config)
+    }
+
+    /// Changes the dark and daylight thresholds of a light level sensor, which the automations
+    /// of the bridge use to tell whether it is dark.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for sensor in bridge.get_all_light_level_sensors().await.unwrap() {
+    ///     let mut thresholds = bridge.get_light_level_thresholds(&sensor).await.unwrap();
+    ///     // consider it dark a bit earlier in the evening
+    ///     thresholds.dark += 2000;
+    ///     bridge
+    ///         .set_light_level_thresholds(&sensor, thresholds)
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// # })
+    /// ```
+    pub async fn set_light_level_thresholds(
+        &self,
+        sensor: &LightLevel,
+        thresholds: LightLevelThresholds,
+    ) -> crate::Result<()> {
+        let url = format!(
+            "https://{}/api/{}{}/config",
+            self.host(),
+            self.application_key,
+            sensor.v1_path()?
+        );
+        let resp: BridgeResponse<Value> =
+            self.send(self.client.put(&url).json(&thresholds)).await?;
+        resp.get_all()?;
+        Ok(())
+    }
+
+    /// Returns a vector of all temperature sensors that are registered at this `Bridge`, sorted
+    /// by their id's.
+    pub async fn get_all_temperature_sensors(&self) -> crate::Result<Vec<Temperature>> {
+        self.get_resources().await
+    }
+
+    /// Enables or disables a temperature sensor. Disabling unused sensors saves Zigbee traffic.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for sensor in bridge.get_all_temperature_sensors().await.unwrap() {
+    ///     bridge.set_temperature_enabled(&sensor.id, false).await.unwrap();
+    /// }
+    /// # })
+    /// ```
+    pub async fn set_temperature_enabled(&self, id: &str, enabled: bool) -> crate::Result<()> {
+        self.set_sensor_enabled("temperature", id, enabled).await
+    }
+}
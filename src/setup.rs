@@ -0,0 +1,692 @@
+//! A stable, name-based description of the rooms, zones and scenes of a bridge.
+//!
+//! The [`Setup`] type is meant to be serialized, kept under version control and compared against
+//! the live bridge later on. This crate does not pick the file format: `Setup` implements the
+//! serde traits, so YAML, TOML or JSON only take the matching serde crate on the caller side. Resources are
+//! referenced by name rather than by id, so an export taken from one bridge can be diffed against
+//! another one.
+//!
+//! ```yaml
+//! version: 1
+//! rooms:
+//!   - name: Kitchen
+//!     archetype: kitchen
+//!     children: [Ceiling, Counter strip]
+//! zones:
+//!   - name: Downstairs
+//!     archetype: home
+//!     children: [Ceiling, Counter strip, Sofa lamp]
+//! scenes:
+//!   - name: Relax
+//!     group:
+//!       room: Kitchen
+//!     actions:
+//!       - light: Ceiling
+//!         action:
+//!           on: { on: true }
+//!           dimming: { brightness: 40.0 }
+//! ```
+//!
+//! [`Bridge::apply_setup`] then creates, updates and deletes what is needed for the bridge to
+//! match the file.
+use crate::{
+    Bridge, CommandLight, CommandRoom, CommandScene, CommandZone, NewRoom, NewScene, NewZone,
+    ResourceIdentifier, SceneAction,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The version of the [`Setup`] schema produced by this crate.
+pub const SETUP_VERSION: u32 = 1;
+
+/// The exported rooms, zones and scenes of a bridge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Setup {
+    /// The schema version, currently always [`SETUP_VERSION`].
+    pub version: u32,
+    #[serde(default)]
+    pub rooms: Vec<SetupGroup>,
+    #[serde(default)]
+    pub zones: Vec<SetupGroup>,
+    #[serde(default)]
+    pub scenes: Vec<SetupScene>,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Setup {
+            version: SETUP_VERSION,
+            rooms: vec![],
+            zones: vec![],
+            scenes: vec![],
+        }
+    }
+}
+
+/// A room or a zone. Children are device names for rooms and light names for zones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetupGroup {
+    pub name: String,
+    pub archetype: String,
+    #[serde(default)]
+    pub children: Vec<String>,
+}
+
+/// The room or zone a scene belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupGroupRef {
+    Room(String),
+    Zone(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupScene {
+    pub name: String,
+    pub group: SetupGroupRef,
+    /// The state of each light of the scene.
+    #[serde(default)]
+    pub actions: Vec<SetupSceneAction>,
+}
+
+/// The state a scene sets a light to, the light being referenced by name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupSceneAction {
+    pub light: String,
+    pub action: CommandLight,
+}
+
+impl PartialEq for SetupSceneAction {
+    // CommandLight is not comparable, two actions are the same if they serialize the same way
+    fn eq(&self, other: &Self) -> bool {
+        self.light == other.light
+            && serde_json::to_value(&self.action).ok() == serde_json::to_value(&other.action).ok()
+    }
+}
+
+/// A single difference between two [`Setup`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetupChange {
+    CreateRoom(SetupGroup),
+    UpdateRoom { from: SetupGroup, to: SetupGroup },
+    DeleteRoom(SetupGroup),
+    CreateZone(SetupGroup),
+    UpdateZone { from: SetupGroup, to: SetupGroup },
+    DeleteZone(SetupGroup),
+    CreateScene(SetupScene),
+    UpdateScene { from: SetupScene, to: SetupScene },
+    DeleteScene(SetupScene),
+}
+
+impl std::fmt::Display for SetupChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetupChange::CreateRoom(room) => {
+                write!(f, "+ room {:?} {:?}", room.name, room.children)
+            }
+            SetupChange::UpdateRoom { from, to } => write!(
+                f,
+                "~ room {:?} {} {:?} -> {} {:?}",
+                to.name, from.archetype, from.children, to.archetype, to.children
+            ),
+            SetupChange::DeleteRoom(room) => write!(f, "- room {:?}", room.name),
+            SetupChange::CreateZone(zone) => {
+                write!(f, "+ zone {:?} {:?}", zone.name, zone.children)
+            }
+            SetupChange::UpdateZone { from, to } => write!(
+                f,
+                "~ zone {:?} {} {:?} -> {} {:?}",
+                to.name, from.archetype, from.children, to.archetype, to.children
+            ),
+            SetupChange::DeleteZone(zone) => write!(f, "- zone {:?}", zone.name),
+            SetupChange::CreateScene(scene) => {
+                write!(f, "+ scene {:?} in {:?}", scene.name, scene.group)
+            }
+            SetupChange::UpdateScene { from, to } => {
+                let changed: Vec<&str> = to
+                    .actions
+                    .iter()
+                    .filter(|action| !from.actions.contains(action))
+                    .chain(
+                        from.actions
+                            .iter()
+                            .filter(|a| !to.actions.iter().any(|b| b.light == a.light)),
+                    )
+                    .map(|action| action.light.as_str())
+                    .collect();
+                write!(f, "~ scene {:?} in {:?} {:?}", to.name, to.group, changed)
+            }
+            SetupChange::DeleteScene(scene) => {
+                write!(f, "- scene {:?} in {:?}", scene.name, scene.group)
+            }
+        }
+    }
+}
+
+impl Setup {
+    /// Sorts every list by name so that two exports of the same bridge compare and serialize
+    /// identically.
+    pub fn normalize(&mut self) {
+        for group in self.rooms.iter_mut().chain(self.zones.iter_mut()) {
+            group.children.sort();
+        }
+        for scene in &mut self.scenes {
+            scene.actions.sort_by(|a, b| a.light.cmp(&b.light));
+        }
+        self.rooms.sort_by(|a, b| a.name.cmp(&b.name));
+        self.zones.sort_by(|a, b| a.name.cmp(&b.name));
+        self.scenes
+            .sort_by(|a, b| (&a.group, &a.name).cmp(&(&b.group, &b.name)));
+    }
+
+    /// Returns the changes needed to go from `self` to `desired`. Applying the result of
+    /// `a.diff(&a)` is a no-op, which makes importing a setup idempotent.
+    pub fn diff(&self, desired: &Setup) -> Vec<SetupChange> {
+        let mut current = self.clone();
+        current.normalize();
+        let mut desired = desired.clone();
+        desired.normalize();
+
+        let mut changes = vec![];
+        diff_groups(
+            &current.rooms,
+            &desired.rooms,
+            &mut changes,
+            SetupChange::CreateRoom,
+            |from, to| SetupChange::UpdateRoom { from, to },
+            SetupChange::DeleteRoom,
+        );
+        diff_groups(
+            &current.zones,
+            &desired.zones,
+            &mut changes,
+            SetupChange::CreateZone,
+            |from, to| SetupChange::UpdateZone { from, to },
+            SetupChange::DeleteZone,
+        );
+        for scene in &desired.scenes {
+            match current.scenes.iter().find(|s| same_scene(s, scene)) {
+                None => changes.push(SetupChange::CreateScene(scene.clone())),
+                Some(existing) if existing != scene => changes.push(SetupChange::UpdateScene {
+                    from: existing.clone(),
+                    to: scene.clone(),
+                }),
+                Some(_) => (),
+            }
+        }
+        for scene in &current.scenes {
+            if !desired.scenes.iter().any(|s| same_scene(s, scene)) {
+                changes.push(SetupChange::DeleteScene(scene.clone()));
+            }
+        }
+        changes
+    }
+}
+
+fn same_scene(a: &SetupScene, b: &SetupScene) -> bool {
+    let same_group = match (&a.group, &b.group) {
+        (SetupGroupRef::Room(a), SetupGroupRef::Room(b))
+        | (SetupGroupRef::Zone(a), SetupGroupRef::Zone(b)) => crate::names_match(a, b),
+        _ => false,
+    };
+    same_group && crate::names_match(&a.name, &b.name)
+}
+
+/// The id of the resource named `name` among `candidates`, given as `(name, id)` pairs.
+fn id_by_name<'a>(
+    kind: &str,
+    name: &str,
+    mut candidates: impl Iterator<Item = (&'a str, &'a str)>,
+) -> crate::Result<String> {
+    candidates
+        .find(|(candidate, _)| crate::names_match(candidate, name))
+        .map(|(_, id)| id.to_string())
+        .ok_or_else(|| crate::HueError::protocol_err(format!("unknown {kind} {name:?}")))
+}
+
+fn diff_groups(
+    current: &[SetupGroup],
+    desired: &[SetupGroup],
+    changes: &mut Vec<SetupChange>,
+    create: impl Fn(SetupGroup) -> SetupChange,
+    update: impl Fn(SetupGroup, SetupGroup) -> SetupChange,
+    delete: impl Fn(SetupGroup) -> SetupChange,
+) {
+    for group in desired {
+        match current
+            .iter()
+            .find(|g| crate::names_match(&g.name, &group.name))
+        {
+            None => changes.push(create(group.clone())),
+            Some(existing) if existing != group => {
+                changes.push(update(existing.clone(), group.clone()))
+            }
+            Some(_) => (),
+        }
+    }
+    for group in current {
+        if !desired
+            .iter()
+            .any(|g| crate::names_match(&g.name, &group.name))
+        {
+            changes.push(delete(group.clone()));
+        }
+    }
+}
+
+impl Bridge {
+    /// Exports the rooms, zones and scenes of this bridge as a [`Setup`].
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let setup = bridge.export_setup().await.unwrap();
+    /// println!("{}", serde_json::to_string_pretty(&setup).unwrap());
+    /// # })
+    /// ```
+    pub async fn export_setup(&self) -> crate::Result<Setup> {
+        let devices = self.index_all_devices().await?;
+        let lights = self.index_all_lights().await?;
+        let rooms = self.get_all_rooms().await?;
+        let zones = self.get_all_zones().await?;
+        let scenes = self.get_all_scenes().await?;
+
+        let mut groups: HashMap<&str, SetupGroupRef> = HashMap::new();
+        for room in &rooms {
+            groups.insert(&room.id, SetupGroupRef::Room(room.metadata.name.clone()));
+        }
+        for zone in &zones {
+            groups.insert(&zone.id, SetupGroupRef::Zone(zone.metadata.name.clone()));
+        }
+
+        let mut setup = Setup {
+            rooms: rooms
+                .iter()
+                .map(|room| SetupGroup {
+                    name: room.metadata.name.clone(),
+                    archetype: room.metadata.archetype.to_string(),
+                    children: room
+                        .children
+                        .iter()
+                        .filter_map(|child| devices.get(&child.rid))
+                        .map(|device| device.metadata.name.clone())
+                        .collect(),
+                })
+                .collect(),
+            zones: zones
+                .iter()
+                .map(|zone| SetupGroup {
+                    name: zone.metadata.name.clone(),
+                    archetype: zone.metadata.archetype.to_string(),
+                    children: zone
+                        .children
+                        .iter()
+                        .filter_map(|child| lights.get(&child.rid))
+                        .map(|light| light.metadata.name.clone())
+                        .collect(),
+                })
+                .collect(),
+            scenes: scenes
+                .iter()
+                .filter_map(|scene| {
+                    Some(SetupScene {
+                        name: scene.metadata.name.clone(),
+                        group: groups.get(scene.group.rid.as_str())?.clone(),
+                        actions: scene
+                            .actions
+                            .iter()
+                            .filter_map(|action| {
+                                Some(SetupSceneAction {
+                                    light: lights.get(&action.target.rid)?.metadata.name.clone(),
+                                    action: action.action.clone(),
+                                })
+                            })
+                            .collect(),
+                    })
+                })
+                .collect(),
+            ..Default::default()
+        };
+        setup.normalize();
+        Ok(setup)
+    }
+
+    /// Creates, updates and deletes rooms, zones and scenes so that this bridge matches
+    /// `desired`, and returns the changes that were applied. Running it again right after is a
+    /// no-op. Use `export_setup().await?.diff(&desired)` to preview the changes instead.
+    ///
+    /// Devices and lights are looked up by name, and an unknown name fails before anything
+    /// is deleted. Rooms and zones are deleted last, after the scenes. A setup with another
+    /// version than [`SETUP_VERSION`] fails with a `HueError::ProtocolError` without touching
+    /// the bridge.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let desired: hueclient::Setup =
+    ///     serde_json::from_str(&std::fs::read_to_string("setup.json").unwrap()).unwrap();
+    /// for change in bridge.apply_setup(&desired).await.unwrap() {
+    ///     println!("{change}");
+    /// }
+    /// # })
+    /// ```
+    pub async fn apply_setup(&self, desired: &Setup) -> crate::Result<Vec<SetupChange>> {
+        if desired.version != SETUP_VERSION {
+            return Err(crate::HueError::protocol_err(format!(
+                "unsupported setup version {}, expected {SETUP_VERSION}",
+                desired.version
+            )));
+        }
+        let changes = self.export_setup().await?.diff(desired);
+        let devices = self.get_all_devices().await?;
+        let lights = self.get_all_lights().await?;
+        let device_ids = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| {
+                    let candidates = devices
+                        .iter()
+                        .map(|device| (device.metadata.name.as_str(), device.id.as_str()));
+                    id_by_name("device", name, candidates)
+                })
+                .collect::<crate::Result<Vec<_>>>()
+        };
+        let light_ids = |names: &mut dyn Iterator<Item = &String>| {
+            names
+                .map(|name| {
+                    let candidates = lights
+                        .iter()
+                        .map(|light| (light.metadata.name.as_str(), light.id.as_str()));
+                    id_by_name("light", name, candidates)
+                })
+                .collect::<crate::Result<Vec<_>>>()
+        };
+
+        let rooms = self.get_all_rooms().await?;
+        let zones = self.get_all_zones().await?;
+        let room_id = |name: &str| {
+            let candidates = rooms
+                .iter()
+                .map(|r| (r.metadata.name.as_str(), r.id.as_str()));
+            id_by_name("room", name, candidates)
+        };
+        let zone_id = |name: &str| {
+            let candidates = zones
+                .iter()
+                .map(|z| (z.metadata.name.as_str(), z.id.as_str()));
+            id_by_name("zone", name, candidates)
+        };
+        let mut deleted_groups = vec![];
+        for change in &changes {
+            match change {
+                SetupChange::CreateRoom(room) => {
+                    let room = NewRoom::new(&room.name, room.archetype.as_str())
+                        .with_devices(device_ids(&room.children)?);
+                    self.create_room(&room).await?;
+                }
+                SetupChange::UpdateRoom { from, to } => {
+                    let command = CommandRoom::default()
+                        .with_name(&to.name)
+                        .with_archetype(to.archetype.as_str())
+                        .with_devices(device_ids(&to.children)?);
+                    self.update_room(&room_id(&from.name)?, &command).await?;
+                }
+                SetupChange::DeleteRoom(room) => {
+                    deleted_groups.push(("room", room_id(&room.name)?));
+                }
+                SetupChange::CreateZone(zone) => {
+                    let zone = NewZone::new(&zone.name, zone.archetype.as_str())
+                        .with_lights(light_ids(&mut zone.children.iter())?);
+                    self.create_zone(&zone).await?;
+                }
+                SetupChange::UpdateZone { from, to } => {
+                    let command = CommandZone::default()
+                        .with_name(&to.name)
+                        .with_archetype(to.archetype.as_str())
+                        .with_lights(light_ids(&mut to.children.iter())?);
+                    self.update_zone(&zone_id(&from.name)?, &command).await?;
+                }
+                SetupChange::DeleteZone(zone) => {
+                    deleted_groups.push(("zone", zone_id(&zone.name)?));
+                }
+                _ => (),
+            }
+        }
+
+        // scenes may belong to the rooms and zones that were just created
+        let rooms = self.get_all_rooms().await?;
+        let zones = self.get_all_zones().await?;
+        let room_id = |name: &str| {
+            let candidates = rooms
+                .iter()
+                .map(|r| (r.metadata.name.as_str(), r.id.as_str()));
+            id_by_name("room", name, candidates)
+        };
+        let zone_id = |name: &str| {
+            let candidates = zones
+                .iter()
+                .map(|z| (z.metadata.name.as_str(), z.id.as_str()));
+            id_by_name("zone", name, candidates)
+        };
+        let group = |group: &SetupGroupRef| -> crate::Result<ResourceIdentifier> {
+            let (rtype, rid) = match group {
+                SetupGroupRef::Room(name) => ("room", room_id(name)?),
+                SetupGroupRef::Zone(name) => ("zone", zone_id(name)?),
+            };
+            Ok(ResourceIdentifier {
+                rid,
+                rtype: rtype.into(),
+            })
+        };
+        let actions = |scene: &SetupScene| -> crate::Result<Vec<SceneAction>> {
+            let ids = light_ids(&mut scene.actions.iter().map(|action| &action.light))?;
+            Ok(ids
+                .into_iter()
+                .zip(&scene.actions)
+                .map(|(rid, action)| SceneAction {
+                    target: ResourceIdentifier {
+                        rid,
+                        rtype: "light".into(),
+                    },
+                    action: action.action.clone(),
+                })
+                .collect())
+        };
+        let scenes = self.get_all_scenes().await?;
+        let scene_id = |scene: &SetupScene| {
+            let group = group(&scene.group)?;
+            let candidates = scenes
+                .iter()
+                .filter(|s| s.group.rid == group.rid)
+                .map(|s| (s.metadata.name.as_str(), s.id.as_str()));
+            id_by_name("scene", &scene.name, candidates)
+        };
+        for change in &changes {
+            match change {
+                SetupChange::CreateScene(scene) => {
+                    let scene = NewScene::new(&scene.name, group(&scene.group)?, actions(scene)?);
+                    self.create_scene(&scene).await?;
+                }
+                SetupChange::UpdateScene { from, to } => {
+                    let command = CommandScene::default()
+                        .with_name(&to.name)
+                        .with_actions(actions(to)?);
+                    self.update_scene(&scene_id(from)?, &command).await?;
+                }
+                SetupChange::DeleteScene(scene) => {
+                    self.delete_resource("scene", &scene_id(scene)?).await?;
+                }
+                _ => (),
+            }
+        }
+
+        for (rtype, id) in deleted_groups {
+            self.delete_resource(rtype, &id).await?;
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(name: &str, children: &[&str]) -> SetupGroup {
+        SetupGroup {
+            name: name.into(),
+            archetype: "living_room".into(),
+            children: children.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn diff_is_empty_for_reordered_setup() {
+        let a = Setup {
+            rooms: vec![group("Kitchen", &["a", "b"]), group("Office", &[])],
+            ..Default::default()
+        };
+        let b = Setup {
+            rooms: vec![group("Office", &[]), group("Kitchen", &["b", "a"])],
+            ..Default::default()
+        };
+        assert!(a.diff(&b).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_changes() {
+        let current = Setup {
+            rooms: vec![group("Kitchen", &["a"]), group("Attic", &[])],
+            ..Default::default()
+        };
+        let desired = Setup {
+            rooms: vec![group("Kitchen", &["a", "b"]), group("Office", &[])],
+            scenes: vec![SetupScene {
+                name: "Relax".into(),
+                group: SetupGroupRef::Room("Office".into()),
+                actions: vec![],
+            }],
+            ..Default::default()
+        };
+        let changes = current.diff(&desired);
+        assert_eq!(
+            changes,
+            vec![
+                SetupChange::UpdateRoom {
+                    from: group("Kitchen", &["a"]),
+                    to: group("Kitchen", &["a", "b"]),
+                },
+                SetupChange::CreateRoom(group("Office", &[])),
+                SetupChange::DeleteRoom(group("Attic", &[])),
+                SetupChange::CreateScene(SetupScene {
+                    name: "Relax".into(),
+                    group: SetupGroupRef::Room("Office".into()),
+                    actions: vec![],
+                }),
+            ]
+        );
+    }
+
+    fn scene(actions: &[(&str, f32)]) -> SetupScene {
+        SetupScene {
+            name: "Relax".into(),
+            group: SetupGroupRef::Room("Kitchen".into()),
+            actions: actions
+                .iter()
+                .map(|(light, brightness)| SetupSceneAction {
+                    light: light.to_string(),
+                    action: CommandLight::default().on().with_brightness(*brightness),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn diff_compares_scene_actions() {
+        let current = Setup {
+            scenes: vec![scene(&[("Ceiling", 40.), ("Counter strip", 20.)])],
+            ..Default::default()
+        };
+        let reordered = Setup {
+            scenes: vec![scene(&[("Counter strip", 20.), ("Ceiling", 40.)])],
+            ..Default::default()
+        };
+        assert!(current.diff(&reordered).is_empty());
+
+        let desired = Setup {
+            scenes: vec![scene(&[("Ceiling", 60.), ("Counter strip", 20.)])],
+            ..Default::default()
+        };
+        let changes = current.diff(&desired);
+        assert_eq!(
+            changes,
+            vec![SetupChange::UpdateScene {
+                from: scene(&[("Ceiling", 40.), ("Counter strip", 20.)]),
+                to: scene(&[("Ceiling", 60.), ("Counter strip", 20.)]),
+            }]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            r#"~ scene "Relax" in Room("Kitchen") ["Ceiling"]"#
+        );
+    }
+
+    #[test]
+    fn scene_actions_round_trip() {
+        let setup = Setup {
+            scenes: vec![scene(&[("Ceiling", 40.)])],
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&setup).unwrap();
+        assert_eq!(
+            json["scenes"][0]["actions"][0],
+            serde_json::json!({
+                "light": "Ceiling",
+                "action": {"on": {"on": true}, "dimming": {"brightness": 40.0}}
+            })
+        );
+        let parsed: Setup = serde_json::from_value(json).unwrap();
+        assert!(setup.diff(&parsed).is_empty());
+    }
+
+    #[test]
+    fn setup_round_trips_through_toml() {
+        let setup = Setup {
+            rooms: vec![group("Kitchen", &["Ceiling", "Counter strip"])],
+            zones: vec![group("Downstairs", &["Ceiling"])],
+            scenes: vec![scene(&[("Ceiling", 40.)])],
+            ..Default::default()
+        };
+        let text = toml::to_string(&setup).unwrap();
+        let parsed: Setup = toml::from_str(&text).unwrap();
+        assert_eq!(parsed, setup);
+    }
+
+    #[tokio::test]
+    async fn apply_setup_rejects_other_versions() {
+        // fails before any request, so the address does not matter
+        let bridge = Bridge::for_ip([127u8, 0, 0, 1]).with_user("user");
+        let desired = Setup {
+            version: SETUP_VERSION + 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            bridge.apply_setup(&desired).await,
+            Err(crate::HueError::ProtocolError { .. })
+        ));
+    }
+
+    #[test]
+    fn scene_names_are_matched_loosely() {
+        let current = Setup {
+            scenes: vec![scene(&[("Ceiling", 40.)])],
+            ..Default::default()
+        };
+        let mut desired = current.clone();
+        desired.scenes[0].name = "relax".into();
+        desired.scenes[0].group = SetupGroupRef::Room("KITCHEN".into());
+        let changes = current.diff(&desired);
+        assert!(matches!(&changes[..], [SetupChange::UpdateScene { .. }]));
+    }
+}
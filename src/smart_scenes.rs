@@ -0,0 +1,119 @@
+//! Smart scenes, which recall a different scene depending on the time of day.
+use crate::{Bridge, BridgeResponseV2, ResourceIdentifier, SceneMetadata};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartSceneState {
+    Active,
+    Inactive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeslotStart {
+    /// `time` for a fixed time of day, or `sunset`.
+    pub kind: String,
+    pub time: Option<TimeOfDay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timeslot {
+    pub start_time: TimeslotStart,
+    /// The scene recalled during this timeslot.
+    pub target: ResourceIdentifier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekTimeslots {
+    pub timeslots: Vec<Timeslot>,
+    /// The days these timeslots apply to, such as `monday`.
+    pub recurrence: Vec<String>,
+}
+
+/// The timeslot a smart scene is currently in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveTimeslot {
+    /// The index of the timeslot in the [`WeekTimeslots`] of `weekday`.
+    pub timeslot_id: u32,
+    pub weekday: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartScene {
+    pub id: String,
+    pub id_v1: Option<String>,
+    pub metadata: SceneMetadata,
+    /// The room or zone this smart scene belongs to.
+    pub group: ResourceIdentifier,
+    #[serde(default)]
+    pub week_timeslots: Vec<WeekTimeslots>,
+    /// The time to fade from one timeslot to the next, in milliseconds.
+    pub transition_duration: Option<u32>,
+    pub active_timeslot: Option<ActiveTimeslot>,
+    pub state: SmartSceneState,
+}
+
+impl Bridge {
+    /// Returns a vector of all smart scenes that are registered at this `Bridge`, sorted by
+    /// their id's.
+    pub async fn get_all_smart_scenes(&self) -> crate::Result<Vec<SmartScene>> {
+        self.require(crate::Feature::SmartScenes)?;
+        self.get_resources().await
+    }
+
+    async fn recall_smart_scene(&self, id: &str, action: &str) -> crate::Result<()> {
+        #[derive(Serialize)]
+        struct Recall<'a> {
+            action: &'a str,
+        }
+        #[derive(Serialize)]
+        struct PutSmartScene<'a> {
+            recall: Recall<'a>,
+        }
+        self.require(crate::Feature::SmartScenes)?;
+        let url = format!(
+            "https://{}/clip/v2/resource/smart_scene/{}",
+            self.host(),
+            id
+        );
+        let command = PutSmartScene {
+            recall: Recall { action },
+        };
+        let resp: BridgeResponseV2<Value> = self.send(self.client.put(&url).json(&command)).await?;
+        resp.get()?;
+        Ok(())
+    }
+
+    /// Activates a smart scene, which recalls the scene of the current timeslot, then the next
+    /// ones as time goes by.
+    pub async fn activate_smart_scene(&self, id: &str) -> crate::Result<()> {
+        self.recall_smart_scene(id, "activate").await
+    }
+
+    /// Deactivates a smart scene. The lights stay as they are, but stop following the timeslots.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::SmartSceneState;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// for smart_scene in bridge.get_all_smart_scenes().await.unwrap() {
+    ///     if smart_scene.state == SmartSceneState::Active {
+    ///         println!("{} is in {:?}", smart_scene.metadata.name, smart_scene.active_timeslot);
+    ///         bridge.deactivate_smart_scene(&smart_scene.id).await.unwrap();
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn deactivate_smart_scene(&self, id: &str) -> crate::Result<()> {
+        self.recall_smart_scene(id, "deactivate").await
+    }
+}
@@ -0,0 +1,358 @@
+//! An in-memory snapshot of the lights, rooms, zones and scenes, kept up to date with the event
+//! stream instead of polling the bridge.
+#[cfg(feature = "events")]
+use crate::TaskHandle;
+use crate::{
+    Bridge, EventData, EventGroup, EventLight, GroupMetadata, HueEvent, Light, Resource,
+    ResourceIdentifier, Room, Scene, Zone, XY,
+};
+#[cfg(feature = "events")]
+use futures::{stream, Stream, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// The lights, rooms, zones and scenes of a bridge, indexed by id.
+#[derive(Debug, Clone, Default)]
+pub struct HueState {
+    lights: HashMap<String, Light>,
+    rooms: HashMap<String, Room>,
+    zones: HashMap<String, Zone>,
+    scenes: HashMap<String, Scene>,
+}
+
+/// A [`HueState`] shared with the task keeping it up to date, see [`Bridge::watch_state`].
+pub type SharedHueState = Arc<RwLock<HueState>>;
+
+fn index<T>(resources: Vec<T>, id: impl Fn(&T) -> &str) -> HashMap<String, T> {
+    resources
+        .into_iter()
+        .map(|resource| (id(&resource).to_string(), resource))
+        .collect()
+}
+
+/// The value of a property before and after an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl Change<bool> {
+    /// Whether the property went from false to true, such as a light turning on.
+    pub fn is_rising(&self) -> bool {
+        !self.old && self.new
+    }
+
+    /// Whether the property went from true to false, such as a light turning off.
+    pub fn is_falling(&self) -> bool {
+        self.old && !self.new
+    }
+}
+
+/// The properties of a light an event actually changed, as returned by
+/// [`HueState::record_event`]. Properties the event repeats with their current value are not
+/// reported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedFields {
+    /// The id of the light.
+    pub id: String,
+    pub on: Option<Change<bool>>,
+    pub brightness: Option<Change<f32>>,
+    /// The white point, or `None` when the light is not in color temperature mode.
+    pub mirek: Option<Change<Option<u16>>>,
+    pub xy: Option<Change<XY>>,
+}
+
+impl ChangedFields {
+    /// Returns true if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.on.is_none() && self.brightness.is_none() && self.mirek.is_none() && self.xy.is_none()
+    }
+}
+
+fn update<T: Clone + PartialEq>(current: &mut T, new: &T) -> Option<Change<T>> {
+    (current != new).then(|| Change {
+        old: std::mem::replace(current, new.clone()),
+        new: new.clone(),
+    })
+}
+
+fn apply_light(light: &mut Light, change: &EventLight) -> ChangedFields {
+    let mut changed = ChangedFields {
+        id: light.id.clone(),
+        on: None,
+        brightness: None,
+        mirek: None,
+        xy: None,
+    };
+    if let Some(on) = &change.on {
+        changed.on = update(&mut light.on.on, &on.on);
+    }
+    if let (Some(dimming), Some(change)) = (&mut light.dimming, &change.dimming) {
+        changed.brightness = update(&mut dimming.brightness, &change.brightness);
+    }
+    if let (Some(ct), Some(change)) = (&mut light.color_temperature, &change.color_temperature) {
+        let mirek = change.mirek.filter(|_| change.mirek_valid);
+        let current = ct.mirek.filter(|_| ct.mirek_valid);
+        ct.mirek = change.mirek;
+        ct.mirek_valid = change.mirek_valid;
+        changed.mirek = (current != mirek).then_some(Change {
+            old: current,
+            new: mirek,
+        });
+    }
+    if let (Some(color), Some(change)) = (&mut light.color, &change.color) {
+        changed.xy = update(&mut color.xy, &change.xy);
+    }
+    changed
+}
+
+fn apply_group(
+    metadata: &mut GroupMetadata,
+    children: &mut Vec<ResourceIdentifier>,
+    change: &EventGroup,
+) {
+    if let Some(changed) = &change.metadata {
+        if let Some(name) = &changed.name {
+            metadata.name = name.clone();
+        }
+        if let Some(archetype) = &changed.archetype {
+            metadata.archetype = archetype.as_str().into();
+        }
+    }
+    if let Some(changed) = &change.children {
+        *children = changed.clone();
+    }
+}
+
+impl HueState {
+    /// Fetches the lights, rooms, zones and scenes.
+    pub async fn fetch(bridge: &Bridge) -> crate::Result<HueState> {
+        Ok(HueState {
+            lights: index(bridge.get_all_lights().await?, |light| &light.id),
+            rooms: index(bridge.get_all_rooms().await?, |room| &room.id),
+            zones: index(bridge.get_all_zones().await?, |zone| &zone.id),
+            scenes: index(bridge.get_all_scenes().await?, |scene| &scene.id),
+        })
+    }
+
+    pub fn light(&self, id: &str) -> Option<&Light> {
+        self.lights.get(id)
+    }
+
+    pub fn lights(&self) -> impl Iterator<Item = &Light> {
+        self.lights.values()
+    }
+
+    pub fn room(&self, id: &str) -> Option<&Room> {
+        self.rooms.get(id)
+    }
+
+    pub fn rooms(&self) -> impl Iterator<Item = &Room> {
+        self.rooms.values()
+    }
+
+    pub fn zone(&self, id: &str) -> Option<&Zone> {
+        self.zones.get(id)
+    }
+
+    pub fn zones(&self) -> impl Iterator<Item = &Zone> {
+        self.zones.values()
+    }
+
+    pub fn scene(&self, id: &str) -> Option<&Scene> {
+        self.scenes.get(id)
+    }
+
+    pub fn scenes(&self) -> impl Iterator<Item = &Scene> {
+        self.scenes.values()
+    }
+
+    fn add(&mut self, resource: Resource) {
+        match resource {
+            Resource::Light(light) => drop(self.lights.insert(light.id.clone(), light)),
+            Resource::Room(room) => drop(self.rooms.insert(room.id.clone(), room)),
+            Resource::Zone(zone) => drop(self.zones.insert(zone.id.clone(), zone)),
+            Resource::Scene(scene) => drop(self.scenes.insert(scene.id.clone(), scene)),
+            _ => (),
+        }
+    }
+
+    fn remove(&mut self, identifier: &ResourceIdentifier) {
+        let id = &identifier.rid;
+        match identifier.rtype.as_str() {
+            "light" => drop(self.lights.remove(id)),
+            "room" => drop(self.rooms.remove(id)),
+            "zone" => drop(self.zones.remove(id)),
+            "scene" => drop(self.scenes.remove(id)),
+            _ => (),
+        }
+    }
+
+    /// Applies the changes of an event to the resources it is about, and returns the properties
+    /// of the lights that actually changed. Created and deleted resources are added and removed.
+    pub fn record_event(&mut self, event: &HueEvent) -> Vec<ChangedFields> {
+        let mut changes = vec![];
+        let data = match event {
+            HueEvent::Update(data) => data,
+            HueEvent::Add(resources) => {
+                for resource in resources {
+                    self.add(resource.clone());
+                }
+                return changes;
+            }
+            HueEvent::Delete(identifiers) => {
+                for identifier in identifiers {
+                    self.remove(identifier);
+                }
+                return changes;
+            }
+            HueEvent::Error(_) => return changes,
+        };
+        for data in data {
+            match data {
+                EventData::Light(change) => {
+                    if let Some(light) = self.lights.get_mut(&change.id) {
+                        let changed = apply_light(light, change);
+                        if !changed.is_empty() {
+                            changes.push(changed);
+                        }
+                    }
+                }
+                EventData::Room(change) => {
+                    if let Some(room) = self.rooms.get_mut(&change.id) {
+                        apply_group(&mut room.metadata, &mut room.children, change);
+                    }
+                }
+                EventData::Zone(change) => {
+                    if let Some(zone) = self.zones.get_mut(&change.id) {
+                        apply_group(&mut zone.metadata, &mut zone.children, change);
+                    }
+                }
+                EventData::Scene(change) => {
+                    let scene = self.scenes.get_mut(&change.id);
+                    if let (Some(scene), Some(metadata)) = (scene, &change.metadata) {
+                        scene.metadata = metadata.clone();
+                    }
+                }
+                _ => (),
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(feature = "events")]
+impl Bridge {
+    /// Fetches the state of the bridge, then keeps it up to date in a background task applying
+    /// the changes of the event stream, until the returned handle is stopped.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let (state, _task) = bridge.watch_state().await.unwrap();
+    /// loop {
+    ///     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    ///     let state = state.read().unwrap();
+    ///     let on = state.lights().filter(|light| light.on.on).count();
+    ///     println!("{on} lights on");
+    /// }
+    /// # })
+    /// ```
+    pub async fn watch_state(&self) -> crate::Result<(SharedHueState, TaskHandle)> {
+        let mut events = Box::pin(self.events()?);
+        let state = Arc::new(RwLock::new(HueState::fetch(self).await?));
+        let watched = state.clone();
+        let task = TaskHandle::spawn(move |stopped| async move {
+            loop {
+                let event = tokio::select! {
+                    _ = stopped.cancelled() => break,
+                    event = events.next() => event,
+                };
+                match event {
+                    Some(event) => drop(watched.write().unwrap().record_event(&event)),
+                    None => break,
+                }
+            }
+        });
+        Ok((state, task))
+    }
+
+    /// Returns a stream of the light properties that actually change, with their old and new
+    /// values, so that rules can react to transitions such as a light turning on.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use futures::StreamExt;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let mut changes = Box::pin(bridge.light_changes().await.unwrap());
+    /// while let Some(changed) = changes.next().await {
+    ///     if changed.on.is_some_and(|on| on.is_rising()) {
+    ///         println!("{} turned on", changed.id);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn light_changes(&self) -> crate::Result<impl Stream<Item = ChangedFields>> {
+        let events = self.events()?;
+        let mut state = HueState::fetch(self).await?;
+        Ok(events.flat_map(move |event| stream::iter(state.record_event(&event))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(data: serde_json::Value) -> HueEvent {
+        HueEvent::Update(vec![serde_json::from_value(data).unwrap()])
+    }
+
+    #[test]
+    fn events_update_the_state() {
+        let light = crate::bridge::test_light(json!({
+            "on": { "on": false },
+            "dimming": { "brightness": 10.0 }
+        }));
+        let room: Room = serde_json::from_value(json!({
+            "id": "r1",
+            "metadata": { "name": "Office", "archetype": "office" },
+            "children": [],
+            "services": []
+        }))
+        .unwrap();
+        let mut state = HueState {
+            lights: index(vec![light], |light| &light.id),
+            rooms: index(vec![room], |room| &room.id),
+            ..Default::default()
+        };
+
+        let changes = state.record_event(&event(json!({
+            "type": "light", "id": "l1", "on": { "on": true }, "dimming": { "brightness": 80.0 }
+        })));
+        assert!(changes[0].on.as_ref().unwrap().is_rising());
+        assert_eq!(changes[0].brightness, Some(Change { old: 10., new: 80. }));
+        // repeating the current state is not a change
+        let changes = state.record_event(&event(json!({
+            "type": "light", "id": "l1", "on": { "on": true }
+        })));
+        assert!(changes.is_empty());
+        state.record_event(&event(json!({
+            "type": "room", "id": "r1", "metadata": { "name": "Study" }
+        })));
+        let light = state.light("l1").unwrap();
+        assert!(light.on.on);
+        assert_eq!(light.dimming.as_ref().unwrap().brightness, 80.);
+        assert_eq!(state.room("r1").unwrap().metadata.name, "Study");
+        assert_eq!(state.rooms().count(), 1);
+
+        state.record_event(&HueEvent::Delete(vec![ResourceIdentifier {
+            rid: "r1".into(),
+            rtype: "room".into(),
+        }]));
+        assert_eq!(state.rooms().count(), 0);
+    }
+}
@@ -0,0 +1,204 @@
+//! Persisting the state this crate keeps between runs, such as the application key or the
+//! address of the bridge, on a pluggable [`Storage`].
+use crate::{Bridge, UnauthBridge};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const APPLICATION_KEY: &str = "application_key";
+#[cfg(feature = "discovery")]
+const BRIDGE_IP: &str = "bridge_ip";
+#[cfg(feature = "discovery")]
+const BRIDGE_ID: &str = "bridge_id";
+
+/// A key-value store for the state of this crate. Keys are short ASCII identifiers such as
+/// `application_key`. Implement it to keep that state somewhere else than in files, such as a
+/// flash partition on an embedded device.
+pub trait Storage: Send + Sync {
+    fn load(&self, key: &str) -> crate::Result<Option<Vec<u8>>>;
+
+    fn save(&self, key: &str, value: &[u8]) -> crate::Result<()>;
+
+    fn remove(&self, key: &str) -> crate::Result<()>;
+}
+
+/// A [`Storage`] keeping each key in a file of a directory.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    /// A storage in `dir`, which is created on the first write if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileStorage { dir: dir.into() }
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self, key: &str) -> crate::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.dir.join(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> crate::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        // write then rename, so that a crash never leaves a truncated value behind
+        let tmp = self.dir.join(format!(".{key}.tmp"));
+        std::fs::write(&tmp, value)?;
+        std::fs::rename(tmp, self.dir.join(key))?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> crate::Result<()> {
+        match std::fs::remove_file(self.dir.join(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A [`Storage`] in memory, for tests or for state that does not need to outlive the process.
+/// Clones share the same content.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    values: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, key: &str) -> crate::Result<Option<Vec<u8>>> {
+        Ok(self.values.lock().unwrap().get(key).cloned())
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> crate::Result<()> {
+        self.values
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> crate::Result<()> {
+        self.values.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+fn load_string(storage: &dyn Storage, key: &str) -> crate::Result<Option<String>> {
+    match storage.load(key)? {
+        Some(value) => Ok(Some(
+            String::from_utf8(value).map_err(crate::HueError::protocol_err)?,
+        )),
+        None => Ok(None),
+    }
+}
+
+impl UnauthBridge {
+    /// Returns this bridge authenticated with the application key saved in `storage` by
+    /// [`Bridge::save_application_key`], if there is one.
+    /// ### Example
+    /// ```no_run
+    /// # #[cfg(feature = "discovery")]
+    /// # tokio_test::block_on(async {
+    /// use hueclient::FileStorage;
+    /// let storage = FileStorage::new("/var/lib/myapp/hue");
+    /// let unauth = hueclient::Bridge::discover_cached(&storage).await.unwrap();
+    /// let bridge = match unauth.clone().with_saved_application_key(&storage).unwrap() {
+    ///     Some(bridge) => bridge,
+    ///     None => {
+    ///         let bridge = unauth.register_application("myapp").await.unwrap();
+    ///         bridge.save_application_key(&storage).unwrap();
+    ///         bridge
+    ///     }
+    /// };
+    /// # })
+    /// ```
+    pub fn with_saved_application_key(
+        self,
+        storage: &dyn Storage,
+    ) -> crate::Result<Option<Bridge>> {
+        Ok(load_string(storage, APPLICATION_KEY)?.map(|key| self.with_user(key)))
+    }
+
+    #[cfg(feature = "discovery")]
+    /// Whether a bridge answers at this address.
+    async fn is_reachable(&self) -> bool {
+        let url = format!("https://{}/api/0/config", self.host());
+        let request = self
+            .client
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(2))
+            .send();
+        matches!(request.await, Ok(response) if response.status().is_success())
+    }
+}
+
+impl Bridge {
+    pub fn save_application_key(&self, storage: &dyn Storage) -> crate::Result<()> {
+        storage.save(APPLICATION_KEY, self.application_key.as_bytes())
+    }
+
+    #[cfg(feature = "discovery")]
+    /// Same as [`Bridge::discover`], but first tries the address of the bridge found by the
+    /// previous discovery, as saved in `storage`, which saves the discovery delay when the bridge
+    /// did not move. The id of the bridge is saved along, to keep validating its certificate.
+    pub async fn discover_cached(storage: &dyn Storage) -> crate::Result<UnauthBridge> {
+        if let Some(ip) = load_string(storage, BRIDGE_IP)? {
+            let bridge = Bridge::for_ip(ip.parse::<std::net::IpAddr>()?);
+            let bridge = match load_string(storage, BRIDGE_ID)? {
+                Some(bridge_id) => bridge.with_bridge_id(&bridge_id),
+                None => bridge,
+            };
+            if bridge.is_reachable().await {
+                return Ok(bridge);
+            }
+            log::info!("the bridge is not at {ip} anymore, discovering it again");
+        }
+        let bridge = Bridge::discover()
+            .await
+            .ok_or_else(|| crate::HueError::DiscoveryError {
+                msg: "Could not discover bridge".into(),
+            })?;
+        storage.save(BRIDGE_IP, bridge.ip.to_string().as_bytes())?;
+        match &bridge.tls.bridge_id {
+            Some(bridge_id) => storage.save(BRIDGE_ID, bridge_id.as_bytes())?,
+            None => storage.remove(BRIDGE_ID)?,
+        }
+        Ok(bridge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(storage: &dyn Storage) {
+        assert_eq!(storage.load("key").unwrap(), None);
+        storage.save("key", b"value").unwrap();
+        storage.save("key", b"new value").unwrap();
+        assert_eq!(
+            storage.load("key").unwrap().as_deref(),
+            Some(&b"new value"[..])
+        );
+        storage.remove("key").unwrap();
+        storage.remove("key").unwrap();
+        assert_eq!(storage.load("key").unwrap(), None);
+    }
+
+    #[test]
+    fn storages() {
+        round_trip(&MemoryStorage::new());
+        let dir = std::env::temp_dir().join(format!("hueclient-storage-{}", std::process::id()));
+        round_trip(&FileStorage::new(&dir));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,449 @@
+//! Streaming colors to entertainment areas, over the DTLS connection of the bridge.
+use crate::{Bridge, XY};
+use openssl::ssl::{SslContext, SslMethod, SslOptions, SslStream, SslVerifyMode, SslVersion};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::UdpSocket;
+use std::time::Duration;
+
+// the UDP port of the entertainment stream
+const STREAMING_PORT: u16 = 2100;
+// how long the DTLS handshake may take
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+// the largest datagram sent, well under the usual MTU
+const MTU: u32 = 1400;
+// the bridge drops the frames beyond this rate
+const MAX_FPS: u32 = 60;
+// how often an unchanged frame is sent again, the bridge ends the stream after 10s of silence
+const KEEPALIVE: Duration = Duration::from_secs(1);
+// the bridge ignores the channels after the 20th
+const MAX_CHANNELS: usize = 20;
+// how often the status of the area is checked while streaming
+const STATUS_POLL: Duration = Duration::from_secs(5);
+
+/// How the colors of a [`FrameBuffer`] are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Red, green and blue, each on 16 bits.
+    #[default]
+    Rgb,
+    /// CIE xy chromaticity and brightness, each on 16 bits.
+    Xy,
+}
+
+/// The colors of the channels of an entertainment area, sent as a single HueStream message by
+/// [`StreamingSession::send`]. Channels that are not set keep their previous color.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameBuffer {
+    color_space: ColorSpace,
+    channels: BTreeMap<u8, [u16; 3]>,
+}
+
+impl FrameBuffer {
+    pub fn new(color_space: ColorSpace) -> FrameBuffer {
+        FrameBuffer {
+            color_space,
+            channels: BTreeMap::new(),
+        }
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Sets the sRGB color of `channel`, converted to xy in the [`ColorSpace::Xy`] color space.
+    pub fn set_rgb(&mut self, channel: u8, [r, g, b]: [u8; 3]) {
+        let value = match self.color_space {
+            ColorSpace::Rgb => [r, g, b].map(|v| v as u16 * 257),
+            ColorSpace::Xy => {
+                let xy = XY::from_rgb(r, g, b);
+                let brightness = r.max(g).max(b) as f32 / 255.;
+                [xy.x, xy.y, brightness].map(to_u16)
+            }
+        };
+        self.channels.insert(channel, value);
+    }
+
+    /// Sets the chromaticity and the brightness, in percent, of `channel`, converted to sRGB in
+    /// the [`ColorSpace::Rgb`] color space.
+    pub fn set_xy(&mut self, channel: u8, xy: XY, brightness: f32) {
+        let value = match self.color_space {
+            ColorSpace::Rgb => xy.to_rgb(brightness).map(|v| v as u16 * 257),
+            ColorSpace::Xy => [xy.x, xy.y, brightness / 100.].map(to_u16),
+        };
+        self.channels.insert(channel, value);
+    }
+
+    /// Forgets the colors of all the channels.
+    pub fn clear(&mut self) {
+        self.channels.clear();
+    }
+
+    /// The HueStream v2 message holding this frame.
+    fn encode(&self, configuration: &str, sequence: u8) -> Vec<u8> {
+        let mut message = Vec::with_capacity(52 + 7 * self.channels.len());
+        message.extend_from_slice(b"HueStream");
+        message.extend_from_slice(&[2, 0, sequence, 0, 0]);
+        message.push(match self.color_space {
+            ColorSpace::Rgb => 0,
+            ColorSpace::Xy => 1,
+        });
+        message.push(0);
+        message.extend_from_slice(configuration.as_bytes());
+        for (channel, value) in self.channels.iter().take(MAX_CHANNELS) {
+            message.push(*channel);
+            for v in value {
+                message.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        message
+    }
+}
+
+fn to_u16(v: f32) -> u16 {
+    (v.clamp(0., 1.) * 65535.).round() as u16
+}
+
+fn decode_client_key(client_key: &str) -> crate::Result<Vec<u8>> {
+    let invalid = || crate::HueError::StreamingError {
+        msg: "the client key is not an hexadecimal string".into(),
+    };
+    if !client_key.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..client_key.len())
+        .step_by(2)
+        .map(|i| {
+            client_key
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+fn streaming_err(err: impl std::fmt::Display) -> crate::HueError {
+    crate::HueError::StreamingError {
+        msg: err.to_string(),
+    }
+}
+
+/// A connected UDP socket, seen as a stream of datagrams by OpenSSL.
+#[derive(Debug)]
+struct Datagrams(UdpSocket);
+
+impl Read for Datagrams {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.recv(buf)
+    }
+}
+
+impl Write for Datagrams {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.send(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An entertainment area being streamed to, opened with [`Bridge::start_streaming`].
+///
+/// Frames can be sent at up to 60 Hz, faster frames are dropped by the bridge. The bridge ends
+/// the stream after 10 seconds without frames.
+#[derive(Debug)]
+pub struct StreamingSession {
+    bridge: Bridge,
+    configuration: String,
+    psk: Vec<u8>,
+    stream: SslStream<Datagrams>,
+    sequence: u8,
+    // the application streaming to the area, as reported by the bridge once the stream is open
+    streamer: Option<String>,
+}
+
+impl Bridge {
+    /// Starts the entertainment configuration with the id `configuration`, and opens the DTLS
+    /// connection to stream colors to its channels. `client_key` is the key returned by
+    /// [`crate::UnauthBridge::register_entertainment_application`] along with the application key
+    /// of this `Bridge`. This requires the `entertainment` feature.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::{ColorSpace, FrameBuffer};
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let mut session = bridge
+    ///     .start_streaming(
+    ///         "1a8d99cc-967b-44f2-9202-43f976c0fa6b",
+    ///         "321c0c2ebfa7361e55491095b2f5f9db",
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// let mut frame = FrameBuffer::new(ColorSpace::Rgb);
+    /// for step in 0..600u32 {
+    ///     frame.set_rgb(0, [(step % 256) as u8, 0, 255]);
+    ///     session.send(&frame).unwrap();
+    ///     tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    /// }
+    /// session.stop().await.unwrap();
+    /// # })
+    /// ```
+    pub async fn start_streaming(
+        &self,
+        configuration: &str,
+        client_key: &str,
+    ) -> crate::Result<StreamingSession> {
+        let psk = decode_client_key(client_key)?;
+        let stream = self.open_stream(configuration, &psk).await?;
+        log::info!("streaming to entertainment configuration {configuration}");
+        Ok(StreamingSession {
+            bridge: self.clone(),
+            configuration: configuration.to_string(),
+            psk,
+            stream,
+            sequence: 0,
+            streamer: self.active_streamer(configuration).await,
+        })
+    }
+
+    async fn active_streamer(&self, configuration: &str) -> Option<String> {
+        match self.get_entertainment_configuration(configuration).await {
+            Ok(configuration) => configuration.active_streamer.map(|streamer| streamer.rid),
+            Err(e) => {
+                log::warn!("could not read the streamer of {configuration}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Starts the entertainment configuration and performs the DTLS handshake, stopping the
+    /// configuration again if the handshake fails.
+    async fn open_stream(
+        &self,
+        configuration: &str,
+        psk: &[u8],
+    ) -> crate::Result<SslStream<Datagrams>> {
+        self.put_entertainment_action(configuration, "start")
+            .await?;
+        let identity = self.application_key.clone();
+        let psk = psk.to_vec();
+        let ip = self.ip;
+        let connect = move || -> crate::Result<SslStream<Datagrams>> {
+            let mut context =
+                SslContext::builder(SslMethod::dtls_client()).map_err(streaming_err)?;
+            context
+                .set_min_proto_version(Some(SslVersion::DTLS1_2))
+                .map_err(streaming_err)?;
+            context
+                .set_cipher_list("PSK-AES128-GCM-SHA256")
+                .map_err(streaming_err)?;
+            context.set_verify(SslVerifyMode::NONE);
+            context.set_options(SslOptions::NO_QUERY_MTU);
+            context.set_psk_client_callback(move |_, _, identity_buf, psk_buf| {
+                // the identity is sent null terminated
+                if identity.len() >= identity_buf.len() || psk.len() > psk_buf.len() {
+                    log::warn!("the application key or client key is too long for DTLS");
+                    return Err(openssl::error::ErrorStack::get());
+                }
+                identity_buf[..identity.len()].copy_from_slice(identity.as_bytes());
+                identity_buf[identity.len()] = 0;
+                psk_buf[..psk.len()].copy_from_slice(&psk);
+                Ok(psk.len())
+            });
+            let mut ssl = openssl::ssl::Ssl::new(&context.build()).map_err(streaming_err)?;
+            ssl.set_mtu(MTU).map_err(streaming_err)?;
+            let local: std::net::SocketAddr = match ip {
+                std::net::IpAddr::V4(_) => ([0u8; 4], 0).into(),
+                std::net::IpAddr::V6(_) => ([0u16; 8], 0).into(),
+            };
+            let socket = UdpSocket::bind(local)?;
+            socket.connect((ip, STREAMING_PORT))?;
+            socket.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+            ssl.connect(Datagrams(socket)).map_err(streaming_err)
+        };
+        match tokio::task::spawn_blocking(connect)
+            .await
+            .map_err(streaming_err)
+        {
+            Ok(Ok(stream)) => Ok(stream),
+            Ok(Err(e)) | Err(e) => {
+                // best effort, to give the area back to the other applications
+                let _ = self.put_entertainment_action(configuration, "stop").await;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl StreamingSession {
+    /// The id of the entertainment configuration being streamed to.
+    pub fn configuration(&self) -> &str {
+        &self.configuration
+    }
+
+    /// Sends the colors of `frame` to the bridge.
+    pub fn send(&mut self, frame: &FrameBuffer) -> crate::Result<()> {
+        let message = frame.encode(&self.configuration, self.sequence);
+        self.sequence = self.sequence.wrapping_add(1);
+        self.stream.write_all(&message)?;
+        Ok(())
+    }
+
+    /// Starts the entertainment configuration again and opens a new DTLS connection, for instance
+    /// after the bridge ended the stream because another application took over the area.
+    pub async fn renew(&mut self) -> crate::Result<()> {
+        // the old connection is most likely dead already
+        let _ = self.stream.shutdown();
+        self.stream = self
+            .bridge
+            .open_stream(&self.configuration, &self.psk)
+            .await?;
+        self.sequence = 0;
+        self.streamer = self.bridge.active_streamer(&self.configuration).await;
+        log::info!(
+            "renewed the stream to entertainment configuration {}",
+            self.configuration
+        );
+        Ok(())
+    }
+
+    /// Streams frames at `fps` frames per second, at most 60, until an error occurs. `render` is
+    /// called before each frame to update the colors of the channels, in the [`ColorSpace::Rgb`]
+    /// color space. Frames that did not change are only sent once per second, which is enough to
+    /// keep the stream alive. If sending a frame fails, the session is renewed once before giving
+    /// up.
+    ///
+    /// The status of the area is checked every 5 seconds, and a `HueError::StreamingError` is
+    /// returned once the bridge has ended the stream, for instance because another application
+    /// took the area over. Otherwise the loop only ends when it is dropped, for instance with
+    /// [`crate::cancellable`], after which [`StreamingSession::stop`] can be called.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let mut session = bridge
+    ///     .start_streaming(
+    ///         "1a8d99cc-967b-44f2-9202-43f976c0fa6b",
+    ///         "321c0c2ebfa7361e55491095b2f5f9db",
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// let shutdown = hueclient::CancellationToken::new();
+    /// let mut step = 0u32;
+    /// let _ = hueclient::cancellable(
+    ///     &shutdown,
+    ///     session.run_at(50, |frame| {
+    ///         step += 1;
+    ///         frame.set_rgb(0, [(step % 256) as u8, 0, 255]);
+    ///     }),
+    /// )
+    /// .await;
+    /// session.stop().await.unwrap();
+    /// # })
+    /// ```
+    pub async fn run_at(
+        &mut self,
+        fps: u32,
+        mut render: impl FnMut(&mut FrameBuffer),
+    ) -> crate::Result<std::convert::Infallible> {
+        let period = Duration::from_secs(1) / fps.clamp(1, MAX_FPS);
+        let mut ticks = tokio::time::interval(period);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut last_checked = tokio::time::Instant::now();
+        let mut frame = FrameBuffer::default();
+        let mut last_sent: Option<(FrameBuffer, tokio::time::Instant)> = None;
+        loop {
+            let now = ticks.tick().await;
+            if now - last_checked >= STATUS_POLL {
+                last_checked = now;
+                self.check_still_streaming().await?;
+            }
+            render(&mut frame);
+            let due = match &last_sent {
+                Some((sent, at)) => *sent != frame || now - *at >= KEEPALIVE,
+                None => true,
+            };
+            if !due {
+                continue;
+            }
+            if let Err(e) = self.send(&frame) {
+                log::warn!("could not send a frame, renewing the stream: {e}");
+                self.renew().await?;
+                self.send(&frame)?;
+            }
+            last_sent = Some((frame.clone(), now));
+        }
+    }
+
+    /// Fails if the bridge no longer streams to the area, or streams from another application.
+    async fn check_still_streaming(&self) -> crate::Result<()> {
+        let configuration = match self
+            .bridge
+            .get_entertainment_configuration(&self.configuration)
+            .await
+        {
+            Ok(configuration) => configuration,
+            Err(e) => {
+                // the frames may still get through, the next check will tell
+                log::warn!("could not check the status of {}: {e}", self.configuration);
+                return Ok(());
+            }
+        };
+        if !configuration.is_streaming() {
+            return Err(streaming_err("the bridge ended the stream"));
+        }
+        let streamer = configuration.active_streamer.map(|streamer| streamer.rid);
+        if self.streamer.is_some() && streamer != self.streamer {
+            return Err(streaming_err("another application took the area over"));
+        }
+        Ok(())
+    }
+
+    /// Closes the DTLS connection and stops the entertainment configuration, giving its lights
+    /// back to the other applications.
+    pub async fn stop(mut self) -> crate::Result<()> {
+        // the bridge does not always answer the close notify
+        let _ = self.stream.shutdown();
+        self.bridge
+            .put_entertainment_action(&self.configuration, "stop")
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIGURATION: &str = "1a8d99cc-967b-44f2-9202-43f976c0fa6b";
+
+    #[test]
+    fn huestream_messages() {
+        let mut frame = FrameBuffer::new(ColorSpace::Rgb);
+        frame.set_rgb(3, [255, 0, 128]);
+        frame.set_rgb(1, [0, 0, 0]);
+        let message = frame.encode(CONFIGURATION, 7);
+        assert_eq!(&message[..9], b"HueStream");
+        assert_eq!(&message[9..16], &[2, 0, 7, 0, 0, 0, 0]);
+        assert_eq!(&message[16..52], CONFIGURATION.as_bytes());
+        assert_eq!(
+            &message[52..],
+            &[1, 0, 0, 0, 0, 0, 0, 3, 255, 255, 0, 0, 128, 128]
+        );
+
+        let mut frame = FrameBuffer::new(ColorSpace::Xy);
+        frame.set_xy(0, XY { x: 0.5, y: 0.25 }, 100.);
+        let message = frame.encode(CONFIGURATION, 0);
+        assert_eq!(message[14], 1);
+        assert_eq!(&message[52..], &[0, 128, 0, 64, 0, 255, 255]);
+    }
+
+    #[test]
+    fn client_keys() {
+        assert_eq!(decode_client_key("00ff1A").unwrap(), vec![0, 255, 26]);
+        assert!(decode_client_key("0ff").is_err());
+        assert!(decode_client_key("zz").is_err());
+    }
+}
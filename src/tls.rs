@@ -0,0 +1,484 @@
+//! Validating the certificate of the bridge.
+//!
+//! The bridge is reached by IP-address, so the usual host name check cannot apply. Instead, as
+//! documented at https://developers.meethue.com/develop/application-design-guidance/using-https/,
+//! the certificate must either be signed by the Hue root CA, or be a self-signed certificate from
+//! an older bridge. In both cases its common name is the id of the bridge, which is checked when
+//! it is known.
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::verify_server_cert_signed_by_trust_anchor;
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, SignatureVerificationAlgorithm, UnixTime};
+use rustls::server::ParsedCertificate;
+use rustls::{CertificateError, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use std::sync::Arc;
+
+pub(crate) const HUE_ROOT_CA: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIICMjCCAdigAwIBAgIUO7FSLbaxikuXAljzVaurLXWmFw4wCgYIKoZIzj0EAwIw
+OTELMAkGA1UEBhMCTkwxFDASBgNVBAoMC1BoaWxpcHMgSHVlMRQwEgYDVQQDDAty
+b290LWJyaWRnZTAiGA8yMDE3MDEwMTAwMDAwMFoYDzIwMzgwMTE5MDMxNDA3WjA5
+MQswCQYDVQQGEwJOTDEUMBIGA1UECgwLUGhpbGlwcyBIdWUxFDASBgNVBAMMC3Jv
+b3QtYnJpZGdlMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEjNw2tx2AplOf9x86
+aTdvEcL1FU65QDxziKvBpW9XXSIcibAeQiKxegpq8Exbr9v6LBnYbna2VcaK0G22
+jOKkTqOBuTCBtjAPBgNVHRMBAf8EBTADAQH/MA4GA1UdDwEB/wQEAwIBhjAdBgNV
+HQ4EFgQUZ2ONTFrDT6o8ItRnKfqWKnHFGmQwdAYDVR0jBG0wa4AUZ2ONTFrDT6o8
+ItRnKfqWKnHFGmShPaQ7MDkxCzAJBgNVBAYTAk5MMRQwEgYDVQQKDAtQaGlsaXBz
+IEh1ZTEUMBIGA1UEAwwLcm9vdC1icmlkZ2WCFDuxUi22sYpLlwJY81Wrqy11phcO
+MAoGCCqGSM49BAMCA0gAMEUCIEBYYEOsa07TH7E5MJnGw557lVkORgit2Rm1h3B2
+sFgDAiEA1Fj/C3AN5psFMjo0//mrQebo0eKd3aWRx+pQY08mk48=
+-----END CERTIFICATE-----";
+
+// 2.5.4.3, the common name attribute
+const COMMON_NAME_OID: &[u8] = &[0x55, 0x04, 0x03];
+
+/// How the certificate of the bridge is validated, shared by [`crate::UnauthBridge`] and
+/// [`crate::Bridge`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TlsOptions {
+    /// The expected common name of the certificate, in lowercase.
+    pub(crate) bridge_id: Option<String>,
+    pub(crate) accept_invalid_certs: bool,
+}
+
+/// Splits the next DER element off `der`, returning its tag, its content and the rest.
+fn next_element(der: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, der) = der.split_first()?;
+    let (&first, der) = der.split_first()?;
+    let (len, der) = if first < 0x80 {
+        (first as usize, der)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || der.len() < count {
+            return None;
+        }
+        let (bytes, der) = der.split_at(count);
+        let len = bytes.iter().fold(0, |len, &b| (len << 8) | b as usize);
+        (len, der)
+    };
+    if der.len() < len {
+        return None;
+    }
+    let (content, rest) = der.split_at(len);
+    Some((tag, content, rest))
+}
+
+/// The raw issuer and subject names of a certificate.
+fn issuer_and_subject(cert: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (_, cert, _) = next_element(cert)?;
+    let (_, mut tbs, _) = next_element(cert)?;
+    // the version is an optional explicitly tagged element
+    if tbs.first() == Some(&0xa0) {
+        tbs = next_element(tbs)?.2;
+    }
+    // then come the serial number and the signature algorithm
+    let (_, _, tbs) = next_element(tbs)?;
+    let (_, _, tbs) = next_element(tbs)?;
+    let (_, issuer, tbs) = next_element(tbs)?;
+    // skipping the validity
+    let (_, _, tbs) = next_element(tbs)?;
+    let (_, subject, _) = next_element(tbs)?;
+    Some((issuer, subject))
+}
+
+/// The common name of the subject of a certificate.
+fn common_name(cert: &[u8]) -> Option<String> {
+    let (_, mut names) = issuer_and_subject(cert)?;
+    while let Some((_, set, rest)) = next_element(names) {
+        let (_, attribute, _) = next_element(set)?;
+        let (_, oid, value) = next_element(attribute)?;
+        if oid == COMMON_NAME_OID {
+            let (_, value, _) = next_element(value)?;
+            return String::from_utf8(value.to_vec()).ok();
+        }
+        names = rest;
+    }
+    None
+}
+
+fn is_self_signed(cert: &[u8]) -> bool {
+    issuer_and_subject(cert).is_some_and(|(issuer, subject)| issuer == subject)
+}
+
+/// The parts of a certificate checked for self-signed certificates.
+struct SignedParts<'a> {
+    /// The signed part of the certificate, with its DER header.
+    tbs: &'a [u8],
+    signature_algorithm: &'a [u8],
+    signature: &'a [u8],
+    validity: &'a [u8],
+    public_key_algorithm: &'a [u8],
+    public_key: &'a [u8],
+}
+
+/// The content of a DER bit string, which must not have unused bits.
+fn bit_string(value: &[u8]) -> Option<&[u8]> {
+    match value.split_first()? {
+        (0, bits) => Some(bits),
+        _ => None,
+    }
+}
+
+fn signed_parts(cert: &[u8]) -> Option<SignedParts<'_>> {
+    let (_, cert, _) = next_element(cert)?;
+    let (_, tbs_content, rest) = next_element(cert)?;
+    let tbs = &cert[..cert.len() - rest.len()];
+    let (_, signature_algorithm, rest) = next_element(rest)?;
+    let (_, signature, _) = next_element(rest)?;
+    let mut fields = tbs_content;
+    if fields.first() == Some(&0xa0) {
+        fields = next_element(fields)?.2;
+    }
+    // the serial number, the signature algorithm and the issuer
+    let (_, _, fields) = next_element(fields)?;
+    let (_, _, fields) = next_element(fields)?;
+    let (_, _, fields) = next_element(fields)?;
+    let (_, validity, fields) = next_element(fields)?;
+    let (_, _, fields) = next_element(fields)?;
+    let (_, public_key_info, _) = next_element(fields)?;
+    let (_, public_key_algorithm, rest) = next_element(public_key_info)?;
+    let (_, public_key, _) = next_element(rest)?;
+    Some(SignedParts {
+        tbs,
+        signature_algorithm,
+        signature: bit_string(signature)?,
+        validity,
+        public_key_algorithm,
+        public_key: bit_string(public_key)?,
+    })
+}
+
+/// Parses an UTCTime or a GeneralizedTime, in seconds since the epoch.
+fn parse_time(tag: u8, value: &[u8]) -> Option<u64> {
+    let value = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        0x17 => {
+            let year: u64 = value.get(..2)?.parse().ok()?;
+            (
+                if year >= 50 { 1900 + year } else { 2000 + year },
+                value.get(2..)?,
+            )
+        }
+        0x18 => (value.get(..4)?.parse().ok()?, value.get(4..)?),
+        _ => return None,
+    };
+    if rest.len() != 10 || !rest.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |i: usize| rest[i..i + 2].parse::<u64>().ok();
+    let [month, day, hour, minute, second] = [0, 2, 4, 6, 8].map(field);
+    let (month, day) = (month?, day?);
+    if !(1..=12).contains(&month) || day == 0 {
+        return None;
+    }
+    // see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    Some(days * 86400 + hour? * 3600 + minute? * 60 + second?)
+}
+
+/// The first and the last second a certificate is valid, from its validity field.
+fn validity_period(validity: &[u8]) -> Option<(u64, u64)> {
+    let (tag, not_before, rest) = next_element(validity)?;
+    let not_before = parse_time(tag, not_before)?;
+    let (tag, not_after, _) = next_element(rest)?;
+    Some((not_before, parse_time(tag, not_after)?))
+}
+
+/// Checks that a certificate is signed by its own key and is currently valid, as the certificates
+/// of older bridges are. They are CA certificates, which webpki refuses as end entities, so they
+/// are checked here.
+fn verify_self_signed(
+    end_entity: &[u8],
+    now: UnixTime,
+    algorithms: &[&dyn SignatureVerificationAlgorithm],
+) -> Result<(), rustls::Error> {
+    if !is_self_signed(end_entity) {
+        return Err(CertificateError::UnknownIssuer.into());
+    }
+    let parts = signed_parts(end_entity).ok_or(CertificateError::BadEncoding)?;
+    let (not_before, not_after) =
+        validity_period(parts.validity).ok_or(CertificateError::BadEncoding)?;
+    if now.as_secs() < not_before {
+        return Err(CertificateError::NotValidYet.into());
+    }
+    if now.as_secs() > not_after {
+        return Err(CertificateError::Expired.into());
+    }
+    let algorithm = algorithms
+        .iter()
+        .find(|algorithm| {
+            algorithm.public_key_alg_id().as_ref() == parts.public_key_algorithm
+                && algorithm.signature_alg_id().as_ref() == parts.signature_algorithm
+        })
+        .ok_or(CertificateError::BadSignature)?;
+    algorithm
+        .verify_signature(parts.public_key, parts.tbs, parts.signature)
+        .map_err(|_| CertificateError::BadSignature.into())
+}
+
+#[derive(Debug)]
+struct BridgeCertVerifier {
+    bridge_id: Option<String>,
+    roots: RootCertStore,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for BridgeCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let cert = ParsedCertificate::try_from(end_entity)?;
+        let algorithms = self.provider.signature_verification_algorithms.all;
+        let signed_by_hue = verify_server_cert_signed_by_trust_anchor(
+            &cert,
+            &self.roots,
+            intermediates,
+            now,
+            algorithms,
+        );
+        let Some(bridge_id) = &self.bridge_id else {
+            // without an id to compare with, only the signature of the Hue root CA is trusted
+            return signed_by_hue.map(|_| ServerCertVerified::assertion());
+        };
+        if signed_by_hue.is_err() {
+            verify_self_signed(end_entity, now, algorithms)?;
+        }
+        match common_name(end_entity) {
+            Some(name) if name.eq_ignore_ascii_case(bridge_id) => {
+                Ok(ServerCertVerified::assertion())
+            }
+            _ => Err(CertificateError::NotValidForName.into()),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let algorithms = &self.provider.signature_verification_algorithms;
+        verify_tls12_signature(message, cert, dss, algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let algorithms = &self.provider.signature_verification_algorithms;
+        verify_tls13_signature(message, cert, dss, algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+impl BridgeCertVerifier {
+    fn new(bridge_id: Option<String>, root_ca: &[u8]) -> BridgeCertVerifier {
+        let mut roots = RootCertStore::empty();
+        let root = CertificateDer::from_pem_slice(root_ca)
+            .expect("using rustls and this hardcoded certificate should never fail");
+        roots
+            .add(root)
+            .expect("using rustls and this hardcoded certificate should never fail");
+        BridgeCertVerifier {
+            bridge_id,
+            roots,
+            provider: Arc::new(rustls::crypto::ring::default_provider()),
+        }
+    }
+}
+
+/// The TLS configuration validating the bridge certificate as described by `options`, when it
+/// does not accept invalid certificates.
+pub(crate) fn client_config(options: &TlsOptions) -> rustls::ClientConfig {
+    let verifier = BridgeCertVerifier::new(options.bridge_id.clone(), HUE_ROOT_CA);
+    let provider = verifier.provider.clone();
+    rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .expect("the default protocol versions are supported by ring")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRIDGE_ID: &str = "ecb5fafffe0a1b2c";
+    // generated with openssl, valid from 2026 to 2126
+    const TEST_ROOT_CA: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIBxzCCAW2gAwIBAgIUAvYF/X9GB2DMW2m8Cx81zUR4lfIwCgYIKoZIzj0EAwIw
+MDELMAkGA1UEBhMCTkwxDTALBgNVBAoMBFRlc3QxEjAQBgNVBAMMCXRlc3Qtcm9v
+dDAgFw0yNjEwMTcwMDQzNDdaGA8yMTI2MDkyMzAwNDM0N1owMDELMAkGA1UEBhMC
+TkwxDTALBgNVBAoMBFRlc3QxEjAQBgNVBAMMCXRlc3Qtcm9vdDBZMBMGByqGSM49
+AgEGCCqGSM49AwEHA0IABJPgv4Q50xsSeipTV3CNz07ISv2tsygijuNOaql7dMa+
+3L1zGzMo9raAkMzjqmtAgrlFbOurEpk55D0kz2RnSaujYzBhMB0GA1UdDgQWBBSd
+OpbGlolz2W4F/E1fVsYi27yYEjAfBgNVHSMEGDAWgBSdOpbGlolz2W4F/E1fVsYi
+27yYEjAPBgNVHRMBAf8EBTADAQH/MA4GA1UdDwEB/wQEAwICBDAKBggqhkjOPQQD
+AgNIADBFAiAl9Stf6ssTsIkMGtpCfyTQ5u7NqtC8EPYBZ06EHw/W+QIhALqxZBNo
+a46b5r4ZmWddFdNRqcf5luvkWSFmv7D5MTvc
+-----END CERTIFICATE-----";
+    // signed by TEST_ROOT_CA
+    const SIGNED_BY_CA: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIBwTCCAWigAwIBAgIUfQTNBUyAWICt0GPZPJ8Ft16VKI8wCgYIKoZIzj0EAwIw
+MDELMAkGA1UEBhMCTkwxDTALBgNVBAoMBFRlc3QxEjAQBgNVBAMMCXRlc3Qtcm9v
+dDAgFw0yNjEwMTcwMDQzNDdaGA8yMTI2MDkyMzAwNDM0N1owPjELMAkGA1UEBhMC
+TkwxFDASBgNVBAoMC1BoaWxpcHMgSHVlMRkwFwYDVQQDDBBlY2I1ZmFmZmZlMGEx
+YjJjMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEXxcAWFrQ1XZgdpVXnUgtpeoG
+8Q5OCBt+YOcqO/yh4ZxeyYTDSPBgrMzoVLaWETqZycg1fvuGRsGIUBj4TIx1VaNQ
+ME4wDAYDVR0TAQH/BAIwADAdBgNVHQ4EFgQU/m9YhLxOQDt1/yL+hShUAjLVJBUw
+HwYDVR0jBBgwFoAUnTqWxpaJc9luBfxNX1bGItu8mBIwCgYIKoZIzj0EAwIDRwAw
+RAIgSH98W9ITRzgp2g9qAeozxr9rZv0moqsuI/T2AcNqNkcCID/iC1YwnZaWUQgl
+O6UWJji5LZJ8z+yiwgXHbocWeUZ5
+-----END CERTIFICATE-----";
+    // self-signed, as a CA as the certificates of older bridges are
+    const SELF_SIGNED_CA: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIB1DCCAXmgAwIBAgIUcDhVr80XUcfddiYAxoV1VAcTnpEwCgYIKoZIzj0EAwIw
+PjELMAkGA1UEBhMCTkwxFDASBgNVBAoMC1BoaWxpcHMgSHVlMRkwFwYDVQQDDBBl
+Y2I1ZmFmZmZlMGExYjJjMCAXDTI2MTAxNzAwNDM0N1oYDzIxMjYwOTIzMDA0MzQ3
+WjA+MQswCQYDVQQGEwJOTDEUMBIGA1UECgwLUGhpbGlwcyBIdWUxGTAXBgNVBAMM
+EGVjYjVmYWZmZmUwYTFiMmMwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARfFwBY
+WtDVdmB2lVedSC2l6gbxDk4IG35g5yo7/KHhnF7JhMNI8GCszOhUtpYROpnJyDV+
++4ZGwYhQGPhMjHVVo1MwUTAdBgNVHQ4EFgQU/m9YhLxOQDt1/yL+hShUAjLVJBUw
+HwYDVR0jBBgwFoAU/m9YhLxOQDt1/yL+hShUAjLVJBUwDwYDVR0TAQH/BAUwAwEB
+/zAKBggqhkjOPQQDAgNJADBGAiEAvL/3UXpugFhN02Av5lfI/SZex3+Nf7NCW7gS
+DoIdXu0CIQDUOS8/RZpMBt08mLDCtycBWGScwWLoZWZpN2PIgdVNcw==
+-----END CERTIFICATE-----";
+    const SELF_SIGNED: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIB0DCCAXagAwIBAgIUPwYojNBYscJxHXP3X5zFhs50vg8wCgYIKoZIzj0EAwIw
+PjELMAkGA1UEBhMCTkwxFDASBgNVBAoMC1BoaWxpcHMgSHVlMRkwFwYDVQQDDBBl
+Y2I1ZmFmZmZlMGExYjJjMCAXDTI2MTAxNzAwNDM0N1oYDzIxMjYwOTIzMDA0MzQ3
+WjA+MQswCQYDVQQGEwJOTDEUMBIGA1UECgwLUGhpbGlwcyBIdWUxGTAXBgNVBAMM
+EGVjYjVmYWZmZmUwYTFiMmMwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARfFwBY
+WtDVdmB2lVedSC2l6gbxDk4IG35g5yo7/KHhnF7JhMNI8GCszOhUtpYROpnJyDV+
++4ZGwYhQGPhMjHVVo1AwTjAdBgNVHQ4EFgQU/m9YhLxOQDt1/yL+hShUAjLVJBUw
+HwYDVR0jBBgwFoAU/m9YhLxOQDt1/yL+hShUAjLVJBUwDAYDVR0TAQH/BAIwADAK
+BggqhkjOPQQDAgNIADBFAiEAgo2cG5rczB0DJ0A5Vf59HjZfS3EbjD/ttOaeDNg8
+JqICIAIDRbr75Z4iH2IGKxu5+mgFqsgjN1MdXupgpd4OJARn
+-----END CERTIFICATE-----";
+    // issuer and subject are the same, but signed by the key of TEST_ROOT_CA
+    const FORGED_SELF_SIGNED: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIBvTCCAWOgAwIBAgIBBzAKBggqhkjOPQQDAjA+MQswCQYDVQQGEwJOTDEUMBIG
+A1UECgwLUGhpbGlwcyBIdWUxGTAXBgNVBAMMEGVjYjVmYWZmZmUwYTFiMmMwIBcN
+MjYxMDE3MDA0MzU2WhgPMjEyNjA5MjMwMDQzNTZaMD4xCzAJBgNVBAYTAk5MMRQw
+EgYDVQQKDAtQaGlsaXBzIEh1ZTEZMBcGA1UEAwwQZWNiNWZhZmZmZTBhMWIyYzBZ
+MBMGByqGSM49AgEGCCqGSM49AwEHA0IABF8XAFha0NV2YHaVV51ILaXqBvEOTggb
+fmDnKjv8oeGcXsmEw0jwYKzM6FS2lhE6mcnINX77hkbBiFAY+EyMdVWjUDBOMAwG
+A1UdEwEB/wQCMAAwHQYDVR0OBBYEFP5vWIS8TkA7df8i/oUoVAIy1SQVMB8GA1Ud
+IwQYMBaAFJ06lsaWiXPZbgX8TV9WxiLbvJgSMAoGCCqGSM49BAMCA0gAMEUCIB75
+i8aQUzdJ8/B/SivyVbkftPvCJtk8Hmz3x43K3A2CAiEAzYeY6yAfxdV1pNqHGo6D
+IyLXvUoc+IC0L16DQJ4+LlM=
+-----END CERTIFICATE-----";
+    // self-signed for the bridge ecb5fafffe999999
+    const OTHER_BRIDGE: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIB0zCCAXmgAwIBAgIUCEhWTTekciMsqgHgRTHHfVree2QwCgYIKoZIzj0EAwIw
+PjELMAkGA1UEBhMCTkwxFDASBgNVBAoMC1BoaWxpcHMgSHVlMRkwFwYDVQQDDBBl
+Y2I1ZmFmZmZlOTk5OTk5MCAXDTI2MTAxNzAwNDM1NloYDzIxMjYwOTIzMDA0MzU2
+WjA+MQswCQYDVQQGEwJOTDEUMBIGA1UECgwLUGhpbGlwcyBIdWUxGTAXBgNVBAMM
+EGVjYjVmYWZmZmU5OTk5OTkwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARfFwBY
+WtDVdmB2lVedSC2l6gbxDk4IG35g5yo7/KHhnF7JhMNI8GCszOhUtpYROpnJyDV+
++4ZGwYhQGPhMjHVVo1MwUTAdBgNVHQ4EFgQU/m9YhLxOQDt1/yL+hShUAjLVJBUw
+HwYDVR0jBBgwFoAU/m9YhLxOQDt1/yL+hShUAjLVJBUwDwYDVR0TAQH/BAUwAwEB
+/zAKBggqhkjOPQQDAgNIADBFAiEArXWSKyigE20CIik2tJXS/8TBtmzrEGZxEExf
+sp+FPxkCIEvXz7gttIjgRmQ6D+6S/twMAq3zJF0tI/mLSQhRUIzB
+-----END CERTIFICATE-----";
+    // signed by another CA
+    const UNKNOWN_ISSUER: &[u8] = b"-----BEGIN CERTIFICATE-----
+MIIBsjCCAVegAwIBAgIBCTAKBggqhkjOPQQDAjAyMQswCQYDVQQGEwJOTDEOMAwG
+A1UECgwFT3RoZXIxEzARBgNVBAMMCm90aGVyLXJvb3QwIBcNMjYxMDE3MDA0MzU2
+WhgPMjEyNjA5MjMwMDQzNTZaMD4xCzAJBgNVBAYTAk5MMRQwEgYDVQQKDAtQaGls
+aXBzIEh1ZTEZMBcGA1UEAwwQZWNiNWZhZmZmZTBhMWIyYzBZMBMGByqGSM49AgEG
+CCqGSM49AwEHA0IABF8XAFha0NV2YHaVV51ILaXqBvEOTggbfmDnKjv8oeGcXsmE
+w0jwYKzM6FS2lhE6mcnINX77hkbBiFAY+EyMdVWjUDBOMAwGA1UdEwEB/wQCMAAw
+HQYDVR0OBBYEFP5vWIS8TkA7df8i/oUoVAIy1SQVMB8GA1UdIwQYMBaAFASwoYaP
+Nb63LPgZHbKLZJ4T+/v2MAoGCCqGSM49BAMCA0kAMEYCIQDz2NE1YMQIjWmSL02t
+GWvHhkFScF9G2BIlrN06h8AknQIhAOE2dbneZD7LD2iTV1yQJjAVkgei9l1MU80Z
+UOVygb7Y
+-----END CERTIFICATE-----";
+
+    fn verify(bridge_id: Option<&str>, pem: &[u8], now: u64) -> Result<(), rustls::Error> {
+        let verifier = BridgeCertVerifier::new(bridge_id.map(String::from), TEST_ROOT_CA);
+        let cert = CertificateDer::from_pem_slice(pem).unwrap();
+        let name = ServerName::try_from("192.168.0.4").unwrap();
+        let now = UnixTime::since_unix_epoch(std::time::Duration::from_secs(now));
+        verifier
+            .verify_server_cert(&cert, &[], &name, &[], now)
+            .map(|_| ())
+    }
+
+    // 2030, and 2130 when all the certificates have expired
+    const NOW: u64 = 1_900_000_000;
+    const LATER: u64 = 5_000_000_000;
+
+    #[test]
+    fn certificates_signed_by_the_root_ca() {
+        assert!(verify(Some(BRIDGE_ID), SIGNED_BY_CA, NOW).is_ok());
+        assert!(verify(None, SIGNED_BY_CA, NOW).is_ok());
+        assert!(verify(Some("ecb5fafffe999999"), SIGNED_BY_CA, NOW).is_err());
+        assert!(verify(Some(BRIDGE_ID), SIGNED_BY_CA, LATER).is_err());
+    }
+
+    #[test]
+    fn self_signed_certificates() {
+        assert!(verify(Some(BRIDGE_ID), SELF_SIGNED, NOW).is_ok());
+        assert!(verify(Some(BRIDGE_ID), SELF_SIGNED_CA, NOW).is_ok());
+        // only trusted when the id of the bridge is known
+        assert!(verify(None, SELF_SIGNED, NOW).is_err());
+        assert!(verify(Some(BRIDGE_ID), SELF_SIGNED, LATER).is_err());
+        assert!(verify(Some(BRIDGE_ID), FORGED_SELF_SIGNED, NOW).is_err());
+    }
+
+    #[test]
+    fn certificate_times() {
+        assert_eq!(parse_time(0x17, b"700101000000Z"), Some(0));
+        assert_eq!(parse_time(0x17, b"000229123456Z"), Some(951827696));
+        assert_eq!(parse_time(0x18, b"20380119031407Z"), Some(2147483647));
+        assert_eq!(parse_time(0x18, b"20381319031407Z"), None);
+        assert_eq!(parse_time(0x17, b"700101000000"), None);
+    }
+
+    #[test]
+    fn certificates_for_another_bridge() {
+        assert!(matches!(
+            verify(Some(BRIDGE_ID), OTHER_BRIDGE, NOW),
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName
+            ))
+        ));
+    }
+
+    #[test]
+    fn certificates_from_an_unknown_issuer() {
+        assert!(verify(Some(BRIDGE_ID), UNKNOWN_ISSUER, NOW).is_err());
+        assert!(verify(None, UNKNOWN_ISSUER, NOW).is_err());
+    }
+
+    #[test]
+    fn certificate_names() {
+        let root = CertificateDer::from_pem_slice(HUE_ROOT_CA).unwrap();
+        assert_eq!(common_name(&root).as_deref(), Some("root-bridge"));
+        assert!(is_self_signed(&root));
+        assert_eq!(common_name(&root[..root.len() / 2]), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Checking commands against the capabilities of a light before sending them.
+use crate::{CommandLight, Effect, Light, TimedEffect};
+
+/// A reason for the bridge to reject a [`CommandLight`], found by [`CommandLight::validate`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum CommandError {
+    #[error("The light cannot be dimmed")]
+    NoDimming,
+    #[error("Brightness {} is below the minimum of {}", brightness, minimum)]
+    BrightnessBelowMinimum { brightness: f32, minimum: f32 },
+    #[error("Brightness {} is not a percentage", brightness)]
+    BrightnessOutOfRange { brightness: f32 },
+    #[error("The light has no color temperature")]
+    NoColorTemperature,
+    #[error(
+        "Color temperature {} is out of {}..={} mirek",
+        mirek,
+        minimum,
+        maximum
+    )]
+    MirekOutOfRange {
+        mirek: u16,
+        minimum: u16,
+        maximum: u16,
+    },
+    #[error("The light has no color")]
+    NoColor,
+    #[error("The light has no gradient")]
+    NoGradient,
+    #[error("The gradient has {} points, the light supports {}", points, capable)]
+    TooManyGradientPoints { points: usize, capable: u32 },
+    #[error("The light does not support the effect {:?}", effect)]
+    UnsupportedEffect { effect: Effect },
+    #[error("The light does not support the timed effect {:?}", effect)]
+    UnsupportedTimedEffect { effect: TimedEffect },
+}
+
+impl CommandLight {
+    /// Checks that `light` supports everything this command sets, to report errors without a
+    /// round trip to the bridge. Colors out of the gamut of the light are accepted, the bridge
+    /// brings them within it.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::CommandLight;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj");
+    /// let command = CommandLight::default().on().with_xy(0.675, 0.322);
+    /// for light in bridge.get_all_lights().await.unwrap() {
+    ///     match command.validate(&light) {
+    ///         Ok(()) => bridge.set_light_state(&light.id, &command).await.unwrap(),
+    ///         Err(e) => println!("skipping {}: {e}", light.metadata.name),
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub fn validate(&self, light: &Light) -> Result<(), CommandError> {
+        if let Some(dimming) = &self.dimming {
+            let capability = light.dimming.as_ref().ok_or(CommandError::NoDimming)?;
+            let brightness = dimming.brightness;
+            if !(0. ..=100.).contains(&brightness) {
+                return Err(CommandError::BrightnessOutOfRange { brightness });
+            }
+            let minimum = capability.min_dim_level.unwrap_or(0.);
+            if brightness < minimum {
+                return Err(CommandError::BrightnessBelowMinimum {
+                    brightness,
+                    minimum,
+                });
+            }
+        }
+        if self.dimming_delta.is_some() && light.dimming.is_none() {
+            return Err(CommandError::NoDimming);
+        }
+        if let Some(ct) = &self.color_temperature {
+            let capability = light
+                .color_temperature
+                .as_ref()
+                .ok_or(CommandError::NoColorTemperature)?;
+            let schema = &capability.mirek_schema;
+            if !(schema.mirek_minimum..=schema.mirek_maximum).contains(&ct.mirek) {
+                return Err(CommandError::MirekOutOfRange {
+                    mirek: ct.mirek,
+                    minimum: schema.mirek_minimum,
+                    maximum: schema.mirek_maximum,
+                });
+            }
+        }
+        if self.color_temperature_delta.is_some() && light.color_temperature.is_none() {
+            return Err(CommandError::NoColorTemperature);
+        }
+        if self.color.is_some() && light.color.is_none() {
+            return Err(CommandError::NoColor);
+        }
+        if let Some(gradient) = &self.gradient {
+            let capability = light.gradient.as_ref().ok_or(CommandError::NoGradient)?;
+            if gradient.points.len() > capability.points_capable as usize {
+                return Err(CommandError::TooManyGradientPoints {
+                    points: gradient.points.len(),
+                    capable: capability.points_capable,
+                });
+            }
+        }
+        if let Some(effects) = &self.effects {
+            let effect = &effects.effect;
+            let supported = light
+                .effects
+                .as_ref()
+                .is_some_and(|e| e.supports(effect.clone()));
+            if !supported && *effect != Effect::NoEffect {
+                return Err(CommandError::UnsupportedEffect {
+                    effect: effect.clone(),
+                });
+            }
+        }
+        if let Some(timed_effects) = &self.timed_effects {
+            let effect = &timed_effects.effect;
+            let supported = light
+                .timed_effects
+                .as_ref()
+                .is_some_and(|e| e.supports(effect.clone()));
+            if !supported && *effect != TimedEffect::NoEffect {
+                return Err(CommandError::UnsupportedTimedEffect {
+                    effect: effect.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn white_bulb() {
+        let light = crate::bridge::test_light(json!({
+            "dimming": { "brightness": 50.0, "min_dim_level": 2.0 }
+        }));
+        let on = CommandLight::default().on();
+        assert_eq!(on.clone().with_brightness(40.).validate(&light), Ok(()));
+        assert_eq!(
+            on.clone().with_brightness(1.).validate(&light),
+            Err(CommandError::BrightnessBelowMinimum {
+                brightness: 1.,
+                minimum: 2.
+            })
+        );
+        assert_eq!(
+            on.clone().with_xy(0.3, 0.3).validate(&light),
+            Err(CommandError::NoColor)
+        );
+        assert_eq!(
+            on.with_effect(Effect::Candle).validate(&light),
+            Err(CommandError::UnsupportedEffect {
+                effect: Effect::Candle
+            })
+        );
+    }
+}
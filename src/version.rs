@@ -0,0 +1,213 @@
+//! The configuration of a bridge, its firmware and API versions, and the features they support.
+use crate::{Bridge, BridgeResponse, UnauthBridge};
+use serde::{Deserialize, Serialize};
+
+/// A feature of the bridge API that older firmwares lack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// The CLIP v2 API, under `/clip/v2`.
+    ClipV2,
+    /// The server-sent event stream, see [`Bridge::events`].
+    EventStream,
+    /// Gradient light strips and lamps.
+    Gradient,
+    /// The `smart_scene` resource.
+    SmartScenes,
+    /// Sharing the lights with Matter controllers.
+    Matter,
+}
+
+impl Feature {
+    /// The first API version supporting this feature.
+    fn min_api_version(self) -> (u32, u32, u32) {
+        match self {
+            Feature::ClipV2 | Feature::EventStream | Feature::Gradient => (1, 46, 0),
+            Feature::SmartScenes => (1, 55, 0),
+            Feature::Matter => (1, 60, 0),
+        }
+    }
+}
+
+/// The versions reported by a bridge in its public configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BridgeVersion {
+    /// The API version, such as `1.65.0`.
+    #[serde(rename = "apiversion")]
+    pub api_version: String,
+    /// The firmware version, such as `1965111030`.
+    #[serde(rename = "swversion")]
+    pub sw_version: String,
+}
+
+impl BridgeVersion {
+    /// The API version as a (major, minor, patch) triplet, missing or invalid parts being 0.
+    pub fn api_version_triplet(&self) -> (u32, u32, u32) {
+        let mut parts = self
+            .api_version
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0));
+        (
+            parts.next().unwrap_or(0),
+            parts.next().unwrap_or(0),
+            parts.next().unwrap_or(0),
+        )
+    }
+
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.api_version_triplet() >= feature.min_api_version()
+    }
+}
+
+/// The configuration of a bridge. The fields after `sw_version` are only set when read with a
+/// valid application key, through [`Bridge::get_config`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BridgeConfig {
+    pub name: String,
+    /// The unique id of the bridge, such as `ECB5FAFFFE0A1B2C`.
+    #[serde(rename = "bridgeid")]
+    pub bridge_id: String,
+    /// The hardware model, `BSB002` for the square bridge.
+    #[serde(rename = "modelid")]
+    pub model_id: String,
+    #[serde(rename = "mac")]
+    pub mac_address: String,
+    #[serde(flatten)]
+    pub version: BridgeVersion,
+    #[serde(rename = "ipaddress")]
+    pub ip_address: Option<String>,
+    #[serde(rename = "zigbeechannel")]
+    pub zigbee_channel: Option<u8>,
+    pub timezone: Option<String>,
+}
+
+impl UnauthBridge {
+    /// Reads the public configuration of the bridge, which does not need an application key.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4]);
+    /// let config = bridge.get_config().await.unwrap();
+    /// println!("{} runs firmware {}", config.bridge_id, config.version.sw_version);
+    /// # })
+    /// ```
+    pub async fn get_config(&self) -> crate::Result<BridgeConfig> {
+        let url = format!("https://{}/api/0/config", self.host());
+        let resp: BridgeResponse<BridgeConfig> = self.client.get(&url).send().await?.json().await?;
+        resp.get()
+    }
+}
+
+impl Bridge {
+    /// Reads the full configuration of this `Bridge`.
+    pub async fn get_config(&self) -> crate::Result<BridgeConfig> {
+        let url = format!(
+            "https://{}/api/{}/config",
+            self.host(),
+            self.application_key
+        );
+        let resp: BridgeResponse<BridgeConfig> = self.send(self.client.get(&url)).await?;
+        resp.get()
+    }
+
+    /// Reads the firmware and API versions of this `Bridge`. This does not need an application
+    /// key.
+    pub async fn get_version(&self) -> crate::Result<BridgeVersion> {
+        let url = format!("https://{}/api/0/config", self.host());
+        let resp: BridgeResponse<BridgeVersion> = self.send(self.client.get(&url)).await?;
+        resp.get()
+    }
+
+    /// Reads the versions of this `Bridge` and remembers them, so that [`Bridge::supports`] can
+    /// tell which features are available.
+    /// ### Example
+    /// ```no_run
+    /// # tokio_test::block_on(async {
+    /// use hueclient::Feature;
+    /// let bridge = hueclient::Bridge::for_ip([192u8, 168, 0, 4])
+    ///    .with_user("rVV05G0i52vQMMLn6BK3dpr0F3uDiqtDjPLPK2uj")
+    ///    .negotiate_version()
+    ///    .await
+    ///    .unwrap();
+    /// if !bridge.supports(Feature::EventStream) {
+    ///     println!("this bridge is too old for the event stream, falling back to polling");
+    /// }
+    /// # })
+    /// ```
+    pub async fn negotiate_version(self) -> crate::Result<Bridge> {
+        let version = self.get_version().await?;
+        Ok(Bridge {
+            version: Some(version),
+            ..self
+        })
+    }
+
+    /// The versions read by [`Bridge::negotiate_version`], if it was called.
+    pub fn version(&self) -> Option<&BridgeVersion> {
+        self.version.as_ref()
+    }
+
+    /// Whether this `Bridge` supports `feature`. Until [`Bridge::negotiate_version`] is called,
+    /// all features are assumed to be supported.
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.version
+            .as_ref()
+            .is_none_or(|version| version.supports(feature))
+    }
+
+    /// Returns `HueError::UnsupportedFeature` if this `Bridge` is known not to support `feature`.
+    pub fn require(&self, feature: Feature) -> crate::Result<()> {
+        match &self.version {
+            Some(version) if !version.supports(feature) => {
+                Err(crate::HueError::UnsupportedFeature {
+                    msg: format!(
+                        "{feature:?} is not supported by API version {}",
+                        version.api_version
+                    ),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_and_full_config() {
+        let public = serde_json::json!({
+            "name": "Philips hue",
+            "datastoreversion": "172",
+            "swversion": "1967054020",
+            "apiversion": "1.67.0",
+            "mac": "ec:b5:fa:0a:1b:2c",
+            "bridgeid": "ECB5FAFFFE0A1B2C",
+            "factorynew": false,
+            "replacesbridgeid": null,
+            "modelid": "BSB002",
+            "starterkitid": ""
+        });
+        let config: BridgeConfig = serde_json::from_value(public.clone()).unwrap();
+        assert_eq!(config.version.api_version, "1.67.0");
+        assert_eq!(config.zigbee_channel, None);
+        let mut full = public;
+        full["zigbeechannel"] = 25.into();
+        full["ipaddress"] = "192.168.0.4".into();
+        let config: BridgeConfig = serde_json::from_value(full).unwrap();
+        assert_eq!(config.zigbee_channel, Some(25));
+    }
+
+    #[test]
+    fn features_by_api_version() {
+        let version = |api_version: &str| BridgeVersion {
+            api_version: api_version.into(),
+            sw_version: "0".into(),
+        };
+        assert_eq!(version("1.65.0").api_version_triplet(), (1, 65, 0));
+        assert!(!version("1.45.0").supports(Feature::ClipV2));
+        assert!(version("1.46.0").supports(Feature::EventStream));
+        assert!(!version("1.50.0").supports(Feature::SmartScenes));
+        assert!(version("1.65.0").supports(Feature::Matter));
+    }
+}